@@ -0,0 +1,196 @@
+//! Measure how much memory and time the game history costs per round.
+//!
+//! Every step of a game is its own generation in the history.  Generations share whatever did not
+//! change from the one before, the cards in the shoe included.  This compares that history with
+//! the one kept before anything was shared, where every generation owned a copy of every part of
+//! its state and its shoe owned every card left to deal and every card in the discard tray.
+//!
+//! Run with `cargo bench -p blackjack --bench history`.
+
+use blackjack::prelude::{GameState, Halt, History, MimicTheDealer, Progress, Rules, TableRules};
+use cards::prelude::{Card, HasCards, Shoe};
+use player::{House, Seat};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+/// Keeps count of how many bytes are allocated right now.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// The shoe as it was before shoes shared their cards.  It is only kept to be measured, so
+/// nothing reads it.
+#[allow(dead_code)]
+struct CopiedShoe {
+    cards: Vec<Card>,
+    discards: Vec<Card>,
+    cut_card: usize,
+}
+
+/// A copy of the state which shares nothing, the way every generation kept its state before.  The
+/// parts of the state still sit behind their own references, which costs a few bytes more than
+/// before.
+fn copied(state: &GameState, cut_card: usize) -> GameState<CopiedShoe> {
+    let mut shoe = state.shoe.clone();
+    let mut cards = Vec::with_capacity(shoe.cards_left());
+    cards.extend(std::iter::from_fn(|| shoe.deal()));
+    cards.reverse();
+
+    GameState {
+        progress: state.progress.clone(),
+        house: House::clone(&state.house).into(),
+        players: state.players.to_vec().into(),
+        seats: state
+            .seats
+            .iter()
+            .map(|seat| Seat::clone(seat).into())
+            .collect(),
+        shoe: CopiedShoe {
+            cards,
+            // Only the room the tray takes matters here, not which cards are in it.
+            discards: Vec::with_capacity(state.shoe.discards()),
+            cut_card,
+        },
+        turn: state.turn,
+        results: state.results.to_vec().into(),
+        insurance: state.insurance.to_vec().into(),
+        ledger: state.ledger.clone(),
+        seed: state.seed,
+        shuffles: state.shuffles,
+    }
+}
+
+const ROUNDS: usize = 2_000;
+const PLAYERS: usize = 3;
+const SEED: u64 = 2022;
+
+/// What one run of the benchmark measured.
+struct Measure {
+    generations: usize,
+    bytes: usize,
+    seconds: f64,
+}
+
+/// Copy every generation which is not in the copies yet into them, the way the history kept
+/// them before.  This gives how many more bytes the copies hold.
+///
+/// * `history`: The history being played
+/// * `copies`: Every generation copied so far
+/// * `cut_card`: Where the cut card is placed in the shoe
+fn copy_new(history: &History, copies: &mut Option<History<CopiedShoe>>, cut_card: usize) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let from = copies.as_ref().map_or(0, History::number_of_generations);
+    for id in from..history.number_of_generations() {
+        let generation = history.get(id).unwrap();
+        let state = copied(generation.state(), cut_card);
+        match copies {
+            None => *copies = Some(History::new(state)),
+            Some(copies) => copies.add_generation_with_events(state, generation.events().to_vec()),
+        }
+    }
+    ALLOCATED.load(Ordering::Relaxed) - before
+}
+
+/// Play the rounds with every seat mimicking the dealer, keeping the whole history.  When copying,
+/// every generation is also copied into a history which shares nothing, and only those copies are
+/// measured.  The time is for playing and copying together, as every step used to copy the whole
+/// state.
+///
+/// * `table`: The table to play at
+/// * `copy`: Whether to measure copies of every generation instead of the history itself
+fn play(table: &TableRules, copy: bool) -> Measure {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    let mut rules = Rules::<Shoe>::with_seed(table.clone(), SEED);
+    for seat in 0..PLAYERS {
+        rules.add_player().unwrap();
+        rules.buy_in(seat, 1_000_000).unwrap();
+    }
+    rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+
+    let mut copies = None;
+    let mut copied_bytes = 0;
+    let mut played = 0;
+    while played < ROUNDS {
+        match rules.next() {
+            Some(state) if state.progress == Progress::Done => played += 1,
+            Some(_) => (),
+            None if rules.halted() == Some(&Halt::Reshuffle) => rules.reshuffle().unwrap(),
+            None => panic!("The game stopped early: {:?}", rules.halted()),
+        }
+        if copy {
+            copied_bytes += copy_new(rules.history(), &mut copies, table.reshuffle_at());
+        }
+    }
+
+    let seconds = start.elapsed().as_secs_f64();
+    let bytes = if copy {
+        copied_bytes
+    } else {
+        ALLOCATED.load(Ordering::Relaxed) - before
+    };
+    let generations = rules.history().number_of_generations();
+    drop(copies);
+    drop(rules);
+    Measure {
+        generations,
+        bytes,
+        seconds,
+    }
+}
+
+fn report(name: &str, measure: &Measure) {
+    println!(
+        "{:<8} {:>6} generations {:>10.1} KiB/round {:>8.1} µs/round",
+        name,
+        measure.generations,
+        measure.bytes as f64 / 1024.0 / ROUNDS as f64,
+        measure.seconds * 1e6 / ROUNDS as f64,
+    );
+}
+
+fn main() {
+    let table = TableRules {
+        decks: 7,
+        ..TableRules::vegas_strip()
+    };
+    println!(
+        "{} rounds, {} players, {} decks",
+        ROUNDS, PLAYERS, table.decks
+    );
+
+    let shared = play(&table, false);
+    let copied = play(&table, true);
+    report("shared", &shared);
+    report("copied", &copied);
+    println!(
+        "shared uses {:.1}% of the memory and {:.1}% of the time",
+        100.0 * shared.bytes as f64 / copied.bytes as f64,
+        100.0 * shared.seconds / copied.seconds,
+    );
+}
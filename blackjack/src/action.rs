@@ -0,0 +1,34 @@
+/// Action is every decision a player can make on their turn.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Surrender,
+}
+
+impl Action {
+    /// Every action in the order they are usually offered.
+    pub const ALL: [Action; 5] = [
+        Action::Hit,
+        Action::Stand,
+        Action::Double,
+        Action::Split,
+        Action::Surrender,
+    ];
+}
+
+/// Action needs a standard way to be printed out.  That's what Display is for.
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Hit => write!(f, "Hit"),
+            Action::Stand => write!(f, "Stand"),
+            Action::Double => write!(f, "Double"),
+            Action::Split => write!(f, "Split"),
+            Action::Surrender => write!(f, "Surrender"),
+        }
+    }
+}
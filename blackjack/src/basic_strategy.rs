@@ -0,0 +1,641 @@
+use crate::{
+    action::Action,
+    game_state::{GameState, Insurance},
+    strategy::{flat_bet, Decision, Strategy},
+    table_rules::{SurrenderRule, TableRules},
+};
+use cards::prelude::CardSource;
+use player::{Chips, HandleCards};
+
+/// Play is a single cell of a basic strategy chart.  Doubling and surrendering are not always
+/// allowed, so those cells say what to do instead.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Play {
+    Hit,
+    Stand,
+    DoubleOrHit,
+    DoubleOrStand,
+    Split,
+    SurrenderOrHit,
+    SurrenderOrStand,
+    SurrenderOrSplit,
+}
+
+impl Play {
+    /// Turn the play into an action the seat is allowed to take.
+    pub fn resolve(&self, legal_actions: &[Action]) -> Action {
+        let can = |action| legal_actions.contains(&action);
+        let action = match self {
+            Play::Hit => Action::Hit,
+            Play::Stand => Action::Stand,
+            Play::DoubleOrHit if can(Action::Double) => Action::Double,
+            Play::DoubleOrHit => Action::Hit,
+            Play::DoubleOrStand if can(Action::Double) => Action::Double,
+            Play::DoubleOrStand => Action::Stand,
+            Play::Split => Action::Split,
+            Play::SurrenderOrHit if can(Action::Surrender) => Action::Surrender,
+            Play::SurrenderOrHit => Action::Hit,
+            Play::SurrenderOrStand if can(Action::Surrender) => Action::Surrender,
+            Play::SurrenderOrStand => Action::Stand,
+            Play::SurrenderOrSplit if can(Action::Surrender) => Action::Surrender,
+            Play::SurrenderOrSplit => Action::Split,
+        };
+
+        if can(action) {
+            action
+        } else {
+            Action::Stand
+        }
+    }
+}
+
+/// Play is printed the way published charts write it.
+impl std::fmt::Display for Play {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Play::Hit => write!(f, "H"),
+            Play::Stand => write!(f, "S"),
+            Play::DoubleOrHit => write!(f, "D"),
+            Play::DoubleOrStand => write!(f, "Ds"),
+            Play::Split => write!(f, "P"),
+            Play::SurrenderOrHit => write!(f, "Rh"),
+            Play::SurrenderOrStand => write!(f, "Rs"),
+            Play::SurrenderOrSplit => write!(f, "Rp"),
+        }
+    }
+}
+
+/// The house up cards in the order a chart lists them, two through ace.  An ace counts as 11.
+const UP_CARDS: [i32; 10] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+/// A basic strategy chart for one set of table rules.  The chart is worked out from the number of
+/// decks, whether the house hits soft 17, double after split, surrender and the hole card.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ Chart, Play, TableRules };
+///
+/// let chart = Chart::new(&TableRules::atlantic_city());
+///
+/// assert_eq!(Play::DoubleOrHit, chart.hard(11, 10));
+/// assert_eq!(Play::SurrenderOrHit, chart.hard(16, 10));
+/// assert_eq!(Some(Play::Split), chart.pair(8, 10));
+/// assert_eq!("S Ds Ds Ds Ds S S H H H", chart.soft_row(18));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    hard: [[Play; 10]; 22],
+    soft: [[Play; 10]; 22],
+    pairs: [[Option<Play>; 10]; 11],
+}
+
+impl Chart {
+    /// Work out the chart for the given table rules.
+    pub fn new(table: &TableRules) -> Self {
+        let mut chart = Chart {
+            hard: [[Play::Stand; 10]; 22],
+            soft: [[Play::Stand; 10]; 22],
+            pairs: [[None; 10]; 11],
+        };
+
+        for (column, &up) in UP_CARDS.iter().enumerate() {
+            for total in 4..=21 {
+                chart.hard[total as usize][column] = hard_play(total, up, table);
+            }
+            for total in 12..=21 {
+                chart.soft[total as usize][column] = soft_play(total, up, table);
+            }
+            for value in 1..=10 {
+                chart.pairs[value as usize][column] = pair_play(value, up, table);
+            }
+        }
+        chart
+    }
+
+    /// The play for a hard total against the house up card.  An ace up card is 1 or 11.
+    pub fn hard(&self, total: i32, up: i32) -> Play {
+        self.hard[total.clamp(4, 21) as usize][column(up)]
+    }
+
+    /// The play for a soft total against the house up card.
+    pub fn soft(&self, total: i32, up: i32) -> Play {
+        self.soft[total.clamp(12, 21) as usize][column(up)]
+    }
+
+    /// The play for a pair of cards with the given value, when it should be split.
+    pub fn pair(&self, value: i32, up: i32) -> Option<Play> {
+        self.pairs[value.clamp(1, 10) as usize][column(up)]
+    }
+
+    /// The play for a hand against the house up card, given what the seat is allowed to do.
+    pub fn decide<S>(&self, decision: &Decision<S>) -> Action {
+        let hand = decision.hand;
+        let up = decision.dealer_up_card.rank().blackjack_value();
+
+        if hand.is_pair() && decision.legal_actions.contains(&Action::Split) {
+            let value = hand
+                .show_card()
+                .map(|card| card.rank().blackjack_value())
+                .unwrap_or(0);
+            if let Some(play) = self.pair(value, up) {
+                return play.resolve(decision.legal_actions);
+            }
+        }
+
+        let value = hand.value();
+        let play = if value.soft {
+            self.soft(value.total, up)
+        } else {
+            self.hard(value.total, up)
+        };
+        play.resolve(decision.legal_actions)
+    }
+
+    /// A row of the hard chart, two through ace, written the way published charts write it.
+    pub fn hard_row(&self, total: i32) -> String {
+        row(UP_CARDS.iter().map(|&up| self.hard(total, up)))
+    }
+
+    /// A row of the soft chart, two through ace.
+    pub fn soft_row(&self, total: i32) -> String {
+        row(UP_CARDS.iter().map(|&up| self.soft(total, up)))
+    }
+
+    /// A row of the pair chart, two through ace.  When a pair is not split it is played by its
+    /// total.
+    pub fn pair_row(&self, value: i32) -> String {
+        row(UP_CARDS.iter().map(|&up| match self.pair(value, up) {
+            Some(play) => play,
+            None if value == 1 => self.soft(12, up),
+            None => self.hard(value * 2, up),
+        }))
+    }
+}
+
+/// Charts print as three tables, one row for each total and one column for each up card.
+impl std::fmt::Display for Chart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Hard")?;
+        for total in 5..=17 {
+            writeln!(f, "{:>4}  {}", total, self.hard_row(total))?;
+        }
+        writeln!(f, "Soft")?;
+        for total in 13..=20 {
+            writeln!(f, "{:>4}  {}", total, self.soft_row(total))?;
+        }
+        writeln!(f, "Pairs")?;
+        for value in (1..=10).rev() {
+            writeln!(f, "{:>4}  {}", value, self.pair_row(value))?;
+        }
+        Ok(())
+    }
+}
+
+/// The chart column for an up card.
+fn column(up: i32) -> usize {
+    match up {
+        1 | 11 => 9,
+        up => (up.clamp(2, 10) - 2) as usize,
+    }
+}
+
+fn row(plays: impl Iterator<Item = Play>) -> String {
+    plays
+        .map(|play| play.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Hard totals.  The base chart is for four or more decks with the house standing on soft 17,
+/// and then adjusted for the table.
+fn hard_play(total: i32, up: i32, table: &TableRules) -> Play {
+    let few_decks = table.decks <= 2;
+    let h17 = table.dealer_hits_soft_17;
+
+    if let Some(play) = surrender_play(total, up, table) {
+        return play;
+    }
+
+    match total {
+        8 if table.decks == 1 && (5..=6).contains(&up) => Play::DoubleOrHit,
+        9 if (3..=6).contains(&up) || (few_decks && up == 2) => Play::DoubleOrHit,
+        10 if up <= 9 => Play::DoubleOrHit,
+        11 if up <= 9 => Play::DoubleOrHit,
+        11 if up == 10 && table.hole_card => Play::DoubleOrHit,
+        11 if up == 11 && table.hole_card && (h17 || few_decks) => Play::DoubleOrHit,
+        12 if (4..=6).contains(&up) => Play::Stand,
+        13..=16 if up <= 6 => Play::Stand,
+        17..=21 => Play::Stand,
+        _ => Play::Hit,
+    }
+}
+
+/// Soft totals, where an ace is counted as eleven.
+fn soft_play(total: i32, up: i32, table: &TableRules) -> Play {
+    let h17 = table.dealer_hits_soft_17;
+    let single_deck = table.decks == 1;
+
+    match total {
+        13 | 14 if (5..=6).contains(&up) || (single_deck && up == 4) => Play::DoubleOrHit,
+        15 | 16 if (4..=6).contains(&up) => Play::DoubleOrHit,
+        17 if (3..=6).contains(&up) || (single_deck && up == 2) => Play::DoubleOrHit,
+        18 if (3..=6).contains(&up) || (h17 && up == 2) => Play::DoubleOrStand,
+        18 if up <= 8 || (single_deck && !h17 && up == 11) => Play::Stand,
+        19 if up == 6 && (h17 || single_deck) => Play::DoubleOrStand,
+        19..=21 => Play::Stand,
+        _ => Play::Hit,
+    }
+}
+
+/// Pairs which should be split.  Pairs which are not split are played by their total.
+fn pair_play(value: i32, up: i32, table: &TableRules) -> Option<Play> {
+    let das = table.double_after_split;
+    let few_decks = table.decks <= 2;
+    let ace = up == 11;
+
+    let split = match value {
+        1 => table.hole_card || !ace,
+        8 if !table.hole_card => up <= 9,
+        8 if ace && table.dealer_hits_soft_17 && table.surrender != SurrenderRule::NoSurrender => {
+            return Some(Play::SurrenderOrSplit);
+        }
+        8 => true,
+        9 => matches!(up, 2..=6 | 8 | 9),
+        7 => up <= 7 || (few_decks && das && up == 8),
+        6 if das => up <= 6 || (few_decks && up == 7),
+        6 => (3..=6).contains(&up),
+        4 => das && (5..=6).contains(&up),
+        2 | 3 if das => up <= 7,
+        2 | 3 => (4..=7).contains(&up),
+        _ => false,
+    };
+
+    if split {
+        Some(Play::Split)
+    } else {
+        None
+    }
+}
+
+/// Hard totals which should be surrendered when the table allows it.
+fn surrender_play(total: i32, up: i32, table: &TableRules) -> Option<Play> {
+    let ace = up == 11;
+    let h17 = table.dealer_hits_soft_17;
+
+    let surrender = match table.surrender {
+        SurrenderRule::NoSurrender => false,
+        SurrenderRule::Late => match total {
+            16 => up == 10 || ace || (up == 9 && table.decks > 2),
+            15 => up == 10 || (ace && h17),
+            17 => ace && h17,
+            _ => false,
+        },
+        SurrenderRule::Early => {
+            let against_ace = matches!(total, 5..=7 | 12..=17) && ace;
+            let against_ten = matches!(total, 14..=16) && up == 10;
+            against_ace || against_ten
+        }
+    };
+
+    match surrender {
+        true if total >= 17 => Some(Play::SurrenderOrStand),
+        true => Some(Play::SurrenderOrHit),
+        false => None,
+    }
+}
+
+/// BasicStrategy plays every hand by the basic strategy chart for the table in effect and bets
+/// the same amount every game.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ BasicStrategy, Rules, TableRules };
+///
+/// let mut rule = Rules::new(TableRules::downtown_vegas());
+/// rule.add_player();
+/// rule.buy_in(0, 1000);
+/// rule.set_strategy(Box::new(BasicStrategy::new(10)));
+///
+/// assert_eq!(50, rule.by_ref().take(50).count());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BasicStrategy {
+    pub bet: Chips,
+    chart: Option<(TableRules, Chart)>,
+}
+
+impl BasicStrategy {
+    /// Create a basic strategy player which bets the same amount every game.
+    ///
+    /// * `bet`: The bet for every game
+    pub fn new(bet: Chips) -> Self {
+        BasicStrategy { bet, chart: None }
+    }
+
+    /// The chart for the given table.  The chart is only worked out again when the table
+    /// changes.
+    pub fn chart(&mut self, table: &TableRules) -> &Chart {
+        let stale = match &self.chart {
+            Some((rules, _)) => rules != table,
+            None => true,
+        };
+        if stale {
+            self.chart = Some((table.clone(), Chart::new(table)));
+        }
+
+        &self
+            .chart
+            .as_ref()
+            .expect("The chart was just worked out")
+            .1
+    }
+}
+
+impl<S: CardSource> Strategy<S> for BasicStrategy {
+    fn bet(&mut self, seat: usize, state: &GameState<S>, table: &TableRules) -> Chips {
+        flat_bet(self.bet, seat, state, table)
+    }
+
+    fn act(&mut self, decision: &Decision<S>) -> Action {
+        self.chart(decision.table).decide(decision)
+    }
+
+    /// Insurance is always declined.  Early surrender is decided by the chart, the same as it
+    /// would be on the first two cards.
+    fn insure(&mut self, seat: usize, state: &GameState<S>, table: &TableRules) -> Insurance {
+        let dealer_up_card = match state.dealer_up_card() {
+            Some(card) if table.surrender == SurrenderRule::Early => card,
+            _ => return Insurance::Declined,
+        };
+        let decision = Decision {
+            seat,
+            hand: &state.seats[seat].hands[0],
+            dealer_up_card,
+            legal_actions: &[Action::Hit, Action::Stand, Action::Surrender],
+            table,
+            state,
+        };
+        match self.chart(table).decide(&decision) {
+            Action::Surrender => Insurance::Surrendered,
+            _ => Insurance::Declined,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BasicStrategy, Chart, Play};
+    use crate::{
+        action::Action,
+        rules::{Halt, Rules},
+        table_rules::{SurrenderRule, TableRules},
+    };
+
+    /// Four to eight decks, the house stands on soft 17, double after split and late surrender.
+    fn multi_deck() -> TableRules {
+        TableRules {
+            decks: 6,
+            ..TableRules::vegas_strip()
+        }
+    }
+
+    fn assert_rows(rows: &[(i32, &str)], row: impl Fn(i32) -> String) {
+        for (total, expected) in rows {
+            assert_eq!(*expected, row(*total), "row {}", total);
+        }
+    }
+
+    #[test]
+    fn multi_deck_stand_on_soft_17() {
+        let chart = Chart::new(&multi_deck());
+
+        assert_rows(
+            &[
+                (8, "H H H H H H H H H H"),
+                (9, "H D D D D H H H H H"),
+                (10, "D D D D D D D D H H"),
+                (11, "D D D D D D D D D H"),
+                (12, "H H S S S H H H H H"),
+                (13, "S S S S S H H H H H"),
+                (14, "S S S S S H H H H H"),
+                (15, "S S S S S H H H Rh H"),
+                (16, "S S S S S H H Rh Rh Rh"),
+                (17, "S S S S S S S S S S"),
+            ],
+            |total| chart.hard_row(total),
+        );
+        assert_rows(
+            &[
+                (13, "H H H D D H H H H H"),
+                (14, "H H H D D H H H H H"),
+                (15, "H H D D D H H H H H"),
+                (16, "H H D D D H H H H H"),
+                (17, "H D D D D H H H H H"),
+                (18, "S Ds Ds Ds Ds S S H H H"),
+                (19, "S S S S S S S S S S"),
+                (20, "S S S S S S S S S S"),
+            ],
+            |total| chart.soft_row(total),
+        );
+        assert_rows(
+            &[
+                (1, "P P P P P P P P P P"),
+                (10, "S S S S S S S S S S"),
+                (9, "P P P P P S P P S S"),
+                (8, "P P P P P P P P P P"),
+                (7, "P P P P P P H H H H"),
+                (6, "P P P P P H H H H H"),
+                (5, "D D D D D D D D H H"),
+                (4, "H H H P P H H H H H"),
+                (3, "P P P P P P H H H H"),
+                (2, "P P P P P P H H H H"),
+            ],
+            |value| chart.pair_row(value),
+        );
+    }
+
+    #[test]
+    fn multi_deck_hit_soft_17() {
+        let chart = Chart::new(&TableRules {
+            dealer_hits_soft_17: true,
+            ..multi_deck()
+        });
+
+        assert_eq!("D D D D D D D D D D", chart.hard_row(11));
+        assert_eq!("S S S S S H H H Rh Rh", chart.hard_row(15));
+        assert_eq!("S S S S S S S S S Rs", chart.hard_row(17));
+        assert_eq!("Ds Ds Ds Ds Ds S S H H H", chart.soft_row(18));
+        assert_eq!("S S S S Ds S S S S S", chart.soft_row(19));
+        assert_eq!("P P P P P P P P P Rp", chart.pair_row(8));
+    }
+
+    #[test]
+    fn no_double_after_split() {
+        let chart = Chart::new(&TableRules {
+            double_after_split: false,
+            ..multi_deck()
+        });
+
+        assert_eq!("H H P P P P H H H H", chart.pair_row(2));
+        assert_eq!("H H P P P P H H H H", chart.pair_row(3));
+        assert_eq!("H H H H H H H H H H", chart.pair_row(4));
+        assert_eq!("H P P P P H H H H H", chart.pair_row(6));
+    }
+
+    #[test]
+    fn few_decks() {
+        let double_deck = Chart::new(&TableRules {
+            decks: 2,
+            ..multi_deck()
+        });
+        assert_eq!("D D D D D H H H H H", double_deck.hard_row(9));
+        assert_eq!("D D D D D D D D D D", double_deck.hard_row(11));
+        assert_eq!("S S S S S H H H Rh Rh", double_deck.hard_row(16));
+        assert_eq!("P P P P P P H H H H", double_deck.pair_row(6));
+
+        let single_deck = Chart::new(&TableRules {
+            decks: 1,
+            ..multi_deck()
+        });
+        assert_eq!("H H H D D H H H H H", single_deck.hard_row(8));
+    }
+
+    /// The soft charts for one and two decks, as published for each house soft 17 rule.
+    #[test]
+    fn few_deck_soft_charts() {
+        let chart = |decks, dealer_hits_soft_17| {
+            Chart::new(&TableRules {
+                decks,
+                dealer_hits_soft_17,
+                ..multi_deck()
+            })
+        };
+
+        let single_deck = chart(1, false);
+        assert_rows(
+            &[
+                (13, "H H D D D H H H H H"),
+                (14, "H H D D D H H H H H"),
+                (15, "H H D D D H H H H H"),
+                (16, "H H D D D H H H H H"),
+                (17, "D D D D D H H H H H"),
+                (18, "S Ds Ds Ds Ds S S H H S"),
+                (19, "S S S S Ds S S S S S"),
+                (20, "S S S S S S S S S S"),
+            ],
+            |total| single_deck.soft_row(total),
+        );
+
+        let single_deck_h17 = chart(1, true);
+        assert_rows(
+            &[
+                (13, "H H D D D H H H H H"),
+                (14, "H H D D D H H H H H"),
+                (15, "H H D D D H H H H H"),
+                (16, "H H D D D H H H H H"),
+                (17, "D D D D D H H H H H"),
+                (18, "Ds Ds Ds Ds Ds S S H H H"),
+                (19, "S S S S Ds S S S S S"),
+                (20, "S S S S S S S S S S"),
+            ],
+            |total| single_deck_h17.soft_row(total),
+        );
+
+        let double_deck = chart(2, false);
+        assert_rows(
+            &[
+                (13, "H H H D D H H H H H"),
+                (14, "H H H D D H H H H H"),
+                (15, "H H D D D H H H H H"),
+                (16, "H H D D D H H H H H"),
+                (17, "H D D D D H H H H H"),
+                (18, "S Ds Ds Ds Ds S S H H H"),
+                (19, "S S S S S S S S S S"),
+                (20, "S S S S S S S S S S"),
+            ],
+            |total| double_deck.soft_row(total),
+        );
+
+        let double_deck_h17 = chart(2, true);
+        assert_rows(
+            &[
+                (13, "H H H D D H H H H H"),
+                (14, "H H H D D H H H H H"),
+                (15, "H H D D D H H H H H"),
+                (16, "H H D D D H H H H H"),
+                (17, "H D D D D H H H H H"),
+                (18, "Ds Ds Ds Ds Ds S S H H H"),
+                (19, "S S S S Ds S S S S S"),
+                (20, "S S S S S S S S S S"),
+            ],
+            |total| double_deck_h17.soft_row(total),
+        );
+    }
+
+    #[test]
+    fn surrender_options() {
+        let none = Chart::new(&TableRules {
+            surrender: SurrenderRule::NoSurrender,
+            ..multi_deck()
+        });
+        assert_eq!("S S S S S H H H H H", none.hard_row(16));
+
+        let early = Chart::new(&TableRules {
+            surrender: SurrenderRule::Early,
+            ..multi_deck()
+        });
+        assert_eq!("H H H H H H H H H Rh", early.hard_row(7));
+        assert_eq!("S S S S S H H H Rh Rh", early.hard_row(14));
+        assert_eq!("S S S S S S S S S Rs", early.hard_row(17));
+    }
+
+    #[test]
+    fn no_hole_card() {
+        let chart = Chart::new(&TableRules::european());
+        assert_eq!("D D D D D D D D H H", chart.hard_row(11));
+        assert_eq!("P P P P P P P P P H", chart.pair_row(1));
+        assert_eq!("P P P P P P P P H H", chart.pair_row(8));
+    }
+
+    #[test]
+    fn plays_fall_back_when_not_allowed() {
+        let hit_stand = [Action::Hit, Action::Stand];
+        assert_eq!(Action::Hit, Play::DoubleOrHit.resolve(&hit_stand));
+        assert_eq!(Action::Stand, Play::DoubleOrStand.resolve(&hit_stand));
+        assert_eq!(Action::Hit, Play::SurrenderOrHit.resolve(&hit_stand));
+        assert_eq!(Action::Stand, Play::Split.resolve(&hit_stand));
+
+        let everything = [
+            Action::Hit,
+            Action::Stand,
+            Action::Double,
+            Action::Split,
+            Action::Surrender,
+        ];
+        assert_eq!(Action::Double, Play::DoubleOrStand.resolve(&everything));
+        assert_eq!(
+            Action::Surrender,
+            Play::SurrenderOrSplit.resolve(&everything)
+        );
+    }
+
+    #[test]
+    fn only_makes_legal_plays() {
+        for table in [
+            TableRules::vegas_strip(),
+            TableRules::downtown_vegas(),
+            TableRules::atlantic_city(),
+            TableRules::european(),
+        ] {
+            let mut rules = Rules::new(table);
+            rules.add_player().unwrap();
+            rules.add_player().unwrap();
+            rules.buy_in(0, 100_000).unwrap();
+            rules.buy_in(1, 100_000).unwrap();
+            rules.set_strategy(Box::new(BasicStrategy::new(10)));
+
+            while rules.next().is_some() {}
+            assert_eq!(Some(&Halt::Reshuffle), rules.halted());
+        }
+    }
+}
@@ -1,8 +1,80 @@
-use crate::game_state::Progress;
+use crate::{action::Action, game_state::Progress};
+use player::Chips;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
-pub enum RuleError<'a> {
+#[derive(Error, Debug, PartialEq)]
+pub enum RuleError {
     #[error("Game state is in {0}.")]
-    InvalidState(&'a Progress),
+    InvalidState(Progress),
+    #[error("There is no seat {0}.")]
+    NoSuchSeat(usize),
+    #[error("There is no player {0}.")]
+    NoSuchPlayer(usize),
+    #[error("It is not seat {0}'s turn.")]
+    NotYourTurn(usize),
+    #[error("Seat {seat} can not {action}: {reason}.")]
+    IllegalAction {
+        seat: usize,
+        action: Action,
+        reason: &'static str,
+    },
+    #[error("A buy-in has to be more than zero chips, not {0}.")]
+    InvalidBuyIn(Chips),
+    #[error("A bet of {bet} is outside the table limits of {min} to {max}.")]
+    BetOutOfRange { bet: Chips, min: Chips, max: Chips },
+    #[error("Seat {seat} needs {needed} chips but only has {available}.")]
+    NotEnoughChips {
+        seat: usize,
+        needed: Chips,
+        available: Chips,
+    },
+    #[error("Seat {seat} can insure for at most {max}, not {chips}.")]
+    InsuranceOutOfRange {
+        seat: usize,
+        chips: Chips,
+        max: Chips,
+    },
+    #[error("Seat {0} needs a blackjack to take even money.")]
+    NoBlackjack(usize),
+    #[error("Seat {0} was not offered insurance.")]
+    InsuranceNotOffered(usize),
+    #[error("Players are still taking their turns.")]
+    PlayersStillActing,
+    #[error("The shoe is out of cards.")]
+    OutOfCards,
+    #[error("There is nothing to undo.")]
+    NothingToUndo,
+    #[error("There is nothing to redo.")]
+    NothingToRedo,
+    #[error("There is no generation {0} on this line of the history.")]
+    NoSuchGeneration(usize),
+}
+
+/// Why a hand history could not be read or replayed.
+#[derive(Error, Debug, PartialEq)]
+pub enum HandHistoryError {
+    #[error("Line {line} of the hand history could not be read: {text}")]
+    Unreadable { line: usize, text: String },
+    #[error("The rules refused \"{event}\": {error}")]
+    Refused { event: String, error: RuleError },
+    #[error("The replay went differently at line {line}: expected \"{expected}\" but found \"{found}\".")]
+    Mismatch {
+        line: usize,
+        expected: String,
+        found: String,
+    },
+}
+
+/// Why a game could not be saved or loaded.
+#[cfg(feature = "serde")]
+#[derive(Error, Debug)]
+pub enum SaveError {
+    #[error("Could not read or write the save: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("This is not a blackjack save.")]
+    NotASave,
+    #[error("The save is format version {0}, which is newer than this game understands.")]
+    UnsupportedVersion(u32),
+    #[error("The save is corrupt: {0}.")]
+    Corrupt(String),
 }
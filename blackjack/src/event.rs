@@ -0,0 +1,253 @@
+use crate::game_state::{GameState, Outcome};
+use cards::prelude::{Card, Shoe};
+use player::Chips;
+
+/// Who a card was dealt to.  Seats and hands are counted from 0, and a seat's hands are in the
+/// order they are played.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Recipient {
+    Seat { seat: usize, hand: usize },
+    House,
+}
+
+/// Recipient needs a standard way to be printed out.  That's what Display is for.
+impl std::fmt::Display for Recipient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recipient::Seat { seat, hand } => write!(f, "seat {} hand {}", seat, hand),
+            Recipient::House => write!(f, "the house"),
+        }
+    }
+}
+
+/// Event is one thing which happened at the table.  Every generation in the history keeps the
+/// events which took the game from the generation before to it, so the history can explain how
+/// the game came to be in any state.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// A new player sat down at the table in a seat of their own.
+    PlayerJoined { seat: usize },
+    /// A player brought chips to the table.
+    BoughtIn { player: usize, chips: Chips },
+    /// A player took every chip they had away from the table.
+    CashedOut { player: usize, chips: Chips },
+    /// A bet was placed on a seat for the next game.  A bet of zero sits the seat out.
+    BetPlaced { seat: usize, bet: Chips },
+    /// The discard tray was gathered back into the shoe and shuffled.
+    Reshuffled { shuffles: u64 },
+    /// The bets are in and the cards are about to be dealt.
+    RoundStarted,
+    /// A card came out of the shoe.
+    CardDealt { to: Recipient, card: Card },
+    /// A player took another card.
+    PlayerHit { seat: usize, hand: usize },
+    /// A player was done taking cards.
+    PlayerStood { seat: usize, hand: usize },
+    /// A player doubled their bet for one more card.
+    PlayerDoubled { seat: usize, hand: usize },
+    /// A player split their pair.  The new hand comes right after the one which was split.
+    PlayerSplit { seat: usize, hand: usize },
+    /// A player gave up their hand for half of their bet.
+    PlayerSurrendered { seat: usize, hand: usize },
+    /// A hand had nothing left to decide, so play moved on without the player acting.
+    HandFinished { seat: usize, hand: usize },
+    /// Every hand of a seat has been played, or it sat the game out, so play moved to the next
+    /// seat.
+    TurnOver { seat: usize },
+    /// A hand was settled against the house and the chips paid back to the player.
+    Payout {
+        seat: usize,
+        hand: usize,
+        outcome: Outcome,
+        chips: Chips,
+    },
+    /// Every hand was cleared into the discard tray, ready for the next game.
+    TableCleared,
+    /// The house showed an ace, so every seat in the game is asked about insurance before play.
+    InsuranceOffered,
+    /// A seat made its insurance side bet.  A bet of zero declines insurance.
+    Insured { seat: usize, chips: Chips },
+    /// A seat with a blackjack took even money instead of insurance.
+    TookEvenMoney { seat: usize },
+    /// The house checked its hole card for blackjack.  A blackjack ends the game.
+    HousePeeked { blackjack: bool },
+    /// An insurance bet was settled against the house.
+    InsurancePaid { seat: usize, chips: Chips },
+    /// A player already at the table took another seat.
+    SeatTaken { player: usize, seat: usize },
+    /// The shoe ran out part way through a game, so the discard tray was shuffled back into it.
+    ShoeRanOut { shuffles: u64 },
+    /// The table allows early surrender and the house is about to peek, so every seat in the game
+    /// is asked whether to give up its hand first.
+    SurrenderOffered,
+}
+
+impl Event {
+    /// Whether someone chose to make this happen, rather than the rules doing it by themselves.
+    /// Every call to the rules starts with one of these, and everything the rules do to follow
+    /// it up, like dealing, moving the turn on or paying out, comes after.
+    pub fn is_decision(&self) -> bool {
+        !matches!(
+            self,
+            Event::CardDealt { .. }
+                | Event::HandFinished { .. }
+                | Event::TurnOver { .. }
+                | Event::Payout { .. }
+                | Event::InsuranceOffered
+                | Event::HousePeeked { .. }
+                | Event::InsurancePaid { .. }
+                | Event::ShoeRanOut { .. }
+                | Event::SurrenderOffered
+        )
+    }
+}
+
+/// Event needs a standard way to be printed out, so a game can be logged as it is played.
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::PlayerJoined { seat } => write!(f, "A new player sat down in seat {}", seat),
+            Event::BoughtIn { player, chips } => {
+                write!(f, "Player {} bought in for {}", player, chips)
+            }
+            Event::CashedOut { player, chips } => {
+                write!(f, "Player {} cashed out {}", player, chips)
+            }
+            Event::BetPlaced { seat, bet } => write!(f, "Seat {} bet {}", seat, bet),
+            Event::Reshuffled { shuffles } => write!(f, "Shuffle {} of the shoe", shuffles),
+            Event::RoundStarted => write!(f, "The round started"),
+            Event::CardDealt { to, card } => write!(f, "{} was dealt to {}", card, to),
+            Event::PlayerHit { seat, hand } => write!(f, "Seat {} hit hand {}", seat, hand),
+            Event::PlayerStood { seat, hand } => write!(f, "Seat {} stood on hand {}", seat, hand),
+            Event::PlayerDoubled { seat, hand } => {
+                write!(f, "Seat {} doubled hand {}", seat, hand)
+            }
+            Event::PlayerSplit { seat, hand } => write!(f, "Seat {} split hand {}", seat, hand),
+            Event::PlayerSurrendered { seat, hand } => {
+                write!(f, "Seat {} surrendered hand {}", seat, hand)
+            }
+            Event::HandFinished { seat, hand } => {
+                write!(f, "Seat {} finished hand {}", seat, hand)
+            }
+            Event::TurnOver { seat } => write!(f, "Seat {}'s turn is over", seat),
+            Event::Payout {
+                seat,
+                hand,
+                outcome,
+                chips,
+            } => write!(
+                f,
+                "Seat {} hand {} was paid {} for a {}",
+                seat, hand, chips, outcome
+            ),
+            Event::TableCleared => write!(f, "The table was cleared"),
+            Event::InsuranceOffered => write!(f, "The house offered insurance"),
+            Event::Insured { seat, chips: 0 } => write!(f, "Seat {} declined insurance", seat),
+            Event::Insured { seat, chips } => write!(f, "Seat {} insured for {}", seat, chips),
+            Event::TookEvenMoney { seat } => write!(f, "Seat {} took even money", seat),
+            Event::HousePeeked { blackjack: true } => {
+                write!(f, "The house peeked and has blackjack")
+            }
+            Event::HousePeeked { blackjack: false } => {
+                write!(f, "The house peeked and has no blackjack")
+            }
+            Event::InsurancePaid { seat, chips } => {
+                write!(f, "Seat {} was paid {} for insurance", seat, chips)
+            }
+            Event::SeatTaken { player, seat } => {
+                write!(f, "Player {} took seat {}", player, seat)
+            }
+            Event::ShoeRanOut { shuffles } => {
+                write!(f, "Shuffle {} of the shoe after it ran out", shuffles)
+            }
+            Event::SurrenderOffered => write!(f, "The house offered early surrender"),
+        }
+    }
+}
+
+/// An Observer is told about every event as the rules make it, along with the state the event
+/// left the game in.  User interfaces, statistics and loggers can all watch a game this way
+/// without driving it.  Going back and forth through the history with undo and redo does not
+/// make any events.
+///
+/// Any closure taking an event and a state is an observer.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ Event, GameState, Rules };
+/// use std::{ cell::RefCell, rc::Rc };
+///
+/// let seen = Rc::new(RefCell::new(vec![]));
+/// let log = seen.clone();
+///
+/// let mut rule: Rules = Default::default();
+/// rule.add_observer(Box::new(move |event: &Event, _: &GameState| {
+///     log.borrow_mut().push(event.to_string());
+/// }));
+/// rule.add_player();
+/// rule.buy_in(0, 100);
+///
+/// assert_eq!(
+///     vec!["A new player sat down in seat 0", "Player 0 bought in for 100"],
+///     *seen.borrow()
+/// );
+/// ```
+pub trait Observer<S = Shoe> {
+    /// Something happened at the table.
+    ///
+    /// * `event`: What happened
+    /// * `state`: The state of the game once the event's generation was added to the history
+    fn notify(&mut self, event: &Event, state: &GameState<S>);
+}
+
+impl<S, F: FnMut(&Event, &GameState<S>)> Observer<S> for F {
+    fn notify(&mut self, event: &Event, state: &GameState<S>) {
+        self(event, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, Recipient};
+    use crate::game_state::Outcome;
+    use cards::prelude::{Card, Rank, Suit};
+
+    #[test]
+    fn events_print_what_happened() {
+        let dealt = Event::CardDealt {
+            to: Recipient::Seat { seat: 1, hand: 0 },
+            card: Card::new(Rank::Ace, Suit::Spades),
+        };
+        assert_eq!("As was dealt to seat 1 hand 0", dealt.to_string());
+
+        let house = Event::CardDealt {
+            to: Recipient::House,
+            card: Card::new(Rank::Ten, Suit::Hearts),
+        };
+        assert_eq!("Th was dealt to the house", house.to_string());
+
+        let paid = Event::Payout {
+            seat: 0,
+            hand: 1,
+            outcome: Outcome::Win,
+            chips: 20,
+        };
+        assert_eq!("Seat 0 hand 1 was paid 20 for a Win", paid.to_string());
+
+        let declined = Event::Insured { seat: 2, chips: 0 };
+        assert_eq!("Seat 2 declined insurance", declined.to_string());
+        let insured = Event::Insured { seat: 2, chips: 5 };
+        assert_eq!("Seat 2 insured for 5", insured.to_string());
+
+        let seated = Event::SeatTaken { player: 0, seat: 3 };
+        assert_eq!("Player 0 took seat 3", seated.to_string());
+
+        let ran_out = Event::ShoeRanOut { shuffles: 4 };
+        assert_eq!(
+            "Shuffle 4 of the shoe after it ran out",
+            ran_out.to_string()
+        );
+    }
+}
@@ -1,13 +1,18 @@
-use cards::prelude::Shoe;
-use player::{House, Player};
+use crate::{ledger::Ledger, shared::Shared};
+use cards::prelude::{Card, Shoe};
+use player::{Chips, HandleCards, House, Player, Seat};
 
 /// Progress will let you know where you are in the game.  It will help enforce that certain
 /// actions can only occur when the game is in a given state.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Progress {
     Starting,
     Playing,
     Done,
+    /// The house shows an ace and the players are being offered insurance before play starts.
+    /// This comes last so the other stages keep their place in older saves.
+    Insurance,
 }
 
 /// Prrogress needs a standard way to be printed out.  That's what Display is for.
@@ -17,6 +22,7 @@ impl std::fmt::Display for Progress {
             Progress::Starting => write!(f, "Starting"),
             Progress::Playing => write!(f, "Playing"),
             Progress::Done => write!(f, "Done"),
+            Progress::Insurance => write!(f, "Insurance"),
         }
     }
 }
@@ -28,23 +34,260 @@ impl Default for Progress {
     }
 }
 
+/// Outcome is how a player's hand finished against the house once the round is settled.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    Blackjack,
+    Win,
+    Push,
+    Surrender,
+    Lose,
+    /// A blackjack paid one to one, taken instead of insurance when the house showed an ace.
+    EvenMoney,
+}
+
+/// Outcome needs a standard way to be printed out.  That's what Display is for.
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Blackjack => write!(f, "Blackjack"),
+            Outcome::Win => write!(f, "Win"),
+            Outcome::Push => write!(f, "Push"),
+            Outcome::Surrender => write!(f, "Surrender"),
+            Outcome::Lose => write!(f, "Lose"),
+            Outcome::EvenMoney => write!(f, "Blackjack at even money"),
+        }
+    }
+}
+
+/// Insurance is what a seat chose when it was asked before the house peeked: when the house
+/// showed an ace, or at a table with early surrender.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Insurance {
+    /// No side bet was taken.
+    Declined,
+    /// A side bet of up to half the main bet that the house has blackjack.  It pays 2:1.
+    Taken(Chips),
+    /// The seat had a blackjack and took a sure one to one payout for it.
+    EvenMoney,
+    /// The seat gave up its hand for half of its bet before the house peeked.
+    Surrendered,
+}
+
+/// Insurance needs a standard way to be printed out.  That's what Display is for.
+impl std::fmt::Display for Insurance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Insurance::Declined => write!(f, "Declined"),
+            Insurance::Taken(chips) => write!(f, "Insured for {}", chips),
+            Insurance::EvenMoney => write!(f, "Even money"),
+            Insurance::Surrendered => write!(f, "Surrendered"),
+        }
+    }
+}
+
+/// Until a seat is asked it has no insurance.
+impl Default for Insurance {
+    fn default() -> Self {
+        Insurance::Declined
+    }
+}
+
+impl Insurance {
+    /// The chips riding on the side bet.
+    pub fn chips(&self) -> Chips {
+        match self {
+            Insurance::Taken(chips) => *chips,
+            Insurance::Declined | Insurance::EvenMoney | Insurance::Surrendered => 0,
+        }
+    }
+}
+
 /// GameState keeps track of the important things about the game.  As games are added this game
 /// state could be updated to include more generic items about that state.
-#[derive(Default, Clone, Debug)]
-pub struct GameState {
+///
+/// The house is dealt two cards.  The first card is the up card and the second card is the hole
+/// card which stays hidden until the game is done.
+///
+/// Every generation in the history keeps a whole state.  The parts which hold more than a few
+/// numbers are shared between the states until they change, so each generation only copies the
+/// parts it changed.  The shoe shares its cards the same way.
+#[derive(Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameState<S = Shoe> {
     pub progress: Progress,
-    pub house: House,
-    pub players: Vec<Player>,
-    pub shoe: Shoe,
+    pub house: Shared<House>,
+    /// Everyone playing at the table, along with the chips they have not bet.
+    pub players: Shared<Vec<Player>>,
+    /// The places at the table, in the order they are dealt to and play.  Every seat belongs to
+    /// one of the players and a player can have more than one seat.
+    pub seats: Vec<Shared<Seat>>,
+    /// Where the cards are dealt from.  This is a hand shuffled shoe unless the table says
+    /// otherwise.
+    pub shoe: S,
+    /// The index of the seat whose turn it is.  When every seat has acted this will be equal to
+    /// the number of seats and it is the house's turn.
+    pub turn: usize,
+    /// The outcome for each hand of each seat, in the same order as the seats and their hands.
+    /// This is only filled in once the game is done.
+    pub results: Shared<Vec<Vec<Outcome>>>,
+    /// What each seat chose when it was offered insurance, in the same order as the seats.  This
+    /// is empty unless the house showed an ace this game.
+    pub insurance: Shared<Vec<Insurance>>,
+    /// Every chip which has come to or left the table.
+    pub ledger: Ledger,
+    /// The seed every shoe at this table is shuffled from.
+    pub seed: u64,
+    /// How many times the shoe has been reshuffled.  The current shoe was shuffled by the seed
+    /// on this stream, so it can always be shuffled again exactly the same way.
+    pub shuffles: u64,
+}
+
+impl<S> GameState<S> {
+    /// The seat which needs to act, if there is one.
+    pub fn current_seat(&self) -> Option<usize> {
+        if self.progress == Progress::Playing && self.turn < self.seats.len() {
+            Some(self.turn)
+        } else {
+            None
+        }
+    }
+
+    /// The seat which is being offered insurance, if there is one.
+    pub fn insuring_seat(&self) -> Option<usize> {
+        if self.progress == Progress::Insurance && self.turn < self.seats.len() {
+            Some(self.turn)
+        } else {
+            None
+        }
+    }
+
+    /// The player sitting in the given seat.
+    pub fn player_at(&self, seat: usize) -> Option<&Player> {
+        self.players.get(self.seats.get(seat)?.player)
+    }
+
+    /// Every seat the given player is sitting in, in the order they play.
+    pub fn seats_of(&self, player: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.seats.len()).filter(move |&seat| self.seats[seat].player == player)
+    }
+
+    /// The house card every player can see.
+    pub fn dealer_up_card(&self) -> Option<&Card> {
+        self.house.hand.show_card()
+    }
+
+    /// The chips bet on the table which have not been settled yet, including insurance.  The bets
+    /// stay on a seat's hands until the table is cleared, but once the seat has its results they
+    /// have been paid out.
+    pub fn chips_on_table(&self) -> Chips {
+        (0..self.seats.len())
+            .filter(|&seat| self.results.get(seat).is_none_or(Vec::is_empty))
+            .map(|seat| {
+                let insurance = self.insurance.get(seat).map_or(0, Insurance::chips);
+                self.seats[seat].total_bet() + insurance
+            })
+            .sum()
+    }
+
+    /// Every chip at the table, whether it is with a player, the house or bet.
+    pub fn chips_in_play(&self) -> Chips {
+        let bankrolls: Chips = self.players.iter().map(|player| player.bankroll).sum();
+        bankrolls + self.house.bankroll + self.chips_on_table()
+    }
+
+    /// Chips are never made or lost at the table.  The chips in play must match what the ledger
+    /// says was brought to the table.
+    pub fn is_balanced(&self) -> bool {
+        self.chips_in_play() == self.ledger.balance()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::GameState;
+    use super::{GameState, Insurance, Outcome, Progress};
+    use player::Seat;
 
     #[test]
     fn default_game_state() {
         let state: GameState = Default::default();
-        assert_eq!(0, state.players.len())
+        assert_eq!(0, state.players.len());
+        assert_eq!(0, state.seats.len());
+    }
+
+    #[test]
+    fn chips_are_balanced() {
+        let mut state: GameState = Default::default();
+        assert!(state.is_balanced());
+
+        state.ledger.buy_in(100);
+        state.players.push(Default::default());
+        state.seats.push(Seat::new(0).into());
+        state.players[0].bankroll = 90;
+        state.seats[0].hands[0].bet = 10;
+        assert_eq!(10, state.chips_on_table());
+        assert!(state.is_balanced());
+
+        state.progress = Progress::Done;
+        assert_eq!(10, state.chips_on_table());
+        state.results = vec![vec![Outcome::Lose]].into();
+        assert_eq!(0, state.chips_on_table());
+        assert!(!state.is_balanced());
+        state.house.bankroll = 10;
+        assert!(state.is_balanced());
+    }
+
+    #[test]
+    fn insurance_is_on_the_table() {
+        let mut state: GameState = GameState {
+            progress: Progress::Insurance,
+            players: vec![Default::default()].into(),
+            seats: vec![Seat::new(0).into(), Seat::new(0).into()],
+            ..Default::default()
+        };
+        state.ledger.buy_in(100);
+        state.players[0].bankroll = 85;
+        state.seats[0].hands[0].bet = 10;
+        state.insurance = vec![Insurance::Taken(5), Insurance::Declined].into();
+
+        assert_eq!(15, state.chips_on_table());
+        assert!(state.is_balanced());
+        assert_eq!(Some(0), state.insuring_seat());
+        assert_eq!(None, state.current_seat());
+    }
+
+    #[test]
+    fn no_current_seat_unless_playing() {
+        let mut state: GameState = GameState {
+            players: vec![Default::default()].into(),
+            seats: vec![Seat::new(0).into()],
+            ..Default::default()
+        };
+        assert_eq!(None, state.current_seat());
+        state.progress = Progress::Playing;
+        assert_eq!(Some(0), state.current_seat());
+        state.turn = 1;
+        assert_eq!(None, state.current_seat());
+    }
+
+    #[test]
+    fn players_can_sit_in_more_than_one_seat() {
+        let mut state: GameState = GameState {
+            players: vec![Default::default(), Default::default()].into(),
+            seats: vec![
+                Seat::new(1).into(),
+                Seat::new(0).into(),
+                Seat::new(1).into(),
+            ],
+            ..Default::default()
+        };
+        state.players[1].bankroll = 50;
+
+        assert_eq!(vec![0, 2], state.seats_of(1).collect::<Vec<_>>());
+        assert_eq!(vec![1], state.seats_of(0).collect::<Vec<_>>());
+        assert_eq!(Some(50), state.player_at(2).map(|player| player.bankroll));
+        assert_eq!(None, state.player_at(3));
     }
 }
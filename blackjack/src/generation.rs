@@ -1,239 +1,138 @@
-use crate::game_state::GameState;
-use std::time::SystemTime;
-
-/// Generation will contain and maintain the history of the game state.  It will keep this history
-/// in a tree structure.
-#[derive(Debug)]
-pub struct Generation {
-    state: GameState,
-    timestamp: SystemTime,
-    children: Vec<Self>,
-}
-
-impl Default for Generation {
-    fn default() -> Self {
-        Self {
-            state: Default::default(),
-            timestamp: SystemTime::now(),
-            children: Default::default(),
-        }
-    }
+use crate::{event::Event, game_state::GameState};
+use cards::prelude::Shoe;
+
+/// Generation will contain and maintain the history of the game state.  Each generation is one
+/// state of the game with a link back to the generation it came from and to every branch which
+/// came after it, so together they make a tree.  The tree is kept by the History, which gives
+/// every generation an id counting up from 0 in the order they were made.
+///
+/// Along with the state a generation keeps the events which led to it from its parent.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Generation<S = Shoe> {
+    id: usize,
+    parent: Option<usize>,
+    number: usize,
+    state: GameState<S>,
+    events: Vec<Event>,
+    branches: Vec<usize>,
 }
 
-impl Generation {
-    /// Create a new generation with a given state.  A generation can never be created with an
-    /// empty state.
+impl<S> Generation<S> {
+    /// Create a new generation with no branches.
     ///
+    /// * `id`: Where the generation is in the order generations were made
+    /// * `parent`: The id of the generation this one came after, if any
+    /// * `number`: How many generations there are between this one and the first
     /// * `state`: What state should this generation keep track of
-    pub fn new(state: GameState) -> Self {
+    /// * `events`: What happened to get from the parent's state to this one
+    pub(super) fn new(
+        id: usize,
+        parent: Option<usize>,
+        number: usize,
+        state: GameState<S>,
+        events: Vec<Event>,
+    ) -> Self {
         Generation {
+            id,
+            parent,
+            number,
             state,
-            timestamp: SystemTime::now(),
-            ..Default::default()
+            events,
+            branches: vec![],
         }
     }
 
-    /// append_generation will take in a new GameState and append it to the current list of states
-    /// on this generation.  Right now this is a private method.  It is possible to create trees of
-    /// generations using this method.
-    ///
-    /// * `state`: GameState to be added to this generation
-    pub(super) fn append_generation(&mut self, state: GameState) {
-        let generation = Generation::new(state);
-        self.children.push(generation);
+    /// The id of this generation.  Ids count up from 0 in the order the generations were made.
+    pub fn id(&self) -> usize {
+        self.id
     }
 
-    /// Add a new GameState to the list of game states on this generation.  This will allow each
-    /// generation to have multiple possible outcomes.
-    ///
-    /// Example:
-    /// ```
-    /// use blackjack::prelude::{ Generation, GameState };
-    ///
-    /// let mut generation: Generation = Default::default();
-    /// let game_state: GameState = Default::default();
-    ///
-    /// generation.add_generation(game_state);
-    ///
-    /// assert_eq!(1, generation.number_of_branches());
-    /// ```
-    ///
-    /// * `state`: The GameState that needs to be added to this generation.
-    pub fn add_generation(&mut self, state: GameState) {
-        let current = self.mut_current_generation();
-        current.append_generation(state);
+    /// The id of the generation this one came after.  Only the first generation has no parent.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
     }
 
-    /// Return the number of possible branches which occur in this generation.
-    ///
-    /// Example:
-    /// ```
-    /// use blackjack::prelude::{ Generation, GameState };
-    ///
-    /// let mut generation: Generation = Default::default();
-    ///
-    /// generation.add_generation(Default::default());
-    /// generation.add_generation(Default::default());
-    ///
-    /// assert_eq!(1, generation.number_of_branches());
-    /// ```
-    pub fn number_of_branches(&self) -> usize {
-        self.children.len()
+    /// The generation number, which is how many generations came before this one on the way from
+    /// the first generation.  The first generation is generation 0.
+    pub fn number(&self) -> usize {
+        self.number
     }
 
-    /// In some cases you may want to switch to a different path in history.  Using branches will
-    /// allow access to all possible paths which could occur in history.
-    ///
-    /// Example:
-    /// ```
-    /// use blackjack::prelude::{ Generation, GameState };
-    ///
-    /// let mut generation: Generation = Default::default();
-    ///
-    /// generation.add_generation(Default::default());
-    /// generation.add_generation(Default::default());
-    ///
-    /// assert_eq!(1, generation.branches().len());
-    /// ```
-    pub fn branches(&self) -> &Vec<Generation> {
-        &self.children
+    /// The state this generation keeps track of.
+    pub fn state(&self) -> &GameState<S> {
+        &self.state
     }
 
-    /// Gets the current generation for the game state.
-    ///
-    /// Example:
-    /// ```
-    /// use blackjack::prelude::{ Generation, GameState, Progress };
-    ///
-    /// let mut generation: Generation = Default::default();
-    ///
-    /// let mut game_state: GameState = Default::default();
-    /// game_state.progress = Progress::Done;
-    ///
-    /// generation.add_generation(Default::default());
-    /// generation.add_generation(game_state);
-    ///
-    /// let current_branch = generation.current_branch().unwrap();
-    /// assert_eq!(Progress::Done, current_branch.current_state().progress);
-    /// ```
-    pub fn current_branch(&self) -> Option<&Self> {
-        self.children
-            .iter()
-            .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
+    /// The events which took the game from the parent's state to this one, in the order they
+    /// happened.  The first generation has none.
+    pub fn events(&self) -> &[Event] {
+        &self.events
     }
 
-    /// current generation will traverse all generations to discover the current generation.  When
-    /// evaluating this will give the most up-to-date information on the game state.  If the
-    /// history forks this method will still pull the most up-to-date generation until a new
-    /// generation is created.
+    /// Return the number of possible branches which occur in this generation.
     ///
     /// Example:
     /// ```
-    /// use blackjack::prelude::{ Generation, GameState, Progress };
+    /// use blackjack::prelude::{ GameState, History };
+    ///
+    /// let mut history: History = Default::default();
     ///
-    /// let mut generation: Generation = Default::default();
+    /// history.add_generation(Default::default());
+    /// history.undo().unwrap();
+    /// history.add_generation(Default::default());
     ///
-    /// let current = generation.current_generation();
+    /// assert_eq!(2, history.root().number_of_branches());
     /// ```
-    pub fn current_generation(&self) -> &Generation {
-        if let Some(branch) = self.current_branch() {
-            branch.current_generation()
-        } else {
-            self
-        }
-    }
-
-    /// Return a mutable branch.  This will default to the current branch.
-    fn mut_current_branch(&mut self) -> Option<&mut Self> {
-        self.children
-            .iter_mut()
-            .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
+    pub fn number_of_branches(&self) -> usize {
+        self.branches.len()
     }
 
-    /// Return the current generation as mutable.
-    fn mut_current_generation(&mut self) -> &mut Self {
-        if self.children.is_empty() {
-            return self;
-        }
-
-        self.mut_current_branch()
-            .expect("If there are children then there must be a current branch")
-            .mut_current_generation()
+    /// In some cases you may want to switch to a different path in history.  Using branches will
+    /// allow access to all possible paths which could occur in history.  These are the ids of the
+    /// branches, oldest first.
+    pub fn branches(&self) -> &[usize] {
+        &self.branches
     }
 
-    /// Gets the current state for the game.  This will traverse the most current state and return
-    /// that state.
-    ///
-    /// Example:
-    /// ```
-    /// use blackjack::prelude::{ Generation, GameState, Progress };
+    /// Add a branch after this generation.
     ///
-    /// let mut generation: Generation = Default::default();
-    ///
-    /// let mut game_state: GameState = Default::default();
-    /// game_state.progress = Progress::Done;
-    ///
-    /// generation.add_generation(Default::default());
-    /// generation.add_generation(game_state);
-    ///
-    /// let current_branch = generation.current_branch().unwrap();
-    /// assert_eq!(Progress::Done, current_branch.current_state().progress);
-    /// ```
-    pub fn current_state(&self) -> &GameState {
-        if let Some(branch) = self.current_branch() {
-            branch.current_state()
-        } else {
-            &self.state
-        }
+    /// * `id`: The id of the generation which came after this one
+    pub(super) fn add_branch(&mut self, id: usize) {
+        self.branches.push(id);
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::Generation;
-    use crate::game_state::{GameState, Progress};
-
-    #[test]
-    fn can_add_a_generation() {
-        let mut generation: Generation = Default::default();
-        generation.add_generation(Default::default());
-        assert_eq!(1, generation.number_of_branches());
-    }
+    use crate::{
+        event::Event,
+        game_state::{GameState, Progress},
+    };
 
     #[test]
     fn can_add_a_custom_game_state_to_a_generation() {
         let gs: GameState = Default::default();
-        let generation = Generation::new(gs);
+        let generation = Generation::new(0, None, 0, gs, vec![]);
 
         assert_eq!(0, generation.number_of_branches());
+        assert_eq!(None, generation.parent());
         assert_eq!(Progress::Starting, generation.state.progress);
     }
 
     #[test]
-    fn can_get_the_current_branch() {
-        let mut generation: Generation = Default::default();
-
-        assert_eq!(0, generation.number_of_branches());
-
-        let gs = GameState {
-            players: vec![Default::default()],
-            ..Default::default()
-        };
-
-        generation.append_generation(gs);
-
-        assert_eq!(1, generation.number_of_branches());
-
-        let gs2 = GameState {
-            players: vec![Default::default(), Default::default()],
-            ..Default::default()
-        };
-        generation.append_generation(gs2);
-
-        assert_eq!(2, generation.number_of_branches());
-
-        let maybe = generation.current_branch();
-        assert!(maybe.is_some());
-        assert_eq!(2, maybe.unwrap().state.players.len());
+    fn branches_are_kept_in_order() {
+        let events = vec![Event::PlayerJoined { seat: 0 }];
+        let mut generation: Generation =
+            Generation::new(3, Some(1), 2, Default::default(), events.clone());
+        generation.add_branch(4);
+        generation.add_branch(7);
+
+        assert_eq!(3, generation.id());
+        assert_eq!(Some(1), generation.parent());
+        assert_eq!(2, generation.number());
+        assert_eq!(&[4, 7], generation.branches());
+        assert_eq!(events, generation.events());
     }
 }
@@ -0,0 +1,828 @@
+use crate::{
+    action::Action,
+    error::{HandHistoryError, RuleError},
+    event::{Event, Recipient},
+    game_state::{Insurance, Outcome, Progress},
+    history::History,
+    rules::Rules,
+    table_rules::TableRules,
+};
+use cards::prelude::{Card, CardSource, HasCards};
+use player::{Chips, Hand};
+use rand::Rng;
+use std::str::FromStr;
+
+/// The first line of every hand history.
+const TITLE: &str = "# Blackjack hand history";
+
+/// Comes before the house's hand once the house has played it out.
+const HOUSE_FINISHED: &str = "The house finished with ";
+
+/// One line of a hand history.
+#[derive(Debug, PartialEq, Clone)]
+enum Entry {
+    /// A new game starts, counted from 1.
+    Round(usize),
+    /// Something which happened at the table.
+    Event(Event),
+    /// The hand the house finished with, written just before the payouts.
+    House(Hand),
+}
+
+/// Entry needs a standard way to be printed out, one line each.
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Entry::Round(round) => write!(f, "Round {}", round),
+            Entry::Event(event) => write!(f, "{}", event),
+            Entry::House(hand) => write!(f, "{}{}, {}", HOUSE_FINISHED, hand, hand.value()),
+        }
+    }
+}
+
+/// HandHistory is a game written out the way a person would tell it, one line for each thing
+/// which happened: the players sitting down, every bet, every card, every action, the hand the
+/// house finished with and every payout.  Each game starts with its round number.
+///
+/// A hand history can be read back in and replayed through the rules, which checks that the same
+/// cards and the same decisions make the same game.  The table rules are not written out, so the
+/// replay needs to be told which table the game was played at.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ HandHistory, MimicTheDealer, Rules, TableRules };
+///
+/// let mut rule = Rules::with_seed(TableRules::vegas_strip(), 11);
+/// rule.add_player();
+/// rule.buy_in(0, 500);
+/// rule.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+/// rule.by_ref().take(40).for_each(drop);
+///
+/// let text = HandHistory::from(rule.history()).to_string();
+/// assert!(text.contains("Seat 0 bet 10"));
+///
+/// let read: HandHistory = text.parse().unwrap();
+/// let replayed = read.replay(TableRules::vegas_strip()).unwrap();
+/// assert_eq!(rule.current_state().players, replayed.current_state().players);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HandHistory {
+    entries: Vec<Entry>,
+    /// The line each entry was read from, counting from 1.  A hand history made from a game has
+    /// the lines its entries are written on.
+    lines: Vec<usize>,
+}
+
+impl HandHistory {
+    /// Every event in the hand history, in the order they happened.
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Event(event) => Some(event),
+            _ => None,
+        })
+    }
+
+    /// The number of games the hand history covers, including one which is not finished.
+    pub fn number_of_rounds(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry, Entry::Round(_)))
+            .count()
+    }
+
+    /// Play the hand history again at the given table.  The cards are dealt in the order they
+    /// were written and every bet and decision is made again, then the replayed game is written
+    /// out and checked line by line against this hand history.
+    ///
+    /// * `table`: The house rules the game was played by
+    pub fn replay(&self, table: TableRules) -> Result<Rules<ReplayShoe>, HandHistoryError> {
+        let mut shuffles = vec![vec![]];
+        for event in self.events() {
+            match event {
+                Event::CardDealt { card, .. } => shuffles
+                    .last_mut()
+                    .expect("There is always a shuffle to deal from")
+                    .push(*card),
+                Event::Reshuffled { .. } | Event::ShoeRanOut { .. } => shuffles.push(vec![]),
+                _ => {}
+            }
+        }
+        let mut rules = Rules::with_cards(table, ReplayShoe::shuffled(shuffles), 0);
+
+        for event in self.events() {
+            replay_event(&mut rules, event).map_err(|error| HandHistoryError::Refused {
+                event: event.to_string(),
+                error,
+            })?;
+        }
+
+        let replayed = HandHistory::from(rules.history());
+        let length = self.entries.len().max(replayed.entries.len());
+        for index in 0..length {
+            let expected = self.entries.get(index);
+            let found = replayed.entries.get(index);
+            if expected != found {
+                let describe = |entry: Option<&Entry>| {
+                    entry.map_or("the end of the game".to_string(), |entry| entry.to_string())
+                };
+                return Err(HandHistoryError::Mismatch {
+                    line: self.line_of(index),
+                    expected: describe(expected),
+                    found: describe(found),
+                });
+            }
+        }
+        Ok(rules)
+    }
+
+    /// The line an entry was read from, counting from 1.  Anything after the last entry is on the
+    /// line after it.
+    fn line_of(&self, index: usize) -> usize {
+        match self.lines.get(index) {
+            Some(&line) => line,
+            None => self.lines.last().map_or(1, |line| line + 1),
+        }
+    }
+}
+
+/// Two hand histories are the same when they tell the same game, whichever lines it was written
+/// on.
+impl PartialEq for HandHistory {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+/// Make the same call to the rules which made the event the first time.  Events which the rules
+/// make by themselves, like dealing cards or paying out, need nothing done.  The house plays out
+/// its hand as soon as it is next to do anything.
+fn replay_event(rules: &mut Rules<ReplayShoe>, event: &Event) -> Result<(), RuleError> {
+    let house_is_next = matches!(
+        event,
+        Event::CardDealt {
+            to: Recipient::House,
+            ..
+        } | Event::Payout { .. }
+            | Event::InsurancePaid { .. }
+            | Event::TableCleared
+    );
+    if house_is_next && rules.is_playing() && rules.current_state().current_seat().is_none() {
+        rules.done_playing()?;
+    }
+
+    match *event {
+        Event::PlayerJoined { .. } => rules.add_player(),
+        Event::SeatTaken { player, .. } => rules.take_seat(player),
+        Event::BoughtIn { player, chips } => rules.buy_in(player, chips),
+        Event::CashedOut { player, .. } => rules.cash_out(player).map(drop),
+        Event::BetPlaced { seat, bet } => rules.place_bet(seat, bet),
+        Event::Reshuffled { .. } => rules.reshuffle(),
+        Event::RoundStarted => rules.start_playing(),
+        Event::PlayerHit { seat, .. } => rules.act(seat, Action::Hit),
+        Event::PlayerStood { seat, .. } => rules.act(seat, Action::Stand),
+        Event::PlayerDoubled { seat, .. } => rules.act(seat, Action::Double),
+        Event::PlayerSplit { seat, .. } => rules.act(seat, Action::Split),
+        Event::PlayerSurrendered { seat, .. }
+            if rules.current_progress() == &Progress::Insurance =>
+        {
+            rules.insure(seat, Insurance::Surrendered)
+        }
+        Event::PlayerSurrendered { seat, .. } => rules.act(seat, Action::Surrender),
+        Event::TableCleared => rules.new_game(),
+        Event::Insured { seat, chips } => rules.insure(seat, Insurance::Taken(chips)),
+        Event::TookEvenMoney { seat } => rules.insure(seat, Insurance::EvenMoney),
+        Event::CardDealt { .. }
+        | Event::HandFinished { .. }
+        | Event::TurnOver { .. }
+        | Event::Payout { .. }
+        | Event::InsuranceOffered
+        | Event::HousePeeked { .. }
+        | Event::InsurancePaid { .. }
+        | Event::ShoeRanOut { .. }
+        | Event::SurrenderOffered => Ok(()),
+    }
+}
+
+/// Write out every generation from the start of the game to where the history is now.
+impl<S> From<&History<S>> for HandHistory {
+    fn from(history: &History<S>) -> Self {
+        let mut entries = vec![Entry::Round(1)];
+        let mut round = 1;
+        let mut finished = false;
+
+        for id in history.path() {
+            let generation = history
+                .get(id)
+                .expect("The path only has generations in it");
+            let state = generation.state();
+
+            if state.progress == Progress::Done && !finished {
+                entries.push(Entry::House(state.house.hand.clone()));
+            }
+            finished = state.progress == Progress::Done;
+
+            for event in generation.events() {
+                if matches!(entries.last(), Some(Entry::Event(Event::TableCleared))) {
+                    round += 1;
+                    entries.push(Entry::Round(round));
+                }
+                entries.push(Entry::Event(event.clone()));
+            }
+        }
+
+        // The title comes first and every round after the first has a blank line before it.
+        let mut line = 1;
+        let lines = entries
+            .iter()
+            .map(|entry| {
+                line += 1 + matches!(entry, Entry::Round(round) if *round > 1) as usize;
+                line
+            })
+            .collect();
+        HandHistory { entries, lines }
+    }
+}
+
+/// The hand history written out as text.
+impl std::fmt::Display for HandHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", TITLE)?;
+        for entry in self.entries.iter() {
+            if matches!(entry, Entry::Round(round) if *round > 1) {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a hand history back in.  Blank lines and lines starting with `#` are skipped.
+impl FromStr for HandHistory {
+    type Err = HandHistoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = vec![];
+        let mut lines = vec![];
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_entry(line).ok_or_else(|| HandHistoryError::Unreadable {
+                line: index + 1,
+                text: line.to_string(),
+            })?;
+            entries.push(entry);
+            lines.push(index + 1);
+        }
+        Ok(HandHistory { entries, lines })
+    }
+}
+
+/// Read one line of a hand history.  This is the other half of how entries and events are
+/// printed out.
+fn parse_entry(line: &str) -> Option<Entry> {
+    if let Some(house) = line.strip_prefix(HOUSE_FINISHED) {
+        let (cards, _value) = house.split_once(", ")?;
+        return cards.parse().ok().map(Entry::House);
+    }
+
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let number = |word: &str| word.parse::<usize>().ok();
+    let chips = |word: &str| word.parse::<Chips>().ok();
+
+    let event = match words[..] {
+        ["Round", round] => return number(round).map(Entry::Round),
+        ["A", "new", "player", "sat", "down", "in", "seat", seat] => Event::PlayerJoined {
+            seat: number(seat)?,
+        },
+        ["Player", player, "took", "seat", seat] => Event::SeatTaken {
+            player: number(player)?,
+            seat: number(seat)?,
+        },
+        ["Player", player, "bought", "in", "for", amount] => Event::BoughtIn {
+            player: number(player)?,
+            chips: chips(amount)?,
+        },
+        ["Player", player, "cashed", "out", amount] => Event::CashedOut {
+            player: number(player)?,
+            chips: chips(amount)?,
+        },
+        ["Seat", seat, "bet", amount] => Event::BetPlaced {
+            seat: number(seat)?,
+            bet: chips(amount)?,
+        },
+        ["Shuffle", shuffles, "of", "the", "shoe"] => Event::Reshuffled {
+            shuffles: shuffles.parse().ok()?,
+        },
+        ["Shuffle", shuffles, "of", "the", "shoe", "after", "it", "ran", "out"] => {
+            Event::ShoeRanOut {
+                shuffles: shuffles.parse().ok()?,
+            }
+        }
+        ["The", "round", "started"] => Event::RoundStarted,
+        [card, "was", "dealt", "to", "the", "house"] => Event::CardDealt {
+            to: Recipient::House,
+            card: card.parse::<Card>().ok()?,
+        },
+        [card, "was", "dealt", "to", "seat", seat, "hand", hand] => Event::CardDealt {
+            to: Recipient::Seat {
+                seat: number(seat)?,
+                hand: number(hand)?,
+            },
+            card: card.parse::<Card>().ok()?,
+        },
+        ["Seat", seat, "hit", "hand", hand] => Event::PlayerHit {
+            seat: number(seat)?,
+            hand: number(hand)?,
+        },
+        ["Seat", seat, "stood", "on", "hand", hand] => Event::PlayerStood {
+            seat: number(seat)?,
+            hand: number(hand)?,
+        },
+        ["Seat", seat, "doubled", "hand", hand] => Event::PlayerDoubled {
+            seat: number(seat)?,
+            hand: number(hand)?,
+        },
+        ["Seat", seat, "split", "hand", hand] => Event::PlayerSplit {
+            seat: number(seat)?,
+            hand: number(hand)?,
+        },
+        ["Seat", seat, "surrendered", "hand", hand] => Event::PlayerSurrendered {
+            seat: number(seat)?,
+            hand: number(hand)?,
+        },
+        ["Seat", seat, "finished", "hand", hand] => Event::HandFinished {
+            seat: number(seat)?,
+            hand: number(hand)?,
+        },
+        ["Seat", seat, "turn", "is", "over"] => Event::TurnOver {
+            seat: number(seat.strip_suffix("'s")?)?,
+        },
+        ["Seat", seat, "hand", hand, "was", "paid", amount, "for", "a", ref outcome @ ..] => {
+            Event::Payout {
+                seat: number(seat)?,
+                hand: number(hand)?,
+                outcome: parse_outcome(&outcome.join(" "))?,
+                chips: chips(amount)?,
+            }
+        }
+        ["The", "table", "was", "cleared"] => Event::TableCleared,
+        ["The", "house", "offered", "insurance"] => Event::InsuranceOffered,
+        ["The", "house", "offered", "early", "surrender"] => Event::SurrenderOffered,
+        ["Seat", seat, "declined", "insurance"] => Event::Insured {
+            seat: number(seat)?,
+            chips: 0,
+        },
+        ["Seat", seat, "insured", "for", amount] => Event::Insured {
+            seat: number(seat)?,
+            chips: chips(amount)?,
+        },
+        ["Seat", seat, "took", "even", "money"] => Event::TookEvenMoney {
+            seat: number(seat)?,
+        },
+        ["The", "house", "peeked", "and", "has", "blackjack"] => {
+            Event::HousePeeked { blackjack: true }
+        }
+        ["The", "house", "peeked", "and", "has", "no", "blackjack"] => {
+            Event::HousePeeked { blackjack: false }
+        }
+        ["Seat", seat, "was", "paid", amount, "for", "insurance"] => Event::InsurancePaid {
+            seat: number(seat)?,
+            chips: chips(amount)?,
+        },
+        _ => return None,
+    };
+    Some(Entry::Event(event))
+}
+
+fn parse_outcome(word: &str) -> Option<Outcome> {
+    [
+        Outcome::Blackjack,
+        Outcome::Win,
+        Outcome::Push,
+        Outcome::Surrender,
+        Outcome::Lose,
+        Outcome::EvenMoney,
+    ]
+    .into_iter()
+    .find(|outcome| outcome.to_string() == word)
+}
+
+/// ReplayShoe deals the cards of a hand history in the order they were dealt the first time.
+/// Reshuffling never changes the order, so a replay deals every card where it went before.  The
+/// cards of each shuffle run out where they ran out the first time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayShoe {
+    /// The cards still to deal, the next one last.
+    cards: Vec<Card>,
+    /// The cards of every shuffle after this one, the next shuffle last.
+    shuffles: Vec<Vec<Card>>,
+    discards: usize,
+}
+
+impl ReplayShoe {
+    /// A shoe which deals the given cards in order.  Reshuffling it changes nothing.
+    ///
+    /// * `cards`: The cards to deal, the first card first
+    pub fn new(cards: Vec<Card>) -> Self {
+        ReplayShoe::shuffled(vec![cards])
+    }
+
+    /// A shoe which deals the cards of one shuffle after another.  Each shuffle runs out after
+    /// its last card until the shoe is reshuffled, which moves on to the next one.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::ReplayShoe;
+    /// use cards::prelude::{ parse_cards, seeded_rng, CardSource, HasCards };
+    ///
+    /// let first = parse_cards("As Kd").unwrap();
+    /// let second = parse_cards("7h").unwrap();
+    /// let mut shoe = ReplayShoe::shuffled(vec![first.clone(), second.clone()]);
+    /// assert_eq!(Some(first[0]), shoe.deal());
+    /// assert_eq!(Some(first[1]), shoe.deal());
+    /// assert_eq!(None, shoe.deal());
+    ///
+    /// shoe.reshuffle_with(&mut seeded_rng(0, 1));
+    /// assert_eq!(Some(second[0]), shoe.deal());
+    /// ```
+    ///
+    /// * `shuffles`: The cards dealt from each shuffle, the first shuffle and card first
+    pub fn shuffled(shuffles: Vec<Vec<Card>>) -> Self {
+        let mut shuffles: Vec<Vec<Card>> = shuffles
+            .into_iter()
+            .rev()
+            .map(|mut cards| {
+                cards.reverse();
+                cards
+            })
+            .collect();
+        let cards = shuffles.pop().unwrap_or_default();
+        ReplayShoe {
+            cards,
+            shuffles,
+            discards: 0,
+        }
+    }
+}
+
+impl HasCards for ReplayShoe {
+    fn deal(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+
+    fn cards_left(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// The order is already decided, so shuffling does nothing.
+    fn shuffle_with<R: Rng + ?Sized>(&mut self, _rng: &mut R) {}
+}
+
+impl CardSource for ReplayShoe {
+    fn discard(&mut self, cards: Vec<Card>) {
+        self.discards += cards.len();
+    }
+
+    /// Reshuffles are replayed from the hand history, never because of a cut card.
+    fn reshuffle_pending(&self) -> bool {
+        false
+    }
+
+    /// Move on to the cards of the next shuffle, if there is one.
+    fn reshuffle_with<R: Rng + ?Sized>(&mut self, _rng: &mut R) {
+        if let Some(cards) = self.shuffles.pop() {
+            self.cards = cards;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_entry, HandHistory};
+    use crate::{
+        action::Action,
+        basic_strategy::BasicStrategy,
+        error::HandHistoryError,
+        game_state::{GameState, Insurance},
+        rules::{Halt, Rules},
+        strategy::{Decision, Strategy},
+        table_rules::{SurrenderRule, TableRules},
+    };
+    use cards::prelude::Shoe;
+    use player::Chips;
+
+    /// Play a session at the table, reshuffling whenever the cut card comes out.
+    fn session(table: TableRules, seed: u64, steps: usize) -> Rules {
+        session_with(table, seed, steps, Box::new(BasicStrategy::new(10)))
+    }
+
+    /// Play a session at the table with the given strategy.  The first of the three players also
+    /// takes the last seat, so one bankroll is bet from two seats.
+    fn session_with(
+        table: TableRules,
+        seed: u64,
+        steps: usize,
+        strategy: Box<dyn Strategy>,
+    ) -> Rules {
+        let mut rules = Rules::with_seed(table, seed);
+        for player in 0..3 {
+            rules.add_player().unwrap();
+            rules.buy_in(player, 1000).unwrap();
+        }
+        rules.take_seat(0).unwrap();
+        rules.set_strategy(strategy);
+        for _ in 0..steps {
+            if rules.next().is_none() {
+                assert_eq!(Some(&Halt::Reshuffle), rules.halted());
+                rules.reshuffle().unwrap();
+            }
+        }
+        rules
+    }
+
+    #[test]
+    fn every_line_reads_back_the_same() {
+        let rules = session(TableRules::vegas_strip(), 3, 2000);
+        let history = HandHistory::from(rules.history());
+        assert!(history.number_of_rounds() > 50);
+
+        for line in history
+            .to_string()
+            .lines()
+            .skip(1)
+            .filter(|l| !l.is_empty())
+        {
+            let entry = parse_entry(line).unwrap_or_else(|| panic!("Could not read {}", line));
+            assert_eq!(line, entry.to_string());
+        }
+        assert_eq!(history, history.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn replays_reproduce_the_session() {
+        for table in [
+            TableRules::vegas_strip(),
+            TableRules::downtown_vegas(),
+            TableRules::european(),
+        ] {
+            let rules = session(table.clone(), 8, 1500);
+            let text = HandHistory::from(rules.history()).to_string();
+            let replayed = text.parse::<HandHistory>().unwrap().replay(table).unwrap();
+
+            let original = rules.current_state();
+            let state = replayed.current_state();
+            assert_eq!(original.players, state.players);
+            assert_eq!(original.seats, state.seats);
+            assert_eq!(original.house, state.house);
+            assert_eq!(original.results, state.results);
+            assert_eq!(original.ledger, state.ledger);
+            assert_eq!(original.shuffles, state.shuffles);
+        }
+    }
+
+    /// Basic strategy, except every insurance offer is taken: even money on a blackjack and as
+    /// much insurance as the table allows otherwise.
+    struct AlwaysInsure(BasicStrategy);
+
+    impl Strategy for AlwaysInsure {
+        fn bet(&mut self, seat: usize, state: &GameState, table: &TableRules) -> Chips {
+            self.0.bet(seat, state, table)
+        }
+
+        fn act(&mut self, decision: &Decision) -> Action {
+            self.0.act(decision)
+        }
+
+        fn insure(&mut self, seat: usize, state: &GameState, _table: &TableRules) -> Insurance {
+            let hand = &state.seats[seat].hands[0];
+            if hand.is_blackjack() {
+                Insurance::EvenMoney
+            } else {
+                Insurance::Taken(hand.bet / 2)
+            }
+        }
+    }
+
+    #[test]
+    fn insurance_replays_too() {
+        for table in [TableRules::vegas_strip(), TableRules::european()] {
+            let strategy = Box::new(AlwaysInsure(BasicStrategy::new(10)));
+            let rules = session_with(table.clone(), 4, 3000, strategy);
+            let text = HandHistory::from(rules.history()).to_string();
+            for line in [
+                "The house offered insurance",
+                "Seat 0 insured for 5",
+                "Seat 1 took even money",
+                "Seat 2 was paid 15 for insurance",
+                "for a Blackjack at even money",
+            ] {
+                assert!(text.contains(line), "No \"{}\" at {:?}", line, table);
+            }
+            assert_eq!(
+                table.dealer_peeks,
+                text.contains("The house peeked and has blackjack")
+            );
+
+            let replayed = text.parse::<HandHistory>().unwrap().replay(table).unwrap();
+            let state = replayed.current_state();
+            assert_eq!(rules.current_state().players, state.players);
+            assert_eq!(rules.current_state().seats, state.seats);
+            assert_eq!(rules.current_state().ledger, state.ledger);
+        }
+    }
+
+    #[test]
+    fn a_round_reads_like_a_hand_history() {
+        let mut rules = Rules::with_cards(
+            TableRules::vegas_strip(),
+            Shoe::stacked("8s 6h 8d Th 3c 9s Tc").unwrap(),
+            0,
+        );
+        rules.add_player().unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        rules.start_playing().unwrap();
+        rules.split(0).unwrap();
+        rules.stand(0).unwrap();
+        rules.stand(0).unwrap();
+        rules.done_playing().unwrap();
+        rules.new_game().unwrap();
+        rules.place_bet(0, 20).unwrap();
+
+        let expected = "\
+# Blackjack hand history
+Round 1
+A new player sat down in seat 0
+Player 0 bought in for 100
+Seat 0 bet 10
+The round started
+8s was dealt to seat 0 hand 0
+6h was dealt to the house
+8d was dealt to seat 0 hand 0
+Th was dealt to the house
+Seat 0 split hand 0
+3c was dealt to seat 0 hand 0
+Seat 0 stood on hand 0
+9s was dealt to seat 0 hand 1
+Seat 0 stood on hand 1
+Seat 0's turn is over
+Tc was dealt to the house
+The house finished with 6h Th Tc, hard 26
+Seat 0 hand 0 was paid 20 for a Win
+Seat 0 hand 1 was paid 20 for a Win
+The table was cleared
+
+Round 2
+Seat 0 bet 20
+";
+        assert_eq!(expected, HandHistory::from(rules.history()).to_string());
+        let replayed = expected.parse::<HandHistory>().unwrap();
+        assert_eq!(2, replayed.number_of_rounds());
+        assert!(replayed.replay(TableRules::vegas_strip()).is_ok());
+    }
+
+    #[test]
+    fn early_surrender_replays() {
+        let table = TableRules {
+            surrender: SurrenderRule::Early,
+            ..TableRules::vegas_strip()
+        };
+        let shoe = Shoe::stacked("Ts Kh 6d Ah").unwrap();
+        let mut rules = Rules::with_cards(table.clone(), shoe, 0);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.set_strategy(Box::new(BasicStrategy::new(10)));
+        rules.by_ref().take(3).for_each(drop);
+        assert_eq!(95, rules.current_state().players[0].bankroll);
+
+        let text = HandHistory::from(rules.history()).to_string();
+        assert!(text.contains("The house offered early surrender\nSeat 0 surrendered hand 0\n"));
+        let replayed = text.parse::<HandHistory>().unwrap().replay(table).unwrap();
+        assert_eq!(
+            rules.current_state().players,
+            replayed.current_state().players
+        );
+    }
+
+    #[test]
+    fn a_shoe_which_ran_out_replays() {
+        let mut rules = Rules::with_cards(
+            TableRules::vegas_strip(),
+            Shoe::stacked("Ts 6h 7d Th 9c").unwrap(),
+            0,
+        );
+        rules.add_player().unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        rules.start_playing().unwrap();
+        rules.stand(0).unwrap();
+        rules.done_playing().unwrap();
+        rules.new_game().unwrap();
+        rules.place_bet(0, 10).unwrap();
+        rules.start_playing().unwrap();
+        rules.stand(0).unwrap();
+
+        let text = HandHistory::from(rules.history()).to_string();
+        assert!(text.contains("Shuffle 1 of the shoe after it ran out"));
+        let replayed = text
+            .parse::<HandHistory>()
+            .unwrap()
+            .replay(TableRules::vegas_strip())
+            .unwrap();
+        assert_eq!(rules.current_state().seats, replayed.current_state().seats);
+        assert_eq!(rules.current_state().house, replayed.current_state().house);
+    }
+
+    #[test]
+    fn changed_histories_do_not_replay() {
+        let rules = session(TableRules::vegas_strip(), 5, 300);
+        let text = HandHistory::from(rules.history()).to_string();
+
+        let paid_more = text.replacen("was paid 20 for a Win", "was paid 25 for a Win", 1);
+        let error = paid_more
+            .parse::<HandHistory>()
+            .unwrap()
+            .replay(TableRules::vegas_strip())
+            .err()
+            .unwrap();
+        match error {
+            HandHistoryError::Mismatch {
+                line,
+                expected,
+                found,
+            } => {
+                assert_eq!(expected, paid_more.lines().nth(line - 1).unwrap());
+                assert!(expected.contains("paid 25"));
+                assert!(found.contains("paid 20"));
+            }
+            error => panic!("Expected a mismatch, not {}", error),
+        }
+
+        let other_table = text
+            .parse::<HandHistory>()
+            .unwrap()
+            .replay(TableRules::downtown_vegas());
+        assert!(other_table.is_err());
+
+        let garbled = text.replacen("Seat 0 bet 10", "Seat 0 bets ten", 1);
+        assert_eq!(
+            Err(HandHistoryError::Unreadable {
+                line: 10,
+                text: "Seat 0 bets ten".to_string(),
+            }),
+            garbled.parse::<HandHistory>()
+        );
+    }
+
+    #[test]
+    fn mismatches_are_found_on_the_line_they_were_read_from() {
+        let rules = session(TableRules::vegas_strip(), 5, 300);
+        let history = HandHistory::from(rules.history());
+        let text = history.to_string();
+        for (index, entry) in history.entries.iter().enumerate() {
+            let line = text.lines().nth(history.line_of(index) - 1);
+            assert_eq!(Some(entry.to_string().as_str()), line);
+        }
+
+        let mut annotated = String::new();
+        for line in text.lines() {
+            annotated.push_str(line);
+            annotated.push('\n');
+            if line.starts_with("Round") {
+                annotated.push_str("\n# Nobody tipped the dealer\n\n");
+            }
+        }
+        let paid_more = annotated.replacen("was paid 20 for a Win", "was paid 25 for a Win", 1);
+        let error = paid_more
+            .parse::<HandHistory>()
+            .unwrap()
+            .replay(TableRules::vegas_strip())
+            .err()
+            .unwrap();
+        match error {
+            HandHistoryError::Mismatch { line, expected, .. } => {
+                assert!(expected.contains("paid 25"));
+                assert_eq!(expected, paid_more.lines().nth(line - 1).unwrap());
+            }
+            error => panic!("Expected a mismatch, not {}", error),
+        }
+    }
+
+    #[test]
+    fn refused_decisions_say_why() {
+        let text = "Round 1\nA new player sat down in seat 0\nSeat 1 bet 10\n";
+        let error = text
+            .parse::<HandHistory>()
+            .unwrap()
+            .replay(TableRules::vegas_strip())
+            .err()
+            .unwrap();
+        assert_eq!(
+            "The rules refused \"Seat 1 bet 10\": There is no seat 1.",
+            error.to_string()
+        );
+    }
+}
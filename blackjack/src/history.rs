@@ -0,0 +1,561 @@
+use crate::{error::RuleError, event::Event, game_state::GameState, generation::Generation};
+use cards::prelude::Shoe;
+
+#[cfg(feature = "serde")]
+use crate::error::SaveError;
+
+/// History keeps every generation of a game along with a cursor marking the generation the game
+/// is at.  Moving the cursor back undoes the game and moving it forward again redoes it.  Adding a
+/// generation anywhere other than the end of the tree starts a new branch, and the branch which
+/// was there before is kept.
+///
+/// Every generation gets the next id as it is added, so the same game always makes the same
+/// history.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ GameState, History };
+///
+/// let mut history: History = Default::default();
+/// history.add_generation(Default::default());
+/// history.add_generation(Default::default());
+/// assert_eq!(2, history.generation_number());
+///
+/// history.undo().unwrap();
+/// history.undo().unwrap();
+/// assert_eq!(0, history.generation_number());
+///
+/// history.redo().unwrap();
+/// assert_eq!(1, history.generation_number());
+/// ```
+///
+/// A history read back in through serde is checked to hold together before it is used.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawHistory<S>"))]
+pub struct History<S = Shoe> {
+    /// Every generation, where each generation's id is its index.
+    generations: Vec<Generation<S>>,
+    /// The id of the generation the game is at.
+    cursor: usize,
+    /// The ids of the generations which have been undone, the most recently undone last.  Redo
+    /// follows them back until something new is added.
+    undone: Vec<usize>,
+}
+
+/// RawHistory is a history as it was read in, before it has been checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawHistory<S> {
+    generations: Vec<Generation<S>>,
+    cursor: usize,
+    undone: Vec<usize>,
+}
+
+/// Serde only keeps the text of an error, so the problem is handed back as text.
+#[cfg(feature = "serde")]
+impl<S> TryFrom<RawHistory<S>> for History<S> {
+    type Error = String;
+
+    fn try_from(raw: RawHistory<S>) -> Result<Self, Self::Error> {
+        History::from_parts(raw.generations, raw.cursor, raw.undone).map_err(|error| match error {
+            SaveError::Corrupt(problem) => problem,
+            error => error.to_string(),
+        })
+    }
+}
+
+impl<S: Default> Default for History<S> {
+    fn default() -> Self {
+        History::new(Default::default())
+    }
+}
+
+impl<S> History<S> {
+    /// Start a history at the given state.
+    ///
+    /// * `state`: The first state of the game
+    pub fn new(state: GameState<S>) -> Self {
+        History {
+            generations: vec![Generation::new(0, None, 0, state, vec![])],
+            cursor: 0,
+            undone: vec![],
+        }
+    }
+
+    /// The first generation of the game.  Every other generation is one of its branches, or a
+    /// branch of one of those.
+    pub fn root(&self) -> &Generation<S> {
+        &self.generations[0]
+    }
+
+    /// Look up a generation by its id.
+    ///
+    /// * `id`: The id of the generation
+    pub fn get(&self, id: usize) -> Option<&Generation<S>> {
+        self.generations.get(id)
+    }
+
+    /// The branches which came after a generation, oldest first.
+    ///
+    /// * `generation`: The generation to look after
+    pub fn branches<'a>(
+        &'a self,
+        generation: &'a Generation<S>,
+    ) -> impl Iterator<Item = &'a Generation<S>> + 'a {
+        generation
+            .branches()
+            .iter()
+            .map(move |&id| &self.generations[id])
+    }
+
+    /// Every generation in the order they were made.
+    pub fn iter(&self) -> impl Iterator<Item = &Generation<S>> {
+        self.generations.iter()
+    }
+
+    /// How many generations there are on every branch.
+    pub fn number_of_generations(&self) -> usize {
+        self.generations.len()
+    }
+
+    /// The generation the game is at.
+    pub fn current_generation(&self) -> &Generation<S> {
+        &self.generations[self.cursor]
+    }
+
+    /// The state of the game at the cursor.
+    pub fn current_state(&self) -> &GameState<S> {
+        self.current_generation().state()
+    }
+
+    /// How many generations the cursor is from the root.  The root is generation 0.
+    pub fn generation_number(&self) -> usize {
+        self.current_generation().number()
+    }
+
+    /// The generation number at the end of the line the cursor is on.  This is as far as redo can
+    /// go.
+    pub fn last_generation_number(&self) -> usize {
+        self.generation_number() + self.undone.len()
+    }
+
+    /// The ids of the generations from the root to the cursor.
+    pub fn path(&self) -> Vec<usize> {
+        self.path_to(self.cursor)
+            .expect("The cursor is always at a generation")
+    }
+
+    /// The ids of the generations from the root to the given generation, following the parent of
+    /// each generation back.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ GameState, History };
+    ///
+    /// let mut history: History = Default::default();
+    /// history.add_generation(Default::default());
+    /// history.add_generation(Default::default());
+    /// history.undo().unwrap();
+    /// history.add_generation(Default::default());
+    ///
+    /// assert_eq!(Some(vec![0, 1, 3]), history.path_to(3));
+    /// assert_eq!(Some(vec![0, 1, 2]), history.path_to(2));
+    /// assert_eq!(None, history.path_to(4));
+    /// ```
+    ///
+    /// * `id`: The id of the generation to find the way to
+    pub fn path_to(&self, id: usize) -> Option<Vec<usize>> {
+        let mut path = vec![self.get(id)?.id()];
+        while let Some(parent) = self.generations[*path.last()?].parent() {
+            path.push(parent);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every event from the root to the cursor, in the order they happened.  Together they are
+    /// how the game came to be in its current state.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Event, Rules };
+    ///
+    /// let mut rule: Rules = Default::default();
+    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// rule.place_bet(0, 10);
+    /// rule.undo();
+    ///
+    /// let events: Vec<&Event> = rule.history().events().collect();
+    /// assert_eq!(
+    ///     vec![
+    ///         &Event::PlayerJoined { seat: 0 },
+    ///         &Event::BoughtIn { player: 0, chips: 100 },
+    ///     ],
+    ///     events
+    /// );
+    /// ```
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        self.path()
+            .into_iter()
+            .flat_map(move |id| self.generations[id].events())
+    }
+
+    /// Add a state after the current generation and move the cursor to it.  Anything which was
+    /// undone can no longer be redone, but it is still kept in the tree.
+    ///
+    /// * `state`: The next state of the game
+    pub fn add_generation(&mut self, state: GameState<S>) {
+        self.add_generation_with_events(state, vec![]);
+    }
+
+    /// Add a state after the current generation along with the events which led to it, and move
+    /// the cursor to it.
+    ///
+    /// * `state`: The next state of the game
+    /// * `events`: What happened to get from the current state to the next one
+    pub fn add_generation_with_events(&mut self, state: GameState<S>, events: Vec<Event>) {
+        let id = self.generations.len();
+        let number = self.generation_number() + 1;
+        self.generations.push(Generation::new(
+            id,
+            Some(self.cursor),
+            number,
+            state,
+            events,
+        ));
+        self.generations[self.cursor].add_branch(id);
+
+        self.cursor = id;
+        self.undone.clear();
+    }
+
+    /// Move the cursor back one generation.
+    pub fn undo(&mut self) -> Result<(), RuleError> {
+        let parent = self
+            .current_generation()
+            .parent()
+            .ok_or(RuleError::NothingToUndo)?;
+        self.undone.push(self.cursor);
+        self.cursor = parent;
+        Ok(())
+    }
+
+    /// The generation redo would move the cursor to, if anything has been undone.
+    pub fn to_redo(&self) -> Option<&Generation<S>> {
+        self.undone.last().map(|&id| &self.generations[id])
+    }
+
+    /// Move the cursor forward along the branch which was last undone.
+    pub fn redo(&mut self) -> Result<(), RuleError> {
+        self.cursor = self.undone.pop().ok_or(RuleError::NothingToRedo)?;
+        Ok(())
+    }
+
+    /// Move the cursor to the given generation number on the line it is on, undoing or redoing as
+    /// many times as it takes.
+    ///
+    /// * `number`: The generation to move to, between 0 and the last generation number
+    pub fn jump_to(&mut self, number: usize) -> Result<(), RuleError> {
+        if number > self.last_generation_number() {
+            return Err(RuleError::NoSuchGeneration(number));
+        }
+        while self.generation_number() > number {
+            self.undo()?;
+        }
+        while self.generation_number() < number {
+            self.redo()?;
+        }
+        Ok(())
+    }
+
+    /// A new history holding only the generations from the root to the cursor.  The new history
+    /// is free to go its own way without changing this one.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ GameState, History };
+    ///
+    /// let mut history: History = Default::default();
+    /// history.add_generation(Default::default());
+    /// history.add_generation(Default::default());
+    /// history.undo().unwrap();
+    ///
+    /// let fork = history.fork();
+    /// assert_eq!(1, fork.generation_number());
+    /// assert_eq!(1, fork.last_generation_number());
+    /// ```
+    pub fn fork(&self) -> Self
+    where
+        S: Clone,
+    {
+        let mut path = self.path().into_iter();
+        let root = path.next().expect("There is always a root");
+        let mut fork = History::new(self.generations[root].state().clone());
+        for id in path {
+            let generation = &self.generations[id];
+            fork.add_generation_with_events(
+                generation.state().clone(),
+                generation.events().to_vec(),
+            );
+        }
+        fork
+    }
+
+    /// Put a history back together from its parts.  The parts are checked the same way as a
+    /// loaded history.
+    ///
+    /// * `generations`: Every generation, in the order they were made
+    /// * `cursor`: The id of the generation the game is at
+    /// * `undone`: The ids of the generations which can be redone, the next to redo last
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_parts(
+        generations: Vec<Generation<S>>,
+        cursor: usize,
+        undone: Vec<usize>,
+    ) -> Result<Self, SaveError> {
+        History {
+            generations,
+            cursor,
+            undone,
+        }
+        .checked()
+    }
+
+    /// Make sure a history which was read from a save holds together, since everything else
+    /// indexes into it without checking.  Each generation's id has to be its index, every
+    /// generation but the root has to come after its parent and be one of its branches, the
+    /// cursor and everything to redo have to be generations on one line, and every seat has to
+    /// belong to someone at the table.
+    #[cfg(feature = "serde")]
+    pub(crate) fn checked(self) -> Result<Self, SaveError> {
+        let corrupt = |problem: &str| Err(SaveError::Corrupt(problem.to_string()));
+        if self.generations.is_empty() {
+            return corrupt("there are no generations");
+        }
+        if self
+            .generations
+            .iter()
+            .enumerate()
+            .any(|(id, generation)| generation.id() != id)
+        {
+            return corrupt("the generations are out of order");
+        }
+
+        for (id, generation) in self.generations.iter().enumerate() {
+            let linked = match generation.parent() {
+                None => id == 0 && generation.number() == 0,
+                Some(parent) => {
+                    parent < id
+                        && self.generations[parent].number() + 1 == generation.number()
+                        && self.generations[parent].branches().contains(&id)
+                }
+            };
+            if !linked {
+                return corrupt("a generation does not follow on from its parent");
+            }
+            if generation
+                .branches()
+                .iter()
+                .any(|&branch| self.get(branch).and_then(Generation::parent) != Some(id))
+            {
+                return corrupt("a generation has a branch which does not follow on from it");
+            }
+            let state = generation.state();
+            if state
+                .seats
+                .iter()
+                .any(|seat| seat.player >= state.players.len())
+            {
+                return corrupt("a seat belongs to nobody at the table");
+            }
+        }
+        let branches: usize = self.generations.iter().map(|g| g.branches().len()).sum();
+        if branches != self.generations.len() - 1 {
+            return corrupt("a generation is a branch more than once");
+        }
+
+        if self.cursor >= self.generations.len() {
+            return corrupt("the cursor is not on the history");
+        }
+        let mut at = self.cursor;
+        for &id in self.undone.iter().rev() {
+            if self.get(id).and_then(Generation::parent) != Some(at) {
+                return corrupt("the generations to redo do not follow on from the cursor");
+            }
+            at = id;
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::History;
+    use crate::{error::RuleError, game_state::GameState};
+
+    /// A state which can be told apart from the others by its turn.
+    fn state(turn: usize) -> GameState {
+        GameState {
+            turn,
+            ..Default::default()
+        }
+    }
+
+    fn history_of(turns: usize) -> History {
+        let mut history = History::new(state(0));
+        for turn in 1..=turns {
+            history.add_generation(state(turn));
+        }
+        history
+    }
+
+    #[test]
+    fn the_cursor_follows_new_generations() {
+        let history = history_of(3);
+        assert_eq!(3, history.generation_number());
+        assert_eq!(3, history.current_state().turn);
+        assert_eq!(vec![0, 1, 2, 3], history.path());
+        assert_eq!(4, history.number_of_generations());
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history = history_of(3);
+
+        history.undo().unwrap();
+        history.undo().unwrap();
+        assert_eq!(1, history.current_state().turn);
+        assert_eq!(3, history.last_generation_number());
+
+        history.redo().unwrap();
+        assert_eq!(2, history.current_state().turn);
+        history.redo().unwrap();
+        assert_eq!(Err(RuleError::NothingToRedo), history.redo());
+        assert_eq!(3, history.current_state().turn);
+
+        history.jump_to(0).unwrap();
+        assert_eq!(0, history.current_state().turn);
+        assert_eq!(Err(RuleError::NothingToUndo), history.undo());
+    }
+
+    #[test]
+    fn adding_after_an_undo_starts_a_new_branch() {
+        let mut history = history_of(3);
+        history.jump_to(1).unwrap();
+        history.add_generation(state(7));
+
+        assert_eq!(2, history.generation_number());
+        assert_eq!(2, history.last_generation_number());
+        assert_eq!(Err(RuleError::NothingToRedo), history.redo());
+        assert_eq!(7, history.current_state().turn);
+        assert_eq!(vec![0, 1, 4], history.path());
+
+        let first = history.get(1).unwrap();
+        assert_eq!(&[2, 4], first.branches());
+        let turns: Vec<usize> = history
+            .branches(first)
+            .map(|branch| branch.state().turn)
+            .collect();
+        assert_eq!(vec![2, 7], turns);
+    }
+
+    #[test]
+    fn generations_link_back_to_the_root() {
+        let mut history = history_of(2);
+        history.undo().unwrap();
+        history.add_generation(state(5));
+        history.add_generation(state(6));
+
+        let ids: Vec<usize> = history.iter().map(|generation| generation.id()).collect();
+        assert_eq!(vec![0, 1, 2, 3, 4], ids);
+        let parents: Vec<Option<usize>> = history.iter().map(|g| g.parent()).collect();
+        assert_eq!(vec![None, Some(0), Some(1), Some(1), Some(3)], parents);
+        assert_eq!(Some(vec![0, 1, 3, 4]), history.path_to(4));
+        assert_eq!(3, history.get(4).unwrap().number());
+        assert!(history.get(5).is_none());
+    }
+
+    #[test]
+    fn jumping_stays_on_the_line() {
+        let mut history = history_of(5);
+        history.jump_to(2).unwrap();
+        assert_eq!(2, history.current_state().turn);
+        history.jump_to(4).unwrap();
+        assert_eq!(4, history.current_state().turn);
+        assert_eq!(Err(RuleError::NoSuchGeneration(6)), history.jump_to(6));
+        assert_eq!(4, history.generation_number());
+    }
+
+    #[test]
+    fn forks_keep_only_the_line_to_the_cursor() {
+        let mut history = history_of(4);
+        history.jump_to(1).unwrap();
+        history.add_generation(state(9));
+
+        let mut fork = history.fork();
+        assert_eq!(2, fork.generation_number());
+        assert_eq!(9, fork.current_state().turn);
+        assert_eq!(3, fork.number_of_generations());
+
+        fork.add_generation(state(10));
+        assert_eq!(9, history.current_state().turn);
+        assert_eq!(10, fork.current_state().turn);
+    }
+
+    #[test]
+    fn the_same_game_makes_the_same_history() {
+        let build = || {
+            let mut history = history_of(4);
+            history.jump_to(2).unwrap();
+            history.add_generation(state(8));
+            history
+        };
+        assert_eq!(build(), build());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn histories_round_trip() {
+        use crate::{rules::Rules, strategy::MimicTheDealer, table_rules::TableRules};
+        use cards::prelude::ContinuousShuffler;
+
+        let mut rules = Rules::with_seed(TableRules::vegas_strip(), 16);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 1000).unwrap();
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+        rules.by_ref().take(5).for_each(drop);
+        rules.jump_to(3).unwrap();
+        let history = rules.history();
+
+        let json = serde_json::to_string(history).unwrap();
+        assert_eq!(history, &serde_json::from_str::<History>(&json).unwrap());
+
+        let bytes = bincode::serialize(history).unwrap();
+        assert_eq!(history, &bincode::deserialize::<History>(&bytes).unwrap());
+
+        let csm = ContinuousShuffler::with_seed(6, 20, 16);
+        let mut rules = Rules::with_cards(TableRules::vegas_strip(), csm, 16);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 1000).unwrap();
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+        rules.by_ref().take(5).for_each(drop);
+
+        let bytes = bincode::serialize(rules.history()).unwrap();
+        let from_bytes: History<ContinuousShuffler> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(rules.history(), &from_bytes);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn histories_are_checked_as_they_are_read() {
+        let mut history: History = Default::default();
+        history.add_generation(Default::default());
+        let mut json = serde_json::to_value(&history).unwrap();
+        json["cursor"] = 5.into();
+
+        let error = serde_json::from_value::<History>(json).err().unwrap();
+        assert_eq!("the cursor is not on the history", error.to_string());
+    }
+}
@@ -0,0 +1,49 @@
+use player::Chips;
+
+/// The Ledger keeps track of every chip which comes to or leaves the table.  While chips are at
+/// the table they only move between the players, the house and the bets, so the chips in play
+/// should always match the ledger's balance.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ledger {
+    bought_in: Chips,
+    cashed_out: Chips,
+}
+
+impl Ledger {
+    /// Chips brought to the table.
+    pub fn buy_in(&mut self, chips: Chips) {
+        self.bought_in += chips;
+    }
+
+    /// Chips taken away from the table.
+    pub fn cash_out(&mut self, chips: Chips) {
+        self.cashed_out += chips;
+    }
+
+    /// Every chip which was ever brought to the table.
+    pub fn bought_in(&self) -> Chips {
+        self.bought_in
+    }
+
+    /// Every chip which was ever taken away from the table.
+    pub fn cashed_out(&self) -> Chips {
+        self.cashed_out
+    }
+
+    /// The chips which should be at the table right now.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::Ledger;
+    ///
+    /// let mut ledger: Ledger = Default::default();
+    /// ledger.buy_in(500);
+    /// ledger.cash_out(200);
+    ///
+    /// assert_eq!(300, ledger.balance());
+    /// ```
+    pub fn balance(&self) -> Chips {
+        self.bought_in - self.cashed_out
+    }
+}
@@ -1,5 +1,17 @@
+mod action;
+mod basic_strategy;
 mod error;
+mod event;
 mod game_state;
 mod generation;
+mod hand_history;
+mod history;
+mod ledger;
+mod playback;
 pub mod prelude;
 mod rules;
+#[cfg(feature = "serde")]
+mod save;
+mod shared;
+mod strategy;
+mod table_rules;
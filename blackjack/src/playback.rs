@@ -0,0 +1,434 @@
+use crate::{
+    action::Action, basic_strategy::Chart, error::RuleError, event::Event, game_state::GameState,
+    rules::Rules, strategy::Decision,
+};
+use cards::prelude::{CardSource, Shoe};
+
+/// How many generations playback moves through each second unless it is told otherwise.
+const DEFAULT_SPEED: f32 = 4.0;
+
+/// Advice is what basic strategy would have done at a decision a player made.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Advice {
+    /// The seat which made the decision.
+    pub seat: usize,
+    /// The hand the decision was made on.
+    pub hand: usize,
+    /// What basic strategy says to do.
+    pub advised: Action,
+    /// What the player did.
+    pub taken: Action,
+}
+
+impl Advice {
+    /// The player did something other than what basic strategy says.
+    pub fn is_mistake(&self) -> bool {
+        self.advised != self.taken
+    }
+}
+
+/// Playback steps through a finished session one generation at a time, the way a video player
+/// would.  It can play and pause, step forwards and backwards, seek to any generation and jump
+/// between rounds.  At every decision a player made it knows what basic strategy would have done,
+/// so the places a player went wrong can be found and shown.
+///
+/// Playback follows the line of the history the game was on, from the first generation to the
+/// last one which could be redone.  The position is the generation number.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ MimicTheDealer, Playback, Rules, TableRules };
+///
+/// let mut rule = Rules::with_seed(TableRules::vegas_strip(), 5);
+/// rule.add_player();
+/// rule.buy_in(0, 500);
+/// rule.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+/// rule.by_ref().take(30).for_each(drop);
+///
+/// let mut playback = Playback::new(rule).unwrap();
+/// assert_eq!(0, playback.position());
+///
+/// playback.play();
+/// playback.tick(1.0);
+/// assert_eq!(4, playback.position());
+///
+/// playback.next_round().unwrap();
+/// assert!(playback.round() >= 1);
+/// ```
+pub struct Playback<S: CardSource = Shoe> {
+    rules: Rules<S>,
+    /// The generation ids on the line, where the index is the generation number.
+    line: Vec<usize>,
+    /// The positions where a round started.
+    rounds: Vec<usize>,
+    /// The positions where a player did something other than basic strategy.
+    mistakes: Vec<usize>,
+    chart: Chart,
+    playing: bool,
+    /// Generations moved through each second while playing.
+    speed: f32,
+    /// Time played which has not moved the position yet, in generations.
+    pending: f32,
+}
+
+impl<S: CardSource> Playback<S> {
+    /// Get ready to play a game back from its first generation.
+    ///
+    /// * `rules`: The game to play back
+    pub fn new(mut rules: Rules<S>) -> Result<Self, RuleError> {
+        let history = rules.history_mut();
+        history.jump_to(history.last_generation_number())?;
+        let line = history.path();
+        history.jump_to(0)?;
+
+        let history = rules.history();
+        let events = |id: usize| history.get(id).map_or(&[][..], |g| g.events());
+        let rounds = line
+            .iter()
+            .enumerate()
+            .filter(|&(_, &id)| events(id).contains(&Event::RoundStarted))
+            .map(|(position, _)| position)
+            .collect();
+
+        let mut playback = Playback {
+            chart: Chart::new(rules.table_rules()),
+            rules,
+            line,
+            rounds,
+            mistakes: vec![],
+            playing: false,
+            speed: DEFAULT_SPEED,
+            pending: 0.0,
+        };
+        while playback.step_forward() {
+            if playback.advice().is_some_and(|advice| advice.is_mistake()) {
+                playback.mistakes.push(playback.position());
+            }
+        }
+        playback.seek(0)?;
+        Ok(playback)
+    }
+
+    /// How many generations there are to play back.
+    pub fn len(&self) -> usize {
+        self.line.len()
+    }
+
+    /// There is always at least the first generation to play back.
+    pub fn is_empty(&self) -> bool {
+        self.line.is_empty()
+    }
+
+    /// The generation number being shown.
+    pub fn position(&self) -> usize {
+        self.rules.history().generation_number()
+    }
+
+    /// The state of the game at the position.
+    pub fn state(&self) -> &GameState<S> {
+        self.rules.current_state()
+    }
+
+    /// What happened to get to the position.
+    pub fn events(&self) -> &[Event] {
+        self.rules.history().current_generation().events()
+    }
+
+    /// The rules the game is being played back with.
+    pub fn rules(&self) -> &Rules<S> {
+        &self.rules
+    }
+
+    /// Is playback moving forward by itself?
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Start moving forward by itself.  Playing from the end starts again from the beginning.
+    pub fn play(&mut self) {
+        if self.position() + 1 >= self.len() {
+            self.rules
+                .history_mut()
+                .jump_to(0)
+                .expect("The first generation is on the line");
+        }
+        self.playing = true;
+    }
+
+    /// Stop moving forward by itself.
+    pub fn pause(&mut self) {
+        self.playing = false;
+        self.pending = 0.0;
+    }
+
+    /// Play when paused and pause when playing.
+    pub fn toggle(&mut self) {
+        if self.playing {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    /// Generations moved through each second while playing.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Change how many generations are moved through each second while playing.
+    ///
+    /// * `speed`: Generations per second, at least one every ten seconds
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.1);
+    }
+
+    /// Let time pass.  While playing the position moves forward with it, and playback pauses
+    /// once it reaches the end.  Returns true when the position moved.
+    ///
+    /// * `seconds`: How much time passed
+    pub fn tick(&mut self, seconds: f32) -> bool {
+        if !self.playing {
+            return false;
+        }
+
+        self.pending += seconds * self.speed;
+        let mut moved = false;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            if !self.step_forward() {
+                self.pause();
+                break;
+            }
+            moved = true;
+        }
+        moved
+    }
+
+    /// Move forward one generation.  Returns false at the end.
+    pub fn step_forward(&mut self) -> bool {
+        self.rules.history_mut().redo().is_ok()
+    }
+
+    /// Move back one generation.  Returns false at the beginning.
+    pub fn step_back(&mut self) -> bool {
+        self.rules.history_mut().undo().is_ok()
+    }
+
+    /// Move to the given generation.
+    ///
+    /// * `position`: The generation number to show
+    pub fn seek(&mut self, position: usize) -> Result<(), RuleError> {
+        self.rules.history_mut().jump_to(position)
+    }
+
+    /// The round being shown, counted from 1.  Before the first round starts this is 0.
+    pub fn round(&self) -> usize {
+        let position = self.position();
+        self.rounds
+            .iter()
+            .filter(|&&start| start <= position)
+            .count()
+    }
+
+    /// How many rounds were played.
+    pub fn number_of_rounds(&self) -> usize {
+        self.rounds.len()
+    }
+
+    /// Move to the start of the next round.
+    pub fn next_round(&mut self) -> Result<(), RuleError> {
+        let position = self.position();
+        let next = self.rounds.iter().find(|&&start| start > position).copied();
+        self.seek(next.ok_or(RuleError::NoSuchGeneration(position + 1))?)
+    }
+
+    /// Move to the start of this round, or the round before when already at the start.
+    pub fn previous_round(&mut self) -> Result<(), RuleError> {
+        let position = self.position();
+        let previous = self.rounds.iter().rev().find(|&&start| start < position);
+        self.seek(previous.copied().unwrap_or(0))
+    }
+
+    /// The generation numbers where a player did something other than basic strategy.
+    pub fn mistakes(&self) -> &[usize] {
+        &self.mistakes
+    }
+
+    /// Move to the next place a player did something other than basic strategy.
+    pub fn next_mistake(&mut self) -> Result<(), RuleError> {
+        let position = self.position();
+        let next = self.mistakes.iter().find(|&&mistake| mistake > position);
+        self.seek(*next.ok_or(RuleError::NoSuchGeneration(position + 1))?)
+    }
+
+    /// When a player makes a decision at the position, what basic strategy would have done.  The
+    /// decision is the first event of the next generation on the line.
+    pub fn advice(&self) -> Option<Advice> {
+        let history = self.rules.history();
+        let next = history.get(*self.line.get(self.position() + 1)?)?;
+        let (seat, hand, taken) = match *next.events().first()? {
+            Event::PlayerHit { seat, hand } => (seat, hand, Action::Hit),
+            Event::PlayerStood { seat, hand } => (seat, hand, Action::Stand),
+            Event::PlayerDoubled { seat, hand } => (seat, hand, Action::Double),
+            Event::PlayerSplit { seat, hand } => (seat, hand, Action::Split),
+            Event::PlayerSurrendered { seat, hand } => (seat, hand, Action::Surrender),
+            _ => return None,
+        };
+
+        let state = self.state();
+        let legal_actions = self.rules.legal_actions(seat);
+        let decision = Decision {
+            seat,
+            hand: state.seats.get(seat)?.hands.get(hand)?,
+            dealer_up_card: state.dealer_up_card()?,
+            legal_actions: &legal_actions,
+            table: self.rules.table_rules(),
+            state,
+        };
+        Some(Advice {
+            seat,
+            hand,
+            advised: self.chart.decide(&decision),
+            taken,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Playback;
+    use crate::{
+        action::Action,
+        basic_strategy::BasicStrategy,
+        event::Event,
+        game_state::{GameState, Progress},
+        rules::Rules,
+        strategy::{Decision, MimicTheDealer, Strategy},
+        table_rules::TableRules,
+    };
+    use cards::prelude::Shoe;
+    use player::Chips;
+
+    fn session<T: Strategy + 'static>(strategy: T, steps: usize) -> Rules {
+        let mut rules = Rules::with_seed(TableRules::vegas_strip(), 12);
+        for seat in 0..2 {
+            rules.add_player().unwrap();
+            rules.buy_in(seat, 1000).unwrap();
+        }
+        rules.set_strategy(Box::new(strategy));
+        rules.by_ref().take(steps).for_each(drop);
+        rules
+    }
+
+    #[test]
+    fn playing_moves_forward_with_time_and_stops_at_the_end() {
+        let rules = session(MimicTheDealer { bet: 10 }, 40);
+        let end = rules.history().generation_number();
+        let mut playback = Playback::new(rules).unwrap();
+        assert_eq!(end + 1, playback.len());
+        assert_eq!(0, playback.position());
+        assert!(!playback.tick(10.0));
+
+        playback.set_speed(10.0);
+        playback.play();
+        assert!(!playback.tick(0.05));
+        assert!(playback.tick(0.05));
+        assert_eq!(1, playback.position());
+        playback.tick(1.0);
+        assert_eq!(11, playback.position());
+
+        playback.tick(1000.0);
+        assert_eq!(end, playback.position());
+        assert!(!playback.is_playing());
+
+        playback.play();
+        assert_eq!(0, playback.position());
+    }
+
+    #[test]
+    fn stepping_and_seeking() {
+        let mut playback = Playback::new(session(MimicTheDealer { bet: 10 }, 40)).unwrap();
+        assert!(!playback.step_back());
+        assert!(playback.step_forward());
+        assert!(playback.step_forward());
+        assert_eq!(2, playback.position());
+        assert!(playback.step_back());
+        assert_eq!(1, playback.position());
+
+        playback.seek(20).unwrap();
+        assert_eq!(20, playback.position());
+        playback.seek(5).unwrap();
+        assert_eq!(5, playback.position());
+        assert!(playback.seek(playback.len()).is_err());
+        playback.seek(playback.len() - 1).unwrap();
+        assert!(!playback.step_forward());
+    }
+
+    #[test]
+    fn jumping_between_rounds() {
+        let mut playback = Playback::new(session(MimicTheDealer { bet: 10 }, 80)).unwrap();
+        assert_eq!(0, playback.round());
+        assert!(playback.number_of_rounds() > 3);
+
+        playback.next_round().unwrap();
+        assert_eq!(1, playback.round());
+        assert_eq!(Progress::Playing, playback.state().progress);
+        let first = playback.position();
+
+        playback.next_round().unwrap();
+        assert_eq!(2, playback.round());
+        playback.step_forward();
+        playback.previous_round().unwrap();
+        assert_eq!(2, playback.round());
+        playback.previous_round().unwrap();
+        assert_eq!(first, playback.position());
+        playback.previous_round().unwrap();
+        assert_eq!(0, playback.position());
+    }
+
+    /// Hits every hand it is dealt until it has 17, whatever basic strategy says.
+    struct HitToSeventeen;
+
+    impl Strategy for HitToSeventeen {
+        fn bet(&mut self, _: usize, _: &GameState, _: &TableRules) -> Chips {
+            10
+        }
+
+        fn act(&mut self, decision: &Decision<Shoe>) -> Action {
+            if decision.hand.total() < 17 {
+                Action::Hit
+            } else {
+                Action::Stand
+            }
+        }
+    }
+
+    #[test]
+    fn basic_strategy_makes_no_mistakes() {
+        let playback = Playback::new(session(BasicStrategy::new(10), 400)).unwrap();
+        assert!(playback.mistakes().is_empty());
+    }
+
+    #[test]
+    fn mistakes_are_found_at_the_decision() {
+        let mut playback = Playback::new(session(HitToSeventeen, 400)).unwrap();
+        assert!(!playback.mistakes().is_empty());
+
+        playback.next_mistake().unwrap();
+        let advice = playback.advice().unwrap();
+        assert!(advice.is_mistake());
+        assert_eq!(Some(advice.seat), playback.state().current_seat());
+        assert!(matches!(advice.taken, Action::Hit | Action::Stand));
+
+        let (seat, hand) = (advice.seat, advice.hand);
+        let taken = match advice.taken {
+            Action::Hit => Event::PlayerHit { seat, hand },
+            Action::Stand => Event::PlayerStood { seat, hand },
+            Action::Double => Event::PlayerDoubled { seat, hand },
+            Action::Split => Event::PlayerSplit { seat, hand },
+            Action::Surrender => Event::PlayerSurrendered { seat, hand },
+        };
+        playback.step_forward();
+        assert_eq!(Some(&taken), playback.events().first());
+    }
+}
@@ -1,4 +1,34 @@
-pub type Generation = crate::generation::Generation;
-pub type GameState = crate::game_state::GameState;
+use cards::prelude::Shoe;
+
+pub type Action = crate::action::Action;
+pub type Generation<S = Shoe> = crate::generation::Generation<S>;
+pub type GameState<S = Shoe> = crate::game_state::GameState<S>;
+pub type History<S = Shoe> = crate::history::History<S>;
+pub type Ledger = crate::ledger::Ledger;
+pub type Shared<T> = crate::shared::Shared<T>;
+pub type Outcome = crate::game_state::Outcome;
+pub type Insurance = crate::game_state::Insurance;
 pub type Progress = crate::game_state::Progress;
-pub type Rules = crate::rules::Rules;
+pub type Rules<S = Shoe> = crate::rules::Rules<S>;
+pub type Halt = crate::rules::Halt;
+pub type RuleError = crate::error::RuleError;
+#[cfg(feature = "serde")]
+pub type SaveError = crate::error::SaveError;
+pub type TableRules = crate::table_rules::TableRules;
+pub type DoubleRestriction = crate::table_rules::DoubleRestriction;
+pub type SurrenderRule = crate::table_rules::SurrenderRule;
+pub type BlackjackPayout = crate::table_rules::BlackjackPayout;
+pub type Event = crate::event::Event;
+pub type Recipient = crate::event::Recipient;
+pub use crate::event::Observer;
+pub type HandHistory = crate::hand_history::HandHistory;
+pub type HandHistoryError = crate::error::HandHistoryError;
+pub type ReplayShoe = crate::hand_history::ReplayShoe;
+pub type Playback<S = Shoe> = crate::playback::Playback<S>;
+pub type Advice = crate::playback::Advice;
+pub type Decision<'a, S = Shoe> = crate::strategy::Decision<'a, S>;
+pub type MimicTheDealer = crate::strategy::MimicTheDealer;
+pub use crate::strategy::Strategy;
+pub type BasicStrategy = crate::basic_strategy::BasicStrategy;
+pub type Chart = crate::basic_strategy::Chart;
+pub type Play = crate::basic_strategy::Play;
@@ -1,27 +1,202 @@
 use crate::{
+    action::Action,
     error::RuleError,
-    game_state::{GameState, Progress},
-    generation::Generation,
+    event::{Event, Observer, Recipient},
+    game_state::{GameState, Insurance, Outcome, Progress},
+    history::History,
+    shared::Shared,
+    strategy::{Decision, Strategy},
+    table_rules::{SurrenderRule, TableRules},
 };
+use cards::prelude::{seeded_rng, CardSource, Rank, Shoe};
+use player::{Chips, Hand, HandleCards, Seat};
+use rand::{thread_rng, Rng};
+
+/// The house draws until it reaches this total.
+const DEALER_STANDS_AT: i32 = 17;
+
+/// Halt is why iterating over the rules stopped.
+#[derive(Debug, PartialEq)]
+pub enum Halt {
+    /// There is no strategy to make decisions.
+    NoStrategy,
+    /// The shoe needs to be reshuffled before the next game.
+    Reshuffle,
+    /// No player has enough chips left for the table minimum.
+    OutOfMoney,
+    /// Every seat sat out the next game.
+    NoBets,
+    /// The strategy made a bet or an action the rules refused.
+    Refused(RuleError),
+}
+
+/// Halt needs a standard way to be printed out.  That's what Display is for.
+impl std::fmt::Display for Halt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Halt::NoStrategy => write!(f, "No strategy"),
+            Halt::Reshuffle => write!(f, "Reshuffle"),
+            Halt::OutOfMoney => write!(f, "Out of money"),
+            Halt::NoBets => write!(f, "No bets"),
+            Halt::Refused(error) => write!(f, "Refused: {}", error),
+        }
+    }
+}
+
+impl From<RuleError> for Halt {
+    fn from(error: RuleError) -> Self {
+        Halt::Refused(error)
+    }
+}
+
+/// Who a card is being dealt to.
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    Seat(usize),
+    House,
+}
 
 /// Rules will be the hub for blackjack.  In the future Traits "might" be broken out from this impl
 /// , but I'm not sure at the momentA.
-pub struct Rules {
-    generation: Generation,
+///
+/// The cards come from a hand shuffled Shoe unless the rules are given another CardSource.
+pub struct Rules<S: CardSource = Shoe> {
+    history: History<S>,
+    table: TableRules,
+    strategy: Option<Box<dyn Strategy<S>>>,
+    observers: Vec<Box<dyn Observer<S>>>,
+    halted: Option<Halt>,
 }
 
-/// A default rule will have the game in the starting state
+/// A default rule will have the game in the starting state with a freshly shuffled shoe.
 impl Default for Rules {
     fn default() -> Self {
-        Self {
-            generation: Generation::new(Default::default()),
-        }
+        Rules::new(Default::default())
     }
 }
 
 impl Rules {
-    /// add_player will add a new player to the table.   The current state must be in starting for
-    /// this action to be done.
+    /// Create a new game which plays by the given table rules.  The game is in the starting
+    /// state with a freshly shuffled shoe.  The seed is picked at random and recorded in the game
+    /// state.
+    ///
+    /// * `table`: The house rules for this table
+    pub fn new(table: TableRules) -> Self {
+        Rules::with_seed(table, thread_rng().gen())
+    }
+
+    /// Create a new game which shuffles every shoe from the given seed.  Two games with the same
+    /// seed deal exactly the same cards.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Rules, TableRules };
+    ///
+    /// let first = Rules::with_seed(TableRules::vegas_strip(), 2022);
+    /// let second = Rules::with_seed(TableRules::vegas_strip(), 2022);
+    ///
+    /// assert_eq!(2022, first.current_state().seed);
+    /// assert_eq!(first.current_state().shoe, second.current_state().shoe);
+    /// ```
+    ///
+    /// * `table`: The house rules for this table
+    /// * `seed`: The seed every shoe is shuffled from
+    pub fn with_seed(table: TableRules, seed: u64) -> Self {
+        let shoe = table.shuffled_shoe(seed, 0);
+        Rules::with_cards(table, shoe, seed)
+    }
+}
+
+impl<S: CardSource> Rules<S> {
+    /// Create a new game which deals from the given cards.  The cards are dealt as they are, the
+    /// seed is only used when they are reshuffled.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Rules, TableRules };
+    /// use cards::prelude::{ ContinuousShuffler, HasCards };
+    ///
+    /// let csm = ContinuousShuffler::with_seed(6, 20, 7);
+    /// let mut rule = Rules::with_cards(TableRules::vegas_strip(), csm, 7);
+    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// rule.place_bet(0, 10);
+    /// assert!(rule.start_playing().is_ok());
+    ///
+    /// assert_eq!(308, rule.current_state().shoe.cards_left());
+    /// assert!(!rule.needs_reshuffle());
+    /// ```
+    ///
+    /// * `table`: The house rules for this table
+    /// * `cards`: Where the cards are dealt from
+    /// * `seed`: The seed every reshuffle is shuffled from
+    pub fn with_cards(table: TableRules, cards: S, seed: u64) -> Self {
+        Self {
+            history: History::new(GameState {
+                progress: Default::default(),
+                house: Default::default(),
+                players: Default::default(),
+                seats: vec![],
+                shoe: cards,
+                turn: 0,
+                results: Default::default(),
+                insurance: Default::default(),
+                ledger: Default::default(),
+                seed,
+                shuffles: 0,
+            }),
+            table,
+            strategy: None,
+            observers: vec![],
+            halted: None,
+        }
+    }
+
+    /// Pick a game back up from its history.  The game carries on from the current state and
+    /// needs a strategy set again before it can be used as an iterator.
+    ///
+    /// * `table`: The house rules the game was played by
+    /// * `history`: Everything which happened in the game so far
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_history(table: TableRules, history: History<S>) -> Self {
+        Self {
+            history,
+            table,
+            strategy: None,
+            observers: vec![],
+            halted: None,
+        }
+    }
+
+    /// Set the strategy which makes every bet and decision when the rules are used as an
+    /// iterator.
+    ///
+    /// * `strategy`: The decision maker for every seat at the table
+    pub fn set_strategy(&mut self, strategy: Box<dyn Strategy<S>>) {
+        self.strategy = Some(strategy);
+    }
+
+    /// Add an observer which is told about every event from now on.  Observers are told in the
+    /// order they were added.
+    ///
+    /// * `observer`: Something which wants to watch the game
+    pub fn add_observer(&mut self, observer: Box<dyn Observer<S>>) {
+        self.observers.push(observer);
+    }
+
+    /// Why iterating over the rules last stopped.  This is cleared as soon as the iterator moves
+    /// the game forward again.
+    pub fn halted(&self) -> Option<&Halt> {
+        self.halted.as_ref()
+    }
+
+    /// The house rules this game is played by.
+    pub fn table_rules(&self) -> &TableRules {
+        &self.table
+    }
+
+    /// add_player will add a new player to the table, sitting in a new seat of their own.   The
+    /// current state must be in starting for this action to be done.
     ///
     /// Example:
     /// ```
@@ -32,81 +207,639 @@ impl Rules {
     /// rule.add_player();
     ///
     /// assert_eq!(2, rule.current_state().players.len());
+    /// assert_eq!(1, rule.current_state().seats[1].player);
     /// ```
     pub fn add_player(&mut self) -> Result<(), RuleError> {
         if !self.is_starting() {
-            return Err(RuleError::InvalidState(self.current_progress()));
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
         }
 
         let mut gs = self.current_state().clone();
 
         gs.players.push(Default::default());
-        self.generation.add_generation(gs);
+        gs.seats.push(Seat::new(gs.players.len() - 1).into());
+        let seat = gs.seats.len() - 1;
+        self.commit(gs, vec![Event::PlayerJoined { seat }]);
+        Ok(())
+    }
+
+    /// A player already at the table takes another seat.  The new seat is bet on and played by
+    /// itself, after every seat taken before it, but its bets come out of the same bankroll.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::Rules;
+    ///
+    /// let mut rule: Rules = Default::default();
+    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// assert!(rule.take_seat(0).is_ok());
+    /// rule.place_bet(0, 10);
+    /// rule.place_bet(1, 25);
+    ///
+    /// let state = rule.current_state();
+    /// assert_eq!(vec![0, 1], state.seats_of(0).collect::<Vec<_>>());
+    /// assert_eq!(65, state.players[0].bankroll);
+    /// ```
+    ///
+    /// * `player`: The player taking the seat
+    pub fn take_seat(&mut self, player: usize) -> Result<(), RuleError> {
+        if !self.is_starting() {
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+        if player >= self.current_state().players.len() {
+            return Err(RuleError::NoSuchPlayer(player));
+        }
+
+        let mut gs = self.current_state().clone();
+
+        gs.seats.push(Seat::new(player).into());
+        let seat = gs.seats.len() - 1;
+        self.commit(gs, vec![Event::SeatTaken { player, seat }]);
+        Ok(())
+    }
+
+    /// A player brings chips to the table.  The chips are written into the ledger so they can be
+    /// accounted for.  A buy-in has to bring at least one chip.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Rules, RuleError };
+    ///
+    /// let mut rule: Rules = Default::default();
+    /// rule.add_player();
+    /// assert!(rule.buy_in(0, 500).is_ok());
+    /// assert_eq!(Err(RuleError::InvalidBuyIn(-50)), rule.buy_in(0, -50));
+    ///
+    /// assert_eq!(500, rule.current_state().players[0].bankroll);
+    /// assert_eq!(500, rule.current_state().ledger.balance());
+    /// ```
+    pub fn buy_in(&mut self, player: usize, chips: Chips) -> Result<(), RuleError> {
+        if !self.is_starting() {
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+        if chips <= 0 {
+            return Err(RuleError::InvalidBuyIn(chips));
+        }
+
+        let mut gs = self.current_state().clone();
+        gs.players
+            .get_mut(player)
+            .ok_or(RuleError::NoSuchPlayer(player))?
+            .bankroll += chips;
+        gs.ledger.buy_in(chips);
+        self.commit(gs, vec![Event::BoughtIn { player, chips }]);
+        Ok(())
+    }
+
+    /// A player takes every chip they have away from the table, including the bets they have
+    /// down in every one of their seats.  The chips taken are returned.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::Rules;
+    ///
+    /// let mut rule: Rules = Default::default();
+    /// rule.add_player();
+    /// rule.buy_in(0, 500);
+    /// rule.place_bet(0, 25);
+    ///
+    /// assert_eq!(Ok(500), rule.cash_out(0));
+    /// assert_eq!(0, rule.current_state().ledger.balance());
+    /// ```
+    pub fn cash_out(&mut self, player: usize) -> Result<Chips, RuleError> {
+        if !self.is_starting() {
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+
+        let mut gs = self.current_state().clone();
+        let mut chips = gs
+            .players
+            .get_mut(player)
+            .map(|player| std::mem::take(&mut player.bankroll))
+            .ok_or(RuleError::NoSuchPlayer(player))?;
+
+        for seat in gs.seats.iter_mut().filter(|seat| seat.player == player) {
+            chips += seat.total_bet();
+            seat.trash();
+        }
+        gs.ledger.cash_out(chips);
+        self.commit(gs, vec![Event::CashedOut { player, chips }]);
+        Ok(chips)
+    }
+
+    /// Place the bet on a seat for the next game.  A new bet replaces the old one, and a bet of
+    /// zero sits the seat out of the next game.  The bet has to be within the table limits and the
+    /// player in the seat needs the chips to cover it.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Rules, RuleError };
+    ///
+    /// let mut rule: Rules = Default::default();
+    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    ///
+    /// assert!(rule.place_bet(0, 1).is_err());
+    /// assert!(rule.place_bet(0, 200).is_err());
+    /// assert!(rule.place_bet(0, 25).is_ok());
+    /// assert_eq!(75, rule.current_state().players[0].bankroll);
+    /// ```
+    pub fn place_bet(&mut self, seat: usize, bet: Chips) -> Result<(), RuleError> {
+        if !self.is_starting() {
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+        if bet != 0 && !self.table.allows_bet(bet) {
+            return Err(RuleError::BetOutOfRange {
+                bet,
+                min: self.table.min_bet,
+                max: self.table.max_bet,
+            });
+        }
+
+        let mut gs = self.current_state().clone();
+        let place = gs.seats.get_mut(seat).ok_or(RuleError::NoSuchSeat(seat))?;
+        let player = &mut gs.players[place.player];
+
+        let available = player.bankroll + place.total_bet();
+        if bet > available {
+            return Err(RuleError::NotEnoughChips {
+                seat,
+                needed: bet,
+                available,
+            });
+        }
+        player.bankroll = available - bet;
+        place.hands[0].bet = bet;
+        self.commit(gs, vec![Event::BetPlaced { seat, bet }]);
+        Ok(())
+    }
+
+    /// The cut card has come out of the shoe, so it needs to be reshuffled before the next game.
+    /// The round it came out in is always played to the end.
+    pub fn needs_reshuffle(&self) -> bool {
+        self.current_state().shoe.reshuffle_pending()
+    }
+
+    /// Gather the discard tray back into the shoe and shuffle it with the next shuffle from the
+    /// seed.  This can only be done between games, when every card is in the shoe or the tray.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::Rules;
+    ///
+    /// let mut rule: Rules = Default::default();
+    /// assert!(rule.reshuffle().is_ok());
+    /// assert!(!rule.needs_reshuffle());
+    /// ```
+    pub fn reshuffle(&mut self) -> Result<(), RuleError> {
+        if !self.is_starting() {
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+
+        let mut gs = self.current_state().clone();
+
+        gs.shuffles += 1;
+        gs.shoe
+            .reshuffle_with(&mut seeded_rng(gs.seed, gs.shuffles));
+        let shuffles = gs.shuffles;
+        self.commit(gs, vec![Event::Reshuffled { shuffles }]);
         Ok(())
     }
 
     /// Change the state from starting to playing.   This should only occur when the game state is
-    /// in the starting state.
+    /// in the starting state.  The shoe is replaced once it is dealt past the table's penetration,
+    /// then every seat with a bet down and the house are dealt two cards.  Each card dealt is
+    /// its own generation.  Without a hole card the house is only dealt its up card.  Nothing is
+    /// dealt unless every card can be, so a shoe which runs out leaves the game still starting.
+    ///
+    /// When the house shows an ace and the table offers insurance, the game waits for every seat
+    /// in it to insure.  At a table with early surrender the seats are asked the same way
+    /// whenever the house is about to peek, so they can give up their hands first.  Otherwise the
+    /// house peeks for blackjack if the table says so and play starts.
     ///
     /// Example:
     /// ```
     /// use blackjack::prelude::{ Progress, Rules };
+    /// use player::HandleCards;
     ///
     /// let mut rule: Rules = Default::default();
     /// rule.add_player();
     /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// rule.place_bet(0, 10);
     /// assert!(rule.start_playing().is_ok());
     ///
-    /// assert_eq!(&Progress::Playing, rule.current_progress());
+    /// let state = rule.current_state();
+    /// assert!(matches!(state.progress, Progress::Playing | Progress::Insurance));
+    /// assert_eq!(2, state.house.hand.number_of_cards());
+    /// assert_eq!(2, state.seats[0].hands[0].number_of_cards());
+    /// assert_eq!(0, state.seats[1].hands[0].number_of_cards());
     /// ```
     pub fn start_playing(&mut self) -> Result<(), RuleError> {
         if !self.is_starting() {
-            return Err(RuleError::InvalidState(self.current_progress()));
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+
+        if self.needs_reshuffle() {
+            self.reshuffle()?;
         }
 
         let mut gs = self.current_state().clone();
 
         gs.progress = Progress::Playing;
-        self.generation.add_generation(gs);
+        gs.turn = 0;
+
+        let mut targets = vec![];
+        for round in 0..2 {
+            targets.extend(
+                (0..gs.seats.len())
+                    .filter(|&seat| gs.seats[seat].is_betting())
+                    .map(Target::Seat),
+            );
+            if round == 0 || self.table.hole_card {
+                targets.push(Target::House);
+            }
+        }
+
+        let mut dealt = vec![(gs.clone(), vec![Event::RoundStarted])];
+        for target in targets {
+            let mut events = vec![];
+            deal(&mut gs, target, &mut events)?;
+            dealt.push((gs.clone(), events));
+        }
+        for (gs, events) in dealt {
+            self.commit(gs, events);
+        }
+
+        let insurance = self.insurance_offered();
+        let early_surrender = self.early_surrender_offered();
+        let gs = self.current_state();
+        if (insurance || early_surrender) && gs.seats.iter().any(|seat| seat.is_betting()) {
+            let mut gs = gs.clone();
+            gs.progress = Progress::Insurance;
+            gs.insurance = vec![Insurance::Declined; gs.seats.len()].into();
+            gs.turn = next_to_insure(&gs.seats, 0);
+            let mut events = vec![];
+            if insurance {
+                events.push(Event::InsuranceOffered);
+            }
+            if early_surrender {
+                events.push(Event::SurrenderOffered);
+            }
+            self.commit(gs, events);
+            return Ok(());
+        }
+
+        self.peek();
+        self.finish_turns()
+    }
+
+    /// Answer the insurance offer for the given seat.  A seat can insure for up to
+    /// half of its bet, and a seat with a blackjack can take even money instead.  At a table with
+    /// early surrender a seat can give up its hand for half of its bet instead.  Seats answer in
+    /// order, and once the last one has the house peeks if the table says so and play starts.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Insurance, Progress, Rules, TableRules };
+    /// use cards::prelude::Shoe;
+    ///
+    /// let shoe = Shoe::stacked("Ts As 7d 6h").unwrap();
+    /// let mut rule = Rules::with_cards(TableRules::vegas_strip(), shoe, 0);
+    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// rule.place_bet(0, 10);
+    /// rule.start_playing();
+    /// assert_eq!(Some(0), rule.current_state().insuring_seat());
+    ///
+    /// assert!(rule.insure(0, Insurance::Taken(6)).is_err());
+    /// assert!(rule.insure(0, Insurance::Taken(-5)).is_err());
+    /// assert!(rule.insure(0, Insurance::Taken(5)).is_ok());
+    /// assert_eq!(85, rule.current_state().players[0].bankroll);
+    /// assert_eq!(&Progress::Playing, rule.current_progress());
+    /// ```
+    ///
+    /// * `seat`: The seat answering
+    /// * `insurance`: What the seat chose
+    pub fn insure(&mut self, seat: usize, insurance: Insurance) -> Result<(), RuleError> {
+        if self.current_progress() != &Progress::Insurance {
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+
+        let gs = self.current_state();
+        let place = gs.seats.get(seat).ok_or(RuleError::NoSuchSeat(seat))?;
+        if gs.insuring_seat() != Some(seat) {
+            return Err(RuleError::NotYourTurn(seat));
+        }
+
+        let player = &gs.players[place.player];
+        let hand = &place.hands[0];
+        let insurance = match insurance {
+            Insurance::Taken(0) => Insurance::Declined,
+            Insurance::Taken(_) | Insurance::EvenMoney if !self.insurance_offered() => {
+                return Err(RuleError::InsuranceNotOffered(seat))
+            }
+            Insurance::Surrendered if !self.early_surrender_offered() => {
+                return Err(RuleError::IllegalAction {
+                    seat,
+                    action: Action::Surrender,
+                    reason: "the table does not allow early surrender",
+                })
+            }
+            Insurance::Taken(chips) if chips < 0 || chips > hand.bet / 2 => {
+                return Err(RuleError::InsuranceOutOfRange {
+                    seat,
+                    chips,
+                    max: hand.bet / 2,
+                })
+            }
+            Insurance::Taken(chips) if chips > player.bankroll => {
+                return Err(RuleError::NotEnoughChips {
+                    seat,
+                    needed: chips,
+                    available: player.bankroll,
+                })
+            }
+            Insurance::EvenMoney if !hand.is_blackjack() => {
+                return Err(RuleError::NoBlackjack(seat))
+            }
+            insurance => insurance,
+        };
+
+        let mut gs = gs.clone();
+        let player = gs.seats[seat].player;
+        gs.players[player].bankroll -= insurance.chips();
+        gs.insurance[seat] = insurance;
+        if insurance == Insurance::Surrendered {
+            gs.seats[seat].hands[0].surrendered = true;
+        }
+        gs.turn = next_to_insure(&gs.seats, seat + 1);
+        if gs.turn == gs.seats.len() {
+            gs.progress = Progress::Playing;
+            gs.turn = 0;
+        }
+        let event = match insurance {
+            Insurance::EvenMoney => Event::TookEvenMoney { seat },
+            Insurance::Surrendered => Event::PlayerSurrendered { seat, hand: 0 },
+            insurance => Event::Insured {
+                seat,
+                chips: insurance.chips(),
+            },
+        };
+        self.commit(gs, vec![event]);
+
+        if self.is_playing() {
+            self.peek();
+            self.finish_turns()?;
+        }
         Ok(())
     }
 
-    /// This will mark the game as done playing
+    /// Take an action for the given seat.  This is the one entry point for playing a hand; the
+    /// action is checked against the current state and an error explains why an illegal action was
+    /// refused.  Once the hand is finished the turn passes to the seat's next hand or the next
+    /// seat.
     ///
     /// Example:
     /// ```
-    /// use blackjack::prelude::{ Progress, Rules };
+    /// use blackjack::prelude::{ Action, Rules, RuleError, TableRules };
+    /// use cards::prelude::Shoe;
+    ///
+    /// let shoe = Shoe::stacked("Ts 6h 7d 7h").unwrap();
+    /// let mut rule = Rules::with_cards(TableRules::vegas_strip(), shoe, 0);
+    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// rule.place_bet(0, 10);
+    /// assert!(rule.act(0, Action::Hit).is_err());
+    ///
+    /// rule.start_playing();
+    /// assert_eq!(Err(RuleError::NoSuchSeat(3)), rule.act(3, Action::Stand));
+    /// assert!(rule.act(0, Action::Stand).is_ok());
+    /// assert_eq!(None, rule.current_state().current_seat());
+    /// ```
+    pub fn act(&mut self, seat: usize, action: Action) -> Result<(), RuleError> {
+        self.check_action(seat, action)?;
+
+        let mut gs = self.current_state().clone();
+        let place = &mut gs.seats[seat];
+        let player = place.player;
+        let active = place.active;
+
+        let mut events = vec![];
+        match action {
+            Action::Hit => {
+                events.push(Event::PlayerHit { seat, hand: active });
+                deal(&mut gs, Target::Seat(seat), &mut events)?;
+            }
+            Action::Stand => {
+                place.active += 1;
+                events.push(Event::PlayerStood { seat, hand: active });
+            }
+            Action::Double => {
+                let hand = &mut place.hands[active];
+                gs.players[player].bankroll -= hand.bet;
+                hand.bet *= 2;
+                hand.doubled = true;
+                events.push(Event::PlayerDoubled { seat, hand: active });
+                deal(&mut gs, Target::Seat(seat), &mut events)?;
+            }
+            Action::Split => {
+                let hand = &mut place.hands[active];
+                let mut other = hand.split_pair().expect("A checked split must be a pair");
+                other.bet = hand.bet;
+                gs.players[player].bankroll -= other.bet;
+                place.hands.insert(active + 1, other);
+                events.push(Event::PlayerSplit { seat, hand: active });
+                deal(&mut gs, Target::Seat(seat), &mut events)?;
+            }
+            Action::Surrender => {
+                place.hands[active].surrendered = true;
+                events.push(Event::PlayerSurrendered { seat, hand: active });
+            }
+        }
+        self.commit(gs, events);
+
+        self.finish_turns()
+    }
+
+    /// Every action the player in the given seat is allowed to take right now.  When it is not
+    /// the seat's turn there are no legal actions.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Action, Rules, TableRules };
+    /// use cards::prelude::Shoe;
+    ///
+    /// let shoe = Shoe::stacked("Ts 6h 7d 7h").unwrap();
+    /// let mut rule = Rules::with_cards(TableRules::vegas_strip(), shoe, 0);
+    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// rule.place_bet(0, 10);
+    /// assert!(rule.legal_actions(0).is_empty());
+    ///
+    /// rule.start_playing();
+    /// assert_eq!(
+    ///     vec![Action::Hit, Action::Stand, Action::Double, Action::Surrender],
+    ///     rule.legal_actions(0)
+    /// );
+    /// ```
+    pub fn legal_actions(&self, seat: usize) -> Vec<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .filter(|&action| self.check_action(seat, action).is_ok())
+            .collect()
+    }
+
+    /// The player in the given seat takes another card.
+    pub fn hit(&mut self, seat: usize) -> Result<(), RuleError> {
+        self.act(seat, Action::Hit)
+    }
+
+    /// The player in the given seat is done taking cards on this hand.
+    pub fn stand(&mut self, seat: usize) -> Result<(), RuleError> {
+        self.act(seat, Action::Stand)
+    }
+
+    /// The player in the given seat doubles their bet and takes exactly one more card.
+    pub fn double(&mut self, seat: usize) -> Result<(), RuleError> {
+        self.act(seat, Action::Double)
+    }
+
+    /// The player in the given seat splits their pair into two hands.
+    pub fn split(&mut self, seat: usize) -> Result<(), RuleError> {
+        self.act(seat, Action::Split)
+    }
+
+    /// The player in the given seat gives up their hand for half of their bet.
+    pub fn surrender(&mut self, seat: usize) -> Result<(), RuleError> {
+        self.act(seat, Action::Surrender)
+    }
+
+    /// This will mark the game as done playing.  Once every seat has taken its turn the house
+    /// plays out its hand and each seat is settled against it.  The house always finishes with
+    /// at least two cards.  Every bet is paid out, so the chips left on the table move to the
+    /// players or the house.  Insurance pays 2:1 when the house has blackjack.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Insurance, Progress, Rules };
     ///
     /// let mut rule: Rules = Default::default();
     /// rule.add_player();
     /// rule.add_player();
+    /// for seat in 0..2 {
+    ///     rule.buy_in(seat, 100);
+    ///     rule.place_bet(seat, 10);
+    /// }
     /// assert!(rule.start_playing().is_ok());
+    /// while let Some(seat) = rule.current_state().insuring_seat() {
+    ///     rule.insure(seat, Insurance::Declined);
+    /// }
+    /// while let Some(seat) = rule.current_state().current_seat() {
+    ///     assert!(rule.done_playing().is_err());
+    ///     rule.stand(seat);
+    /// }
     /// assert!(rule.done_playing().is_ok());
     ///
     /// assert_eq!(&Progress::Done, rule.current_progress());
+    /// assert_eq!(2, rule.current_state().results.len());
+    /// assert_eq!(0, rule.current_state().chips_on_table());
     /// ```
     pub fn done_playing(&mut self) -> Result<(), RuleError> {
         if !self.is_playing() {
-            return Err(RuleError::InvalidState(self.current_progress()));
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+        if self.current_state().current_seat().is_some() {
+            return Err(RuleError::PlayersStillActing);
+        }
+
+        let live_hands = self
+            .current_state()
+            .seats
+            .iter()
+            .filter(|seat| seat.is_betting())
+            .flat_map(|seat| seat.hands.iter())
+            .any(|hand| !hand.is_bust() && !hand.is_blackjack() && !hand.surrendered);
+        while self.current_state().house.hand.number_of_cards() < 2
+            || (live_hands && self.house_draws(&self.current_state().house.hand))
+        {
+            self.deal_to(Target::House)?;
         }
 
         let mut gs = self.current_state().clone();
 
+        let house_hand = gs.house.hand.clone();
+        let house_blackjack = house_hand.is_blackjack();
+        let only_original_bets = house_blackjack && self.table.original_bets_only;
+        let mut results = vec![];
+        let mut events = vec![];
+        for (seat, place) in gs.seats.iter().enumerate() {
+            let mut outcomes = vec![];
+            if place.is_betting() {
+                let player = &mut gs.players[place.player];
+                let insurance = gs.insurance.get(seat).copied().unwrap_or_default();
+                let original_bet = original_bet(place);
+                for (index, hand) in place.hands.iter().enumerate() {
+                    let mut outcome = match insurance {
+                        Insurance::EvenMoney => Outcome::EvenMoney,
+                        _ => settle(hand, &house_hand, &self.table),
+                    };
+                    // Only the first bet can be lost, everything added to it is handed back.
+                    let at_risk = match index {
+                        _ if !only_original_bets => hand.bet,
+                        0 => original_bet,
+                        _ => {
+                            outcome = Outcome::Push;
+                            0
+                        }
+                    };
+                    let payout = self.table.payout(outcome, at_risk) + hand.bet - at_risk;
+
+                    player.bankroll += payout;
+                    gs.house.bankroll += hand.bet - payout;
+                    outcomes.push(outcome);
+                    events.push(Event::Payout {
+                        seat,
+                        hand: index,
+                        outcome,
+                        chips: payout,
+                    });
+                }
+                if let Insurance::Taken(chips) = insurance {
+                    let paid = if house_blackjack { chips * 3 } else { 0 };
+                    player.bankroll += paid;
+                    gs.house.bankroll += chips - paid;
+                    events.push(Event::InsurancePaid { seat, chips: paid });
+                }
+            }
+            results.push(outcomes);
+        }
+        gs.results = results.into();
         gs.progress = Progress::Done;
-        self.generation.add_generation(gs);
+        self.commit(gs, events);
         Ok(())
     }
 
-    /// This will create a new game, but only after the current game is done
+    /// This will create a new game, but only after the current game is done.  Every hand is
+    /// cleared from the table into the discard tray, along with the bets which were paid out.
     ///
     /// Example:
     /// ```
-    /// use blackjack::prelude::{ Progress, Rules };
+    /// use blackjack::prelude::{ Insurance, Progress, Rules };
     ///
     /// let mut rule: Rules = Default::default();
     /// rule.add_player();
-    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// rule.place_bet(0, 10);
     /// assert!(rule.start_playing().is_ok());
+    /// while let Some(seat) = rule.current_state().insuring_seat() {
+    ///     rule.insure(seat, Insurance::Declined);
+    /// }
+    /// while let Some(seat) = rule.current_state().current_seat() {
+    ///     rule.stand(seat);
+    /// }
     /// assert!(rule.done_playing().is_ok());
     /// assert!(rule.new_game().is_ok());
     ///
@@ -114,16 +847,24 @@ impl Rules {
     /// ```
     pub fn new_game(&mut self) -> Result<(), RuleError> {
         if !self.is_done() {
-            return Err(RuleError::InvalidState(self.current_progress()));
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
         }
 
         let mut gs = self.current_state().clone();
 
+        for seat in gs.seats.iter_mut() {
+            let cards = seat.trash();
+            gs.shoe.discard(cards);
+        }
+        let cards = gs.house.hand.trash();
+        gs.shoe.discard(cards);
+        gs.results = Default::default();
+        gs.insurance = Default::default();
+        gs.turn = 0;
         gs.progress = Progress::Starting;
-        self.generation.add_generation(gs);
+        self.commit(gs, vec![Event::TableCleared]);
         Ok(())
     }
-
     /// Check the current progress of the blackjack game.
     ///
     /// Example:
@@ -197,39 +938,682 @@ impl Rules {
     /// assert!(game_state.players.is_empty());
     /// assert_eq!(Progress::Starting, game_state.progress);
     ///
-    pub fn current_state(&self) -> &GameState {
-        self.generation.current_state()
+    pub fn current_state(&self) -> &GameState<S> {
+        self.history.current_state()
     }
-}
 
-impl Iterator for Rules {
-    type Item = GameState;
+    /// Every state the game has been in, starting from the table being set up.  With the serde
+    /// feature the whole history can be written out and read back in.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::Rules;
+    ///
+    /// let mut rule: Rules = Default::default();
+    /// rule.add_player();
+    ///
+    /// assert_eq!(1, rule.history().generation_number());
+    /// assert_eq!(rule.current_state(), rule.history().current_state());
+    /// ```
+    pub fn history(&self) -> &History<S> {
+        &self.history
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        todo!()
+    /// The history to move through one generation at a time when playing a game back.  Unlike
+    /// undo and redo this stops part way through the deal too.
+    pub(crate) fn history_mut(&mut self) -> &mut History<S> {
+        &mut self.history
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::Rules;
-    use crate::game_state::Progress;
+    /// Take back the last decision.  Every card dealt and every action is its own generation, and
+    /// everything the rules did by themselves to follow the decision up, like dealing the cards or
+    /// moving the turn on, is undone with it.  So the game is never left part way through the
+    /// deal.  Making the decision again starts a new branch in the history and keeps the old one.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Rules, TableRules };
+    /// use cards::prelude::Shoe;
+    ///
+    /// let shoe = Shoe::stacked("Ts 6h 7d 7h 2c").unwrap();
+    /// let mut rule = Rules::with_cards(TableRules::vegas_strip(), shoe, 0);
+    /// rule.add_player();
+    /// rule.buy_in(0, 100);
+    /// rule.place_bet(0, 10);
+    /// rule.start_playing();
+    ///
+    /// let before = rule.current_state().clone();
+    /// rule.hit(0).unwrap();
+    /// assert_eq!(19, rule.current_state().seats[0].hands[0].total());
+    /// rule.undo().unwrap();
+    /// assert_eq!(&before, rule.current_state());
+    ///
+    /// rule.stand(0).unwrap();
+    /// assert_eq!(17, rule.current_state().seats[0].hands[0].total());
+    /// assert_eq!(None, rule.current_state().current_seat());
+    /// ```
+    pub fn undo(&mut self) -> Result<(), RuleError> {
+        self.history.undo()?;
+        self.back_to_a_decision();
+        self.halted = None;
+        Ok(())
+    }
 
-    #[test]
-    fn default_rules() {
-        let rules: Rules = Default::default();
-        assert_eq!(0, rules.generation.branches().len())
+    /// Go forward again along the generations which were undone.  Once something new is done
+    /// there is nothing left to redo.  Everything the rules did to follow up the decision is
+    /// redone along with it.
+    pub fn redo(&mut self) -> Result<(), RuleError> {
+        self.history.redo()?;
+        while !self.at_a_decision() {
+            self.history.redo()?;
+        }
+        self.halted = None;
+        Ok(())
     }
 
-    #[test]
-    fn default_games_are_in_the_starting_state() {
-        let rules: Rules = Default::default();
-        assert_eq!(&Progress::Starting, rules.current_progress())
+    /// Undo or redo until the game is at the given generation.  The table being set up is
+    /// generation 0.  A generation part way through the deal or the house playing out is not
+    /// somewhere anyone can decide anything, so the game goes back to the last decision before
+    /// it.
+    ///
+    /// * `number`: The generation to go to
+    pub fn jump_to(&mut self, number: usize) -> Result<(), RuleError> {
+        self.history.jump_to(number)?;
+        self.back_to_a_decision();
+        self.halted = None;
+        Ok(())
     }
 
-    #[test]
-    fn games_should_move_to_playing_state() {
-        let mut rules: Rules = Default::default();
+    /// Whether the game is waiting for a decision, because the generation which comes next on
+    /// this line starts with one or because nothing comes next.
+    fn at_a_decision(&self) -> bool {
+        self.history.to_redo().is_none_or(|next| {
+            next.events()
+                .first()
+                .is_none_or(|event| event.is_decision())
+        })
+    }
+
+    /// Undo everything the rules did by themselves since the last decision.
+    fn back_to_a_decision(&mut self) {
+        while !self.at_a_decision() && self.history.undo().is_ok() {}
+    }
+
+    /// A new game which starts from where this game is, with its history up to here.  Neither
+    /// game changes the other.  The new game has no strategy or observers until they are set.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Rules, TableRules };
+    ///
+    /// let mut rule = Rules::with_seed(TableRules::vegas_strip(), 18);
+    /// rule.add_player();
+    ///
+    /// let mut fork = rule.fork();
+    /// fork.add_player();
+    ///
+    /// assert_eq!(1, rule.current_state().players.len());
+    /// assert_eq!(2, fork.current_state().players.len());
+    /// ```
+    pub fn fork(&self) -> Self {
+        Self {
+            history: self.history.fork(),
+            table: self.table.clone(),
+            strategy: None,
+            observers: vec![],
+            halted: None,
+        }
+    }
+
+    /// Move the game forward by one step using the strategy.  Between games every seat is asked
+    /// for a bet and the cards are dealt, seats answer any insurance offer one at a time, during a
+    /// game the current seat acts or the house plays out, and once the game is done a new game is
+    /// started.
+    fn step(&mut self, strategy: &mut dyn Strategy<S>) -> Result<(), Halt> {
+        match self.current_progress() {
+            Progress::Starting => {
+                if self.needs_reshuffle() {
+                    return Err(Halt::Reshuffle);
+                }
+
+                let min_bet = self.table.min_bet;
+                let gs = self.current_state();
+                let broke = (0..gs.players.len()).all(|player| {
+                    let bets: Chips = gs
+                        .seats_of(player)
+                        .map(|seat| gs.seats[seat].total_bet())
+                        .sum();
+                    gs.players[player].bankroll + bets < min_bet
+                });
+                if broke {
+                    return Err(Halt::OutOfMoney);
+                }
+
+                for seat in 0..self.current_state().seats.len() {
+                    let bet = strategy.bet(seat, self.current_state(), &self.table);
+                    self.place_bet(seat, bet)?;
+                }
+                if !self
+                    .current_state()
+                    .seats
+                    .iter()
+                    .any(|seat| seat.is_betting())
+                {
+                    return Err(Halt::NoBets);
+                }
+
+                self.start_playing()?;
+            }
+            Progress::Playing => match self.current_state().current_seat() {
+                Some(seat) => {
+                    let legal_actions = self.legal_actions(seat);
+                    let gs = self.current_state();
+                    let decision = Decision {
+                        seat,
+                        hand: gs.seats[seat]
+                            .active_hand()
+                            .expect("The current seat always has an active hand"),
+                        dealer_up_card: gs
+                            .dealer_up_card()
+                            .expect("The house always has an up card while playing"),
+                        legal_actions: &legal_actions,
+                        table: &self.table,
+                        state: gs,
+                    };
+                    let action = strategy.act(&decision);
+                    self.act(seat, action)?;
+                }
+                None => self.done_playing()?,
+            },
+            Progress::Done => self.new_game()?,
+            Progress::Insurance => {
+                let seat = self
+                    .current_state()
+                    .insuring_seat()
+                    .expect("A seat is always deciding while insurance is offered");
+                let insurance = strategy.insure(seat, self.current_state(), &self.table);
+                self.insure(seat, insurance)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check an action against the current state without taking it.
+    fn check_action(&self, seat: usize, action: Action) -> Result<(), RuleError> {
+        if !self.is_playing() {
+            return Err(RuleError::InvalidState(self.current_progress().clone()));
+        }
+
+        let gs = self.current_state();
+        let table = &self.table;
+        let place = gs.seats.get(seat).ok_or(RuleError::NoSuchSeat(seat))?;
+        if gs.current_seat() != Some(seat) {
+            return Err(RuleError::NotYourTurn(seat));
+        }
+
+        let hand = place
+            .active_hand()
+            .expect("The current seat always has an active hand");
+        let illegal = |reason| {
+            Err(RuleError::IllegalAction {
+                seat,
+                action,
+                reason,
+            })
+        };
+
+        let short_of_chips = gs.players[place.player].bankroll < hand.bet;
+
+        match action {
+            Action::Hit if is_split_aces(hand) && !table.hit_split_aces => {
+                illegal("split aces only get one card")
+            }
+            Action::Hit | Action::Stand => Ok(()),
+            Action::Double if hand.number_of_cards() != 2 => {
+                illegal("only the first two cards can be doubled")
+            }
+            Action::Double if hand.split && !table.double_after_split => {
+                illegal("the table does not allow doubling after a split")
+            }
+            Action::Double if !table.double.allows(hand.total()) => {
+                illegal("the table does not allow doubling this total")
+            }
+            Action::Double if short_of_chips => illegal("not enough chips to double"),
+            Action::Split if !hand.is_pair() => illegal("only a pair can be split"),
+            Action::Split if place.hands.len() > table.max_splits => {
+                illegal("no more hands can be split")
+            }
+            Action::Split if is_split_aces(hand) && !table.resplit_aces => {
+                illegal("the table does not allow resplitting aces")
+            }
+            Action::Split if short_of_chips => illegal("not enough chips to split"),
+            Action::Surrender if table.surrender == SurrenderRule::NoSurrender => {
+                illegal("the table does not allow surrender")
+            }
+            Action::Surrender if hand.number_of_cards() != 2 || hand.split => {
+                illegal("only the first two cards can be surrendered")
+            }
+            Action::Double | Action::Split | Action::Surrender => Ok(()),
+        }
+    }
+
+    /// Record a new state as the next generation, along with the events which led to it, then tell
+    /// every observer about the events.  Chips can only move around the table, so every state has
+    /// to balance with the ledger.
+    fn commit(&mut self, gs: GameState<S>, events: Vec<Event>) {
+        debug_assert!(
+            gs.is_balanced(),
+            "{} chips in play but the ledger has {}",
+            gs.chips_in_play(),
+            gs.ledger.balance()
+        );
+        self.history.add_generation_with_events(gs, events);
+
+        let generation = self.history.current_generation();
+        for event in generation.events() {
+            for observer in self.observers.iter_mut() {
+                observer.notify(event, generation.state());
+            }
+        }
+    }
+
+    /// The house checks its hole card for blackjack as its own generation, when the table says it
+    /// does for the up card.
+    fn peek(&mut self) {
+        let gs = self.current_state();
+        if gs
+            .dealer_up_card()
+            .is_some_and(|card| self.table.house_peeks(card))
+        {
+            let blackjack = gs.house.hand.is_blackjack();
+            let gs = gs.clone();
+            self.commit(gs, vec![Event::HousePeeked { blackjack }]);
+        }
+    }
+
+    /// The house shows an ace and the table offers insurance against it.
+    fn insurance_offered(&self) -> bool {
+        self.current_state()
+            .dealer_up_card()
+            .is_some_and(|card| self.table.offers_insurance(card))
+    }
+
+    /// The table allows early surrender and the house is about to peek, so the seats can give up
+    /// their hands before it does.
+    fn early_surrender_offered(&self) -> bool {
+        self.table.surrender == SurrenderRule::Early
+            && self
+                .current_state()
+                .dealer_up_card()
+                .is_some_and(|card| self.table.house_peeks(card))
+    }
+
+    /// The house peeked and found blackjack, so nobody has anything left to decide.
+    fn house_peeked_blackjack(&self) -> bool {
+        let house = &self.current_state().house.hand;
+        house.is_blackjack()
+            && house
+                .show_card()
+                .is_some_and(|card| self.table.house_peeks(card))
+    }
+
+    /// Deal a single card from the shoe as a new generation.
+    fn deal_to(&mut self, target: Target) -> Result<(), RuleError> {
+        let mut gs = self.current_state().clone();
+        let mut events = vec![];
+        deal(&mut gs, target, &mut events)?;
+        self.commit(gs, events);
+        Ok(())
+    }
+
+    /// Move the turn past every hand which has nothing left to decide.  A hand which came from a
+    /// split is dealt its second card when play reaches it.
+    fn finish_turns(&mut self) -> Result<(), RuleError> {
+        while let Some(seat) = self.current_state().current_seat() {
+            let place = &self.current_state().seats[seat];
+
+            match place.active_hand() {
+                Some(hand) if hand.number_of_cards() < 2 && place.is_betting() => {
+                    self.deal_to(Target::Seat(seat))?;
+                }
+                Some(_) if place.is_betting() && !self.is_finished(place) => break,
+                _ => {
+                    let mut gs = self.current_state().clone();
+                    let place = &gs.seats[seat];
+                    let mut events = vec![];
+                    if place.is_betting() && place.active_hand().is_some() {
+                        let hand = place.active;
+                        events.push(Event::HandFinished { seat, hand });
+                    }
+                    if place.is_betting() && place.active + 1 < place.hands.len() {
+                        gs.seats[seat].active += 1;
+                    } else {
+                        gs.turn += 1;
+                        events.push(Event::TurnOver { seat });
+                    }
+                    self.commit(gs, events);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The seat's active hand is finished when there is nothing left to decide.  Unless the
+    /// table allows it split aces only get one card each.  Nothing is decided against a house
+    /// blackjack found by peeking.
+    fn is_finished(&self, seat: &Seat) -> bool {
+        let hand = match seat.active_hand() {
+            Some(hand) => hand,
+            None => return true,
+        };
+        let can_resplit =
+            self.table.resplit_aces && hand.is_pair() && seat.hands.len() <= self.table.max_splits;
+        let split_aces_done = is_split_aces(hand) && !self.table.hit_split_aces && !can_resplit;
+
+        hand.is_bust()
+            || hand.total() == player::BLACKJACK
+            || hand.doubled
+            || hand.surrendered
+            || split_aces_done
+            || self.house_peeked_blackjack()
+    }
+
+    /// The house draws below 17, and on a soft 17 when the table says so.
+    fn house_draws(&self, hand: &Hand) -> bool {
+        let value = hand.value();
+        value.total < DEALER_STANDS_AT
+            || (value.total == DEALER_STANDS_AT && value.soft && self.table.dealer_hits_soft_17)
+    }
+}
+
+/// Deal a single card from the shoe.  A player always receives it on their active hand.  When
+/// the shoe runs out part way through a game the discard tray is shuffled back into it with the
+/// next shuffle from the seed, the way a dealer would.  The events saying what happened are added
+/// to the given events.
+fn deal<S: CardSource>(
+    gs: &mut GameState<S>,
+    target: Target,
+    events: &mut Vec<Event>,
+) -> Result<(), RuleError> {
+    if gs.shoe.cards_left() == 0 {
+        gs.shuffles += 1;
+        gs.shoe
+            .reshuffle_with(&mut seeded_rng(gs.seed, gs.shuffles));
+        let shuffles = gs.shuffles;
+        events.push(Event::ShoeRanOut { shuffles });
+    }
+    let card = gs.shoe.deal().ok_or(RuleError::OutOfCards)?;
+
+    let to = match target {
+        Target::Seat(seat) => {
+            let place = &mut gs.seats[seat];
+            let hand = place.active;
+            place
+                .active_hand_mut()
+                .expect("Cards are only dealt to an active hand")
+                .recieve(card);
+            Recipient::Seat { seat, hand }
+        }
+        Target::House => {
+            gs.house.hand.recieve(card);
+            Recipient::House
+        }
+    };
+    events.push(Event::CardDealt { to, card });
+    Ok(())
+}
+
+/// The first seat from the given one which has a bet down and so is asked about insurance.  When
+/// there is none this is the number of seats.
+fn next_to_insure(seats: &[Shared<Seat>], from: usize) -> usize {
+    (from..seats.len())
+        .find(|&seat| seats[seat].is_betting())
+        .unwrap_or(seats.len())
+}
+
+/// The bet put down on the seat before doubling or splitting.
+fn original_bet(seat: &Seat) -> Chips {
+    match seat.hands.first() {
+        Some(hand) if hand.doubled => hand.bet / 2,
+        Some(hand) => hand.bet,
+        None => 0,
+    }
+}
+
+/// A hand which came from splitting aces.
+fn is_split_aces(hand: &Hand) -> bool {
+    hand.split && hand.show_card().map(|card| card.rank()) == Some(Rank::Ace)
+}
+
+/// Settle a hand against the house.  A late surrender does not save anything from a house
+/// blackjack.
+fn settle(hand: &Hand, house: &Hand, table: &TableRules) -> Outcome {
+    if hand.surrendered {
+        if house.is_blackjack() && table.surrender == SurrenderRule::Late {
+            Outcome::Lose
+        } else {
+            Outcome::Surrender
+        }
+    } else if hand.is_bust() {
+        Outcome::Lose
+    } else if hand.is_blackjack() {
+        if house.is_blackjack() {
+            Outcome::Push
+        } else {
+            Outcome::Blackjack
+        }
+    } else if house.is_blackjack() || (!house.is_bust() && hand.total() < house.total()) {
+        Outcome::Lose
+    } else if house.is_bust() || hand.total() > house.total() {
+        Outcome::Win
+    } else {
+        Outcome::Push
+    }
+}
+
+/// Rules can be driven as an iterator.  Each step asks the strategy what to do, moves the game
+/// forward and yields the new state.  The iterator stops when there is no strategy, the shoe needs
+/// reshuffling, the players run out of money or the strategy does something illegal; the reason
+/// is kept in halted.  Reshuffling or buying in lets the iterator carry on.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ Halt, MimicTheDealer, Rules };
+///
+/// let mut rule: Rules = Default::default();
+/// rule.add_player();
+/// rule.buy_in(0, 1000);
+/// rule.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+///
+/// for state in rule.by_ref() {
+///     assert!(state.is_balanced());
+/// }
+/// assert!(matches!(rule.halted(), Some(Halt::Reshuffle) | Some(Halt::OutOfMoney)));
+/// ```
+impl<S: CardSource> Iterator for Rules<S> {
+    type Item = GameState<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut strategy = match self.strategy.take() {
+            Some(strategy) => strategy,
+            None => {
+                self.halted = Some(Halt::NoStrategy);
+                return None;
+            }
+        };
+
+        let step = self.step(strategy.as_mut());
+        self.strategy = Some(strategy);
+
+        match step {
+            Ok(()) => {
+                self.halted = None;
+                Some(self.current_state().clone())
+            }
+            Err(halt) => {
+                self.halted = Some(halt);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{settle, Halt, Rules};
+    use crate::{
+        action::Action,
+        error::RuleError,
+        event::{Event, Recipient},
+        game_state::{GameState, Insurance, Outcome, Progress},
+        strategy::{Decision, MimicTheDealer, Strategy},
+        table_rules::{DoubleRestriction, SurrenderRule, TableRules},
+    };
+    use cards::prelude::{Card, CardSource, HasCards, Rank, Shoe, Suit};
+    use player::{Chips, Hand, HandleCards};
+    use rand::Rng;
+    use std::{cell::RefCell, rc::Rc};
+
+    /// A test double which deals the given cards in order.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Stacked {
+        cards: Vec<Card>,
+        discards: usize,
+    }
+
+    impl HasCards for Stacked {
+        fn deal(&mut self) -> Option<Card> {
+            if self.cards.is_empty() {
+                None
+            } else {
+                Some(self.cards.remove(0))
+            }
+        }
+
+        fn cards_left(&self) -> usize {
+            self.cards.len()
+        }
+
+        fn shuffle_with<R: Rng + ?Sized>(&mut self, _rng: &mut R) {}
+    }
+
+    impl CardSource for Stacked {
+        fn discard(&mut self, cards: Vec<Card>) {
+            self.discards += cards.len();
+        }
+
+        fn reshuffle_pending(&self) -> bool {
+            false
+        }
+
+        fn reshuffle_with<R: Rng + ?Sized>(&mut self, _rng: &mut R) {}
+    }
+
+    /// A table with one player who bets ten, dealing the given ranks in order.
+    fn stacked(ranks: &[i32]) -> Rules<Stacked> {
+        stacked_at(Default::default(), ranks)
+    }
+
+    /// The given table with one player who bets ten, dealing the given ranks in order.
+    fn stacked_at(table: TableRules, ranks: &[i32]) -> Rules<Stacked> {
+        let cards = ranks
+            .iter()
+            .map(|&rank| Card::new(Rank::try_from(rank).unwrap(), Suit::Hearts))
+            .collect();
+        let mut rules = Rules::with_cards(table, Stacked { cards, discards: 0 }, 0);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        rules.start_playing().unwrap();
+        rules
+    }
+
+    /// A table with one player who bets ten, dealing from a shoe stacked with the given cards.
+    fn stacked_shoe(cards: &str) -> Rules {
+        let mut rules = Rules::with_cards(Default::default(), Shoe::stacked(cards).unwrap(), 0);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        rules.start_playing().unwrap();
+        rules
+    }
+
+    /// A table where the house neither offers insurance nor peeks, so hands are played out
+    /// against a house blackjack.
+    fn without_peeking() -> TableRules {
+        TableRules {
+            insurance: false,
+            dealer_peeks: false,
+            ..Default::default()
+        }
+    }
+
+    fn hand_of(ranks: &[i32]) -> Hand {
+        Hand::from(
+            ranks
+                .iter()
+                .map(|&rank| Card::new(Rank::try_from(rank).unwrap(), Suit::Spades))
+                .collect::<Vec<Card>>(),
+        )
+    }
+
+    /// Sit players down with plenty of chips.
+    fn seat_players(rules: &mut Rules, players: usize) {
+        for _ in 0..players {
+            rules.add_player().unwrap();
+            let player = rules.current_state().players.len() - 1;
+            rules.buy_in(player, 10_000).unwrap();
+        }
+    }
+
+    /// Every seat bets the table minimum, the cards are dealt and any insurance is declined.
+    fn start(rules: &mut Rules) {
+        let min_bet = rules.table_rules().min_bet;
+        for seat in 0..rules.current_state().seats.len() {
+            rules.place_bet(seat, min_bet).unwrap();
+        }
+        rules.start_playing().unwrap();
+        while let Some(seat) = rules.current_state().insuring_seat() {
+            rules.insure(seat, Insurance::Declined).unwrap();
+        }
+    }
+
+    /// Play a round splitting every pair and doubling every eleven.
+    fn play_round(rules: &mut Rules) {
+        start(rules);
+        while let Some(seat) = rules.current_state().current_seat() {
+            let legal = rules.legal_actions(seat);
+            let hand = rules.current_state().seats[seat].active_hand().unwrap();
+
+            let action = if legal.contains(&Action::Split) {
+                Action::Split
+            } else if hand.total() == 11 && legal.contains(&Action::Double) {
+                Action::Double
+            } else if hand.total() < 12 {
+                Action::Hit
+            } else {
+                Action::Stand
+            };
+            rules.act(seat, action).unwrap();
+        }
+        rules.done_playing().unwrap();
+    }
+
+    #[test]
+    fn default_rules() {
+        let rules: Rules = Default::default();
+        assert_eq!(0, rules.history.root().branches().len())
+    }
+
+    #[test]
+    fn default_games_are_in_the_starting_state() {
+        let rules: Rules = Default::default();
+        assert_eq!(&Progress::Starting, rules.current_progress())
+    }
+
+    #[test]
+    fn games_should_move_to_playing_state() {
+        let mut rules: Rules = Default::default();
         let maybe = rules.start_playing();
         assert!(maybe.is_ok());
         assert_eq!(&Progress::Playing, rules.current_progress())
@@ -251,7 +1635,1228 @@ mod tests {
     fn adding_player_to_rules() {
         let mut rules: Rules = Default::default();
         rules.add_player().unwrap();
-        assert_eq!(1, rules.generation.branches().len());
+        assert_eq!(1, rules.history.root().branches().len());
         assert_eq!(1, rules.current_state().players.len());
     }
+
+    #[test]
+    fn starting_deals_two_cards_to_everyone() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 2);
+        start(&mut rules);
+
+        let state = rules.current_state();
+        assert_eq!(2, state.house.hand.number_of_cards());
+        for seat in state.seats.iter() {
+            assert_eq!(2, seat.hands[0].number_of_cards());
+        }
+        assert_eq!(rules.table_rules().shoe_size() - 6, state.shoe.cards_left());
+    }
+
+    #[test]
+    fn players_can_only_act_on_their_turn() {
+        let mut rules: Rules = Default::default();
+        assert_eq!(
+            Err(RuleError::InvalidState(Progress::Starting)),
+            rules.hit(0)
+        );
+        start(&mut rules);
+        assert_eq!(Err(RuleError::NoSuchSeat(0)), rules.stand(0));
+    }
+
+    #[test]
+    fn hitting_until_bust_ends_the_turn() {
+        let mut rules = stacked_shoe("Ts 6h 6d 7h 9c 8s");
+        rules.hit(0).unwrap();
+
+        let hand = &rules.current_state().seats[0].hands[0];
+        assert!(hand.is_bust());
+        assert_eq!(25, hand.total());
+        assert_eq!(None, rules.current_state().current_seat());
+        assert_eq!(1, rules.current_state().turn);
+        assert_eq!(Err(RuleError::NotYourTurn(0)), rules.hit(0));
+    }
+
+    #[test]
+    fn house_plays_out_and_players_are_settled() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 3);
+
+        for _ in 0..20 {
+            play_round(&mut rules);
+
+            let state = rules.current_state();
+            assert_eq!(&Progress::Done, rules.current_progress());
+            assert_eq!(3, state.results.len());
+            for (seat, results) in state.seats.iter().zip(state.results.iter()) {
+                assert_eq!(seat.hands.len(), results.len());
+                assert!(seat.hands.len() <= rules.table_rules().max_splits + 1);
+                for (hand, result) in seat.hands.iter().zip(results.iter()) {
+                    assert_eq!(
+                        &settle(hand, &state.house.hand, rules.table_rules()),
+                        result
+                    );
+                }
+            }
+
+            rules.new_game().unwrap();
+            assert!(rules.current_state().results.is_empty());
+            assert_eq!(0, rules.current_state().house.hand.number_of_cards());
+        }
+    }
+
+    #[test]
+    fn shoe_is_replaced_when_running_low() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 1);
+        let cut_card = rules.table_rules().reshuffle_at();
+
+        let mut reshuffles = 0;
+        for _ in 0..200 {
+            let due = rules.needs_reshuffle();
+            let shuffles = rules.current_state().shuffles;
+            play_round(&mut rules);
+
+            if due {
+                reshuffles += 1;
+                assert_eq!(shuffles + 1, rules.current_state().shuffles);
+                assert!(rules.current_state().shoe.cards_left() > cut_card);
+                assert!(!rules.needs_reshuffle());
+            } else {
+                assert_eq!(shuffles, rules.current_state().shuffles);
+            }
+            rules.new_game().unwrap();
+        }
+
+        assert!(reshuffles > 0);
+        let recorded = rules
+            .history()
+            .events()
+            .filter(|event| matches!(event, Event::Reshuffled { .. }))
+            .count();
+        assert_eq!(reshuffles, recorded);
+    }
+
+    #[test]
+    fn settling_hands() {
+        let table: TableRules = Default::default();
+        assert_eq!(
+            Outcome::Lose,
+            settle(&hand_of(&[10, 6, 9]), &hand_of(&[10, 6, 9]), &table)
+        );
+        assert_eq!(
+            Outcome::Blackjack,
+            settle(&hand_of(&[1, 13]), &hand_of(&[10, 11]), &table)
+        );
+        assert_eq!(
+            Outcome::Push,
+            settle(&hand_of(&[1, 13]), &hand_of(&[12, 1]), &table)
+        );
+        assert_eq!(
+            Outcome::Lose,
+            settle(&hand_of(&[10, 5, 6]), &hand_of(&[12, 1]), &table)
+        );
+        assert_eq!(
+            Outcome::Win,
+            settle(&hand_of(&[10, 2]), &hand_of(&[10, 6, 9]), &table)
+        );
+        assert_eq!(
+            Outcome::Win,
+            settle(&hand_of(&[10, 9]), &hand_of(&[10, 8]), &table)
+        );
+        assert_eq!(
+            Outcome::Push,
+            settle(&hand_of(&[10, 8]), &hand_of(&[10, 8]), &table)
+        );
+        assert_eq!(
+            Outcome::Lose,
+            settle(&hand_of(&[10, 7]), &hand_of(&[10, 8]), &table)
+        );
+    }
+
+    #[test]
+    fn only_the_current_seat_can_act() {
+        let shoe = Shoe::stacked("Ts 9s 6h 7s 8s 7h").unwrap();
+        let mut rules = Rules::with_cards(Default::default(), shoe, 0);
+        seat_players(&mut rules, 2);
+        start(&mut rules);
+
+        assert_eq!(Some(0), rules.current_state().current_seat());
+        assert_eq!(Err(RuleError::NotYourTurn(1)), rules.hit(1));
+        assert!(rules.legal_actions(1).is_empty());
+
+        rules.stand(0).unwrap();
+        assert_eq!(Some(1), rules.current_state().current_seat());
+        assert_eq!(Err(RuleError::NotYourTurn(0)), rules.hit(0));
+        assert!(rules.legal_actions(0).is_empty());
+    }
+
+    #[test]
+    fn generations_share_what_did_not_change() {
+        let shoe = Shoe::stacked("Ts 9s 6h 7s 8s 7h 2c").unwrap();
+        let mut rules = Rules::with_cards(Default::default(), shoe, 0);
+        seat_players(&mut rules, 2);
+        start(&mut rules);
+        let before = rules.current_state().clone();
+        rules.hit(0).unwrap();
+
+        let after = rules.current_state();
+        assert!(!after.seats[0].is_shared_with(&before.seats[0]));
+        assert!(after.seats[1].is_shared_with(&before.seats[1]));
+        assert!(after.house.is_shared_with(&before.house));
+        assert!(after.players.is_shared_with(&before.players));
+        assert_eq!(3, after.seats[0].hands[0].number_of_cards());
+        assert_eq!(2, before.seats[0].hands[0].number_of_cards());
+    }
+
+    #[test]
+    fn legal_actions_on_the_first_two_cards() {
+        for (cards, pair) in [("Ts 6h 7d 7h 2c", false), ("8s 6h 8d 7h 2c", true)] {
+            let mut rules = stacked_shoe(cards);
+            let legal = rules.legal_actions(0);
+            assert!(legal.contains(&Action::Hit));
+            assert!(legal.contains(&Action::Stand));
+            assert!(legal.contains(&Action::Double));
+            assert!(legal.contains(&Action::Surrender));
+            assert_eq!(pair, legal.contains(&Action::Split));
+
+            rules.hit(0).unwrap();
+            assert_eq!(Some(0), rules.current_state().current_seat());
+            assert_eq!(vec![Action::Hit, Action::Stand], rules.legal_actions(0));
+            assert_eq!(
+                Err(RuleError::IllegalAction {
+                    seat: 0,
+                    action: Action::Double,
+                    reason: "only the first two cards can be doubled",
+                }),
+                rules.double(0)
+            );
+        }
+    }
+
+    #[test]
+    fn doubling_takes_one_card_and_ends_the_hand() {
+        let mut rules = stacked_shoe("6s 6h 5d 7h 9c");
+        rules.double(0).unwrap();
+
+        let hand = &rules.current_state().seats[0].hands[0];
+        assert!(hand.doubled);
+        assert_eq!(3, hand.number_of_cards());
+        assert_eq!(20, hand.total());
+        assert_eq!(None, rules.current_state().current_seat());
+    }
+
+    #[test]
+    fn surrendering_ends_the_hand() {
+        let mut rules = stacked_shoe("Ts 6h 6d 7h");
+        rules.surrender(0).unwrap();
+        assert_eq!(None, rules.current_state().current_seat());
+
+        rules.done_playing().unwrap();
+        let state = rules.current_state();
+        assert_eq!(vec![vec![Outcome::Surrender]], *state.results);
+        assert_eq!(2, state.house.hand.number_of_cards());
+        assert_eq!(95, state.players[0].bankroll);
+    }
+
+    #[test]
+    fn stacked_cards_are_dealt_in_order() {
+        let mut rules = stacked(&[10, 9, 6, 8, 5]);
+        let state = rules.current_state();
+        assert_eq!(16, state.seats[0].hands[0].total());
+        assert_eq!(17, state.house.hand.total());
+
+        rules.hit(0).unwrap();
+        rules.done_playing().unwrap();
+        assert_eq!(vec![vec![Outcome::Win]], *rules.current_state().results);
+        assert_eq!(110, rules.current_state().players[0].bankroll);
+
+        rules.new_game().unwrap();
+        assert_eq!(5, rules.current_state().shoe.discards);
+        assert_eq!(0, rules.current_state().shoe.cards_left());
+    }
+
+    #[test]
+    fn split_eights_against_a_busting_house() {
+        let mut rules = stacked_shoe("8s 6h 8d Th 3c 9s Tc");
+        rules.split(0).unwrap();
+        assert_eq!("8s 3c", rules.current_state().seats[0].hands[0].to_string());
+        rules.stand(0).unwrap();
+        assert_eq!("8d 9s", rules.current_state().seats[0].hands[1].to_string());
+        rules.stand(0).unwrap();
+        rules.done_playing().unwrap();
+
+        let state = rules.current_state();
+        assert_eq!("6h Th Tc", state.house.hand.to_string());
+        assert_eq!(vec![vec![Outcome::Win, Outcome::Win]], *state.results);
+        assert_eq!(120, state.players[0].bankroll);
+    }
+
+    #[test]
+    fn retrying_a_decision() {
+        let mut rules = stacked_shoe("Ts 6h 6d Th 9c");
+        let decision = rules.history().generation_number();
+        rules.hit(0).unwrap();
+        assert!(rules.current_state().seats[0].hands[0].is_bust());
+
+        rules.jump_to(decision).unwrap();
+        assert_eq!(Some(0), rules.current_state().current_seat());
+        rules.stand(0).unwrap();
+        rules.done_playing().unwrap();
+
+        let state = rules.current_state();
+        assert_eq!("6h Th 9c", state.house.hand.to_string());
+        assert_eq!(vec![vec![Outcome::Win]], *state.results);
+        assert_eq!(Err(RuleError::NothingToRedo), rules.redo());
+
+        let retried = rules.history().path()[decision];
+        assert_eq!(
+            2,
+            rules.history().get(retried).unwrap().number_of_branches()
+        );
+    }
+
+    #[test]
+    fn undo_goes_back_to_the_last_decision() {
+        let mut rules = Rules::with_cards(
+            Default::default(),
+            Shoe::stacked("Ts 6h 6d 7h 5c 9s").unwrap(),
+            0,
+        );
+        rules.add_player().unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        let betting = rules.current_state().clone();
+        rules.start_playing().unwrap();
+        let dealt = rules.current_state().clone();
+
+        rules.undo().unwrap();
+        assert_eq!(&betting, rules.current_state());
+        assert_eq!(&Progress::Starting, rules.current_progress());
+        assert!(rules.legal_actions(0).is_empty());
+
+        rules.redo().unwrap();
+        assert_eq!(&dealt, rules.current_state());
+        assert_eq!(Err(RuleError::NothingToRedo), rules.redo());
+
+        let deal = rules.history().generation_number();
+        rules.jump_to(deal - 1).unwrap();
+        assert_eq!(&betting, rules.current_state());
+
+        rules.redo().unwrap();
+        rules.hit(0).unwrap();
+        let state = rules.current_state();
+        assert_eq!("Ts 6d 5c", state.seats[0].hands[0].to_string());
+        assert_eq!("6h 7h", state.house.hand.to_string());
+    }
+
+    #[test]
+    fn forks_play_on_by_themselves() {
+        let mut rules = stacked_shoe("Ts 6h 6d Th 9c");
+        let mut fork = rules.fork();
+        assert_eq!(rules.current_state(), fork.current_state());
+        fork.hit(0).unwrap();
+        rules.stand(0).unwrap();
+
+        assert_eq!(3, fork.current_state().seats[0].hands[0].number_of_cards());
+        assert_eq!(2, rules.current_state().seats[0].hands[0].number_of_cards());
+        assert_eq!(1, rules.history().root().number_of_branches());
+        assert_eq!(1, fork.history().root().number_of_branches());
+    }
+
+    #[test]
+    fn a_house_blackjack_beats_twenty_one() {
+        let mut rules = stacked_at(without_peeking(), &[10, 1, 5, 13, 6]);
+        rules.hit(0).unwrap();
+        rules.done_playing().unwrap();
+
+        let state = rules.current_state();
+        assert_eq!(21, state.seats[0].hands[0].total());
+        assert!(state.house.hand.is_blackjack());
+        assert_eq!(vec![vec![Outcome::Lose]], *state.results);
+    }
+
+    #[test]
+    fn late_surrender_loses_to_a_house_blackjack() {
+        let mut rules = stacked_at(without_peeking(), &[10, 1, 6, 13]);
+        rules.surrender(0).unwrap();
+        rules.done_playing().unwrap();
+        assert_eq!(vec![vec![Outcome::Lose]], *rules.current_state().results);
+
+        let mut rules = stacked(&[10, 10, 6, 7]);
+        rules.surrender(0).unwrap();
+        rules.done_playing().unwrap();
+        assert_eq!(
+            vec![vec![Outcome::Surrender]],
+            *rules.current_state().results
+        );
+        assert_eq!(95, rules.current_state().players[0].bankroll);
+    }
+
+    #[test]
+    fn running_out_of_cards() {
+        let mut rules = stacked(&[10, 9, 6, 8]);
+        assert_eq!(Err(RuleError::OutOfCards), rules.hit(0));
+    }
+
+    #[test]
+    fn running_out_of_cards_in_the_deal_deals_nothing() {
+        let mut rules =
+            Rules::with_cards(Default::default(), Shoe::stacked("Ts 6h 7d").unwrap(), 0);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        let before = rules.history().number_of_generations();
+
+        assert_eq!(Err(RuleError::OutOfCards), rules.start_playing());
+        assert!(rules.is_starting());
+        assert_eq!(before, rules.history().number_of_generations());
+        assert_eq!(0, rules.current_state().seats[0].hands[0].number_of_cards());
+        assert_eq!(3, rules.current_state().shoe.cards_left());
+        assert!(rules.place_bet(0, 20).is_ok());
+    }
+
+    #[test]
+    fn the_discards_are_shuffled_back_in_when_the_shoe_runs_out() {
+        let mut rules = stacked_shoe("Ts 6h 7d Th 9c");
+        rules.stand(0).unwrap();
+        rules.done_playing().unwrap();
+        rules.new_game().unwrap();
+        assert_eq!(0, rules.current_state().shoe.cards_left());
+
+        rules.place_bet(0, 10).unwrap();
+        rules.start_playing().unwrap();
+        let state = rules.current_state();
+        assert_eq!(2, state.seats[0].hands[0].number_of_cards());
+        assert_eq!(2, state.house.hand.number_of_cards());
+        assert_eq!(1, state.shuffles);
+        assert_eq!(1, state.shoe.cards_left());
+        assert_eq!(0, state.shoe.discards());
+
+        let ran_out: Vec<_> = rules
+            .history()
+            .path()
+            .into_iter()
+            .flat_map(|id| rules.history().get(id).unwrap().events())
+            .filter(|event| matches!(event, Event::ShoeRanOut { .. }))
+            .collect();
+        assert_eq!(vec![&Event::ShoeRanOut { shuffles: 1 }], ran_out);
+    }
+
+    #[test]
+    fn splitting_plays_each_hand_in_turn() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 1);
+
+        let mut splits = 0;
+        while splits < 5 {
+            start(&mut rules);
+            if rules.legal_actions(0).contains(&Action::Split) {
+                splits += 1;
+                rules.split(0).unwrap();
+
+                let seat = &rules.current_state().seats[0];
+                assert!(seat.hands.len() >= 2);
+                assert!(seat.hands.iter().all(|hand| hand.split));
+                assert_eq!(2, seat.hands[0].number_of_cards());
+                if seat.active == 0 {
+                    assert_eq!(1, seat.hands[1].number_of_cards());
+                }
+            }
+            while let Some(seat) = rules.current_state().current_seat() {
+                rules.stand(seat).unwrap();
+            }
+            for hand in rules.current_state().seats[0].hands.iter() {
+                assert!(hand.number_of_cards() >= 2);
+            }
+            rules.done_playing().unwrap();
+            rules.new_game().unwrap();
+        }
+    }
+
+    #[test]
+    fn surrender_rules() {
+        let table = TableRules {
+            surrender: SurrenderRule::Late,
+            ..Default::default()
+        };
+        let mut surrendered = hand_of(&[10, 6]);
+        surrendered.surrendered = true;
+        let blackjack = hand_of(&[1, 13]);
+        assert_eq!(Outcome::Lose, settle(&surrendered, &blackjack, &table));
+        assert_eq!(
+            Outcome::Surrender,
+            settle(&surrendered, &hand_of(&[10, 9]), &table)
+        );
+
+        let table = TableRules {
+            surrender: SurrenderRule::Early,
+            ..Default::default()
+        };
+        assert_eq!(Outcome::Surrender, settle(&surrendered, &blackjack, &table));
+    }
+
+    #[test]
+    fn early_surrender_saves_half_against_a_peeked_blackjack() {
+        let table = TableRules {
+            surrender: SurrenderRule::Early,
+            ..Default::default()
+        };
+        for cards in ["Ts Ah 6d Kh", "Ts Kh 6d Ah"] {
+            let shoe = Shoe::stacked(cards).unwrap();
+            let mut rules = Rules::with_cards(table.clone(), shoe, 0);
+            rules.add_player().unwrap();
+            rules.buy_in(0, 100).unwrap();
+            rules.place_bet(0, 10).unwrap();
+            rules.start_playing().unwrap();
+            assert_eq!(Some(0), rules.current_state().insuring_seat());
+
+            rules.insure(0, Insurance::Surrendered).unwrap();
+            assert_eq!(None, rules.current_state().current_seat());
+            rules.done_playing().unwrap();
+            assert_eq!(
+                vec![vec![Outcome::Surrender]],
+                *rules.current_state().results
+            );
+            assert_eq!(95, rules.current_state().players[0].bankroll);
+        }
+    }
+
+    #[test]
+    fn early_surrender_is_only_asked_before_a_peek() {
+        let table = TableRules {
+            surrender: SurrenderRule::Early,
+            ..Default::default()
+        };
+        let mut rules = Rules::with_cards(table, Shoe::stacked("Ts Kh 6d 7h").unwrap(), 0);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        rules.start_playing().unwrap();
+        assert_eq!(
+            Err(RuleError::InsuranceNotOffered(0)),
+            rules.insure(0, Insurance::Taken(5))
+        );
+        rules.insure(0, Insurance::Declined).unwrap();
+        assert_eq!(Some(0), rules.current_state().current_seat());
+
+        let mut late = stacked_shoe("Ts Ah 6d 7h");
+        assert!(matches!(
+            late.insure(0, Insurance::Surrendered),
+            Err(RuleError::IllegalAction {
+                action: Action::Surrender,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn tables_without_surrender_refuse_it() {
+        let shoe = Shoe::stacked("Ts 6h 6d 7h").unwrap();
+        let mut rules = Rules::with_cards(TableRules::downtown_vegas(), shoe, 0);
+        seat_players(&mut rules, 1);
+        start(&mut rules);
+
+        assert_eq!(Some(0), rules.current_state().current_seat());
+        assert!(!rules.legal_actions(0).contains(&Action::Surrender));
+        assert!(matches!(
+            rules.surrender(0),
+            Err(RuleError::IllegalAction { .. })
+        ));
+    }
+
+    #[test]
+    fn double_restrictions_are_followed() {
+        let table = TableRules {
+            double: DoubleRestriction::TenToEleven,
+            ..Default::default()
+        };
+
+        for (cards, total, doubles) in [
+            ("4s 6h 5d 7h", 9, false),
+            ("5s 6h 5d 7h", 10, true),
+            ("6s 6h 5d 7h", 11, true),
+            ("7s 6h 5d 7h", 12, false),
+            ("As 6h 9d 7h", 20, false),
+        ] {
+            let shoe = Shoe::stacked(cards).unwrap();
+            let mut rules = Rules::with_cards(table.clone(), shoe, 0);
+            seat_players(&mut rules, 1);
+            start(&mut rules);
+
+            assert_eq!(Some(0), rules.current_state().current_seat());
+            assert_eq!(total, rules.current_state().seats[0].hands[0].total());
+            assert_eq!(doubles, rules.legal_actions(0).contains(&Action::Double));
+        }
+    }
+
+    #[test]
+    fn no_hole_card_tables() {
+        let mut rules = Rules::new(TableRules::european());
+        seat_players(&mut rules, 1);
+        start(&mut rules);
+        assert_eq!(1, rules.current_state().house.hand.number_of_cards());
+
+        while let Some(seat) = rules.current_state().current_seat() {
+            rules.surrender(seat).unwrap_err();
+            rules.stand(seat).unwrap();
+        }
+        rules.done_playing().unwrap();
+        assert!(rules.current_state().house.hand.number_of_cards() >= 2);
+    }
+
+    #[test]
+    fn house_hits_soft_17_when_the_table_says_so() {
+        let soft_17 = hand_of(&[1, 6]);
+        let hard_17 = hand_of(&[10, 7]);
+
+        let stands = Rules::new(TableRules::vegas_strip());
+        assert!(!stands.house_draws(&soft_17));
+        assert!(!stands.house_draws(&hard_17));
+        assert!(stands.house_draws(&hand_of(&[10, 6])));
+
+        let hits = Rules::new(TableRules::downtown_vegas());
+        assert!(hits.house_draws(&soft_17));
+        assert!(!hits.house_draws(&hard_17));
+    }
+
+    #[test]
+    fn reshuffles_at_the_tables_penetration() {
+        let mut rules = Rules::new(TableRules {
+            decks: 1,
+            penetration: 0.5,
+            ..Default::default()
+        });
+        seat_players(&mut rules, 1);
+
+        for _ in 0..30 {
+            play_round(&mut rules);
+            rules.new_game().unwrap();
+            assert!(rules.current_state().shoe.cards_left() > 0);
+        }
+    }
+
+    #[test]
+    fn chips_are_conserved_in_every_generation() {
+        let mut rules = Rules::with_seed(Default::default(), 5);
+        seat_players(&mut rules, 3);
+
+        for _ in 0..100 {
+            play_round(&mut rules);
+            rules.new_game().unwrap();
+        }
+        for generation in rules.history().iter() {
+            assert!(generation.state().is_balanced());
+        }
+
+        let state = rules.current_state();
+        assert_eq!(30_000, state.ledger.balance());
+        assert_ne!(0, state.house.bankroll);
+    }
+
+    #[test]
+    fn bets_are_paid_out_at_settlement() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 1);
+
+        for _ in 0..20 {
+            let before = rules.current_state().players[0].bankroll;
+            play_round(&mut rules);
+
+            let state = rules.current_state();
+            let seat = &state.seats[0];
+            let paid: i64 = seat
+                .hands
+                .iter()
+                .zip(state.results[0].iter())
+                .map(|(hand, &outcome)| rules.table_rules().payout(outcome, hand.bet))
+                .sum();
+            assert_eq!(before - seat.total_bet() + paid, state.players[0].bankroll);
+            rules.new_game().unwrap();
+        }
+    }
+
+    #[test]
+    fn bets_must_be_within_limits_and_covered() {
+        let mut rules: Rules = Default::default();
+        rules.add_player().unwrap();
+        rules.buy_in(0, 50).unwrap();
+
+        assert_eq!(
+            Err(RuleError::BetOutOfRange {
+                bet: 5,
+                min: 10,
+                max: 5000
+            }),
+            rules.place_bet(0, 5)
+        );
+        assert_eq!(
+            Err(RuleError::NotEnoughChips {
+                seat: 0,
+                needed: 100,
+                available: 50
+            }),
+            rules.place_bet(0, 100)
+        );
+        rules.place_bet(0, 50).unwrap();
+        rules.place_bet(0, 40).unwrap();
+        assert_eq!(10, rules.current_state().players[0].bankroll);
+        assert_eq!(Err(RuleError::NoSuchSeat(1)), rules.place_bet(1, 10));
+    }
+
+    #[test]
+    fn doubling_needs_the_chips_to_cover_it() {
+        let shoe = Shoe::stacked("8s 6h 8d 7h").unwrap();
+        let mut rules = Rules::with_cards(Default::default(), shoe, 0);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 10).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        rules.start_playing().unwrap();
+
+        assert_eq!(Some(0), rules.current_state().current_seat());
+        assert_eq!(
+            vec![Action::Hit, Action::Stand, Action::Surrender],
+            rules.legal_actions(0)
+        );
+        assert!(matches!(
+            rules.double(0),
+            Err(RuleError::IllegalAction { .. })
+        ));
+    }
+
+    #[test]
+    fn doubling_doubles_the_bet() {
+        let mut rules = stacked_shoe("6s 6h 5d 7h 9c");
+        rules.double(0).unwrap();
+
+        let state = rules.current_state();
+        assert_eq!(20, state.seats[0].hands[0].bet);
+        assert_eq!(100 - 20, state.players[0].bankroll);
+    }
+
+    #[test]
+    fn a_player_in_two_seats_plays_them_in_seat_order() {
+        let shoe = Shoe::stacked("8s Ts 5s 6h 8d 9s 6s Th 3c 9c Tc Ks").unwrap();
+        let mut rules = Rules::with_cards(Default::default(), shoe, 0);
+        rules.add_player().unwrap();
+        rules.add_player().unwrap();
+        rules.take_seat(0).unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.buy_in(1, 100).unwrap();
+        for seat in 0..3 {
+            rules.place_bet(seat, 10).unwrap();
+        }
+        assert_eq!(80, rules.current_state().players[0].bankroll);
+        rules.start_playing().unwrap();
+
+        assert_eq!(Err(RuleError::NotYourTurn(2)), rules.double(2));
+        rules.split(0).unwrap();
+        rules.stand(0).unwrap();
+        rules.stand(0).unwrap();
+        rules.stand(1).unwrap();
+        rules.double(2).unwrap();
+        assert_eq!(60, rules.current_state().players[0].bankroll);
+        rules.done_playing().unwrap();
+
+        let turns: Vec<(usize, usize)> = rules
+            .history()
+            .events()
+            .filter_map(|event| match *event {
+                Event::PlayerSplit { seat, hand }
+                | Event::PlayerStood { seat, hand }
+                | Event::PlayerDoubled { seat, hand } => Some((seat, hand)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec![(0, 0), (0, 0), (0, 1), (1, 0), (2, 0)], turns);
+
+        let state = rules.current_state();
+        assert_eq!(vec![Outcome::Win, Outcome::Win], state.results[0]);
+        assert_eq!(vec![Outcome::Win], state.results[2]);
+        assert_eq!(60 + 20 + 20 + 40, state.players[0].bankroll);
+        assert_eq!(110, state.players[1].bankroll);
+    }
+
+    #[test]
+    fn cashing_out_empties_every_seat_of_the_player() {
+        let mut rules: Rules = Default::default();
+        rules.add_player().unwrap();
+        rules.add_player().unwrap();
+        assert_eq!(Err(RuleError::NoSuchPlayer(2)), rules.take_seat(2));
+        rules.take_seat(0).unwrap();
+        rules.buy_in(0, 100).unwrap();
+        rules.buy_in(1, 100).unwrap();
+        rules.place_bet(0, 10).unwrap();
+        rules.place_bet(1, 10).unwrap();
+        rules.place_bet(2, 25).unwrap();
+
+        assert_eq!(Ok(100), rules.cash_out(0));
+        let state = rules.current_state();
+        assert_eq!(0, state.players[0].bankroll);
+        assert!(!state.seats[0].is_betting() && !state.seats[2].is_betting());
+        assert_eq!(10, state.seats[1].total_bet());
+
+        rules.start_playing().unwrap();
+        assert_eq!(
+            Err(RuleError::InvalidState(rules.current_progress().clone())),
+            rules.take_seat(1)
+        );
+    }
+
+    #[test]
+    fn players_without_a_bet_sit_out() {
+        let shoe = Shoe::stacked("Ts 6h 9d 7h Ks").unwrap();
+        let mut rules = Rules::with_cards(Default::default(), shoe, 0);
+        seat_players(&mut rules, 2);
+        rules.place_bet(1, 10).unwrap();
+        rules.start_playing().unwrap();
+
+        let state = rules.current_state();
+        assert_eq!(0, state.seats[0].hands[0].number_of_cards());
+        assert_eq!(2, state.seats[1].hands[0].number_of_cards());
+        assert_ne!(Some(0), state.current_seat());
+
+        while let Some(seat) = rules.current_state().current_seat() {
+            rules.stand(seat).unwrap();
+        }
+        rules.done_playing().unwrap();
+        assert!(rules.current_state().results[0].is_empty());
+        assert_eq!(1, rules.current_state().results[1].len());
+        assert_eq!(10_000, rules.current_state().players[0].bankroll);
+    }
+
+    #[test]
+    fn cashing_out_leaves_the_ledger() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 2);
+        play_round(&mut rules);
+        rules.new_game().unwrap();
+
+        let first = rules.cash_out(0).unwrap();
+        let second = rules.cash_out(1).unwrap();
+        let state = rules.current_state();
+        assert_eq!(first + second + state.house.bankroll, 20_000);
+        assert_eq!(20_000, state.ledger.cashed_out() + state.house.bankroll);
+        assert!(state.is_balanced());
+    }
+
+    #[test]
+    fn buy_ins_have_to_bring_chips() {
+        let mut rules: Rules = Default::default();
+        rules.add_player().unwrap();
+        assert_eq!(Err(RuleError::InvalidBuyIn(0)), rules.buy_in(0, 0));
+        assert_eq!(Err(RuleError::InvalidBuyIn(-100)), rules.buy_in(0, -100));
+        assert_eq!(Err(RuleError::NoSuchPlayer(1)), rules.buy_in(1, 100));
+
+        let state = rules.current_state();
+        assert_eq!(0, state.players[0].bankroll);
+        assert_eq!(0, state.ledger.balance());
+    }
+
+    #[test]
+    fn settled_bets_are_off_the_table() {
+        let mut rules = stacked_shoe("Ts 6h 9d 7h Ks");
+        rules.stand(0).unwrap();
+        assert_eq!(10, rules.current_state().chips_on_table());
+
+        rules.done_playing().unwrap();
+        let state = rules.current_state();
+        assert_eq!(10, state.seats[0].total_bet());
+        assert_eq!(0, state.chips_on_table());
+        assert_eq!(110, state.players[0].bankroll);
+        assert!(state.is_balanced());
+    }
+
+    /// A strategy which always tries to split, legal or not.
+    struct AlwaysSplit;
+
+    impl Strategy for AlwaysSplit {
+        fn bet(&mut self, _seat: usize, _state: &GameState, table: &TableRules) -> Chips {
+            table.min_bet
+        }
+
+        fn act(&mut self, _decision: &Decision) -> Action {
+            Action::Split
+        }
+    }
+
+    #[test]
+    fn iterating_needs_a_strategy() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 1);
+        assert!(rules.next().is_none());
+        assert_eq!(Some(&Halt::NoStrategy), rules.halted());
+    }
+
+    #[test]
+    fn iterating_plays_until_the_shoe_needs_reshuffling() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 2);
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 25 }));
+
+        let mut games = 0;
+        for state in rules.by_ref() {
+            assert!(state.is_balanced());
+            if state.progress == Progress::Done {
+                games += 1;
+            }
+        }
+        assert!(games > 5);
+        assert_eq!(Some(&Halt::Reshuffle), rules.halted());
+        assert!(rules.next().is_none());
+
+        rules.reshuffle().unwrap();
+        assert!(rules.next().is_some());
+        assert_eq!(None, rules.halted());
+    }
+
+    #[test]
+    fn seeded_games_repeat() {
+        let play = |seed| {
+            let mut rules = Rules::with_seed(Default::default(), seed);
+            seat_players(&mut rules, 2);
+            rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+            let mut states: Vec<GameState> = rules.by_ref().collect();
+            rules.reshuffle().unwrap();
+            states.extend(rules.by_ref().take(50));
+            states
+        };
+
+        let first = play(99);
+        assert_eq!(first, play(99));
+        assert_ne!(first, play(100));
+
+        let table: TableRules = Default::default();
+        let last = first.last().unwrap();
+        assert_eq!(1, last.shuffles);
+        let mut replayed = table.shuffled_shoe(last.seed, last.shuffles);
+        let mut remaining = last.shoe.clone();
+        while replayed.cards_left() > remaining.cards_left() {
+            replayed.deal();
+        }
+        while let Some(card) = remaining.deal() {
+            assert_eq!(Some(card), replayed.deal());
+        }
+    }
+
+    #[test]
+    fn played_cards_go_to_the_discard_tray() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 3);
+        let size = rules.table_rules().shoe_size();
+
+        while !rules.needs_reshuffle() {
+            play_round(&mut rules);
+            rules.new_game().unwrap();
+            let shoe = &rules.current_state().shoe;
+            assert_eq!(size, shoe.cards_left() + shoe.discards());
+        }
+
+        let shoe = &rules.current_state().shoe;
+        assert!(shoe.cards_left() < rules.table_rules().reshuffle_at());
+        rules.reshuffle().unwrap();
+        let shoe = &rules.current_state().shoe;
+        assert_eq!(size, shoe.cards_left());
+        assert_eq!(0, shoe.discards());
+        assert!(!rules.needs_reshuffle());
+    }
+
+    #[test]
+    fn iterating_stops_when_players_are_out_of_money() {
+        let mut rules = Rules::new(TableRules {
+            penetration: 0.0,
+            ..Default::default()
+        });
+        rules.add_player().unwrap();
+        rules.buy_in(0, 50).unwrap();
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+
+        let mut steps = 0;
+        while rules.next().is_some() {
+            steps += 1;
+            if rules.needs_reshuffle() && rules.is_starting() {
+                rules.reshuffle().unwrap();
+            }
+            assert!(steps < 100_000);
+        }
+
+        let player = &rules.current_state().players[0];
+        assert_eq!(Some(&Halt::OutOfMoney), rules.halted());
+        assert!(player.bankroll < 10);
+    }
+
+    #[test]
+    fn iterating_stops_on_an_illegal_action() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 1);
+        rules.set_strategy(Box::new(AlwaysSplit));
+
+        while rules.next().is_some() {}
+        assert!(matches!(
+            rules.halted(),
+            Some(Halt::Refused(RuleError::IllegalAction {
+                action: Action::Split,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn take_plays_a_fixed_number_of_steps() {
+        let mut rules: Rules = Default::default();
+        seat_players(&mut rules, 1);
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+
+        assert_eq!(25, rules.by_ref().take(25).count());
+    }
+
+    #[test]
+    fn events_explain_a_split_round() {
+        let mut rules = stacked_shoe("8s 6h 8d Th 3c 9s Tc");
+        rules.split(0).unwrap();
+        rules.stand(0).unwrap();
+        rules.stand(0).unwrap();
+        rules.done_playing().unwrap();
+
+        let events: Vec<String> = rules
+            .history()
+            .events()
+            .map(|event| event.to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                "A new player sat down in seat 0",
+                "Player 0 bought in for 100",
+                "Seat 0 bet 10",
+                "The round started",
+                "8s was dealt to seat 0 hand 0",
+                "6h was dealt to the house",
+                "8d was dealt to seat 0 hand 0",
+                "Th was dealt to the house",
+                "Seat 0 split hand 0",
+                "3c was dealt to seat 0 hand 0",
+                "Seat 0 stood on hand 0",
+                "9s was dealt to seat 0 hand 1",
+                "Seat 0 stood on hand 1",
+                "Seat 0's turn is over",
+                "Tc was dealt to the house",
+                "Seat 0 hand 0 was paid 20 for a Win",
+                "Seat 0 hand 1 was paid 20 for a Win",
+            ],
+            events
+        );
+
+        let split = &rules.history().path()[9];
+        assert_eq!(
+            &[
+                Event::PlayerSplit { seat: 0, hand: 0 },
+                Event::CardDealt {
+                    to: Recipient::Seat { seat: 0, hand: 0 },
+                    card: Card::new(Rank::Three, Suit::Clubs),
+                },
+            ],
+            rules.history().get(*split).unwrap().events()
+        );
+    }
+
+    #[test]
+    fn a_bust_finishes_the_hand_and_the_turn() {
+        let mut rules = stacked_shoe("Ts 6h 6d Th 9c");
+        rules.hit(0).unwrap();
+
+        let events = rules.history().current_generation().events();
+        assert_eq!(
+            &[
+                Event::HandFinished { seat: 0, hand: 0 },
+                Event::TurnOver { seat: 0 },
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn observers_see_every_event_as_it_happens() {
+        let seen = Rc::new(RefCell::new(vec![]));
+        let log = seen.clone();
+        let mut rules: Rules = Default::default();
+        rules.add_observer(Box::new(move |event: &Event, state: &GameState| {
+            assert!(state.is_balanced());
+            log.borrow_mut().push(event.clone());
+        }));
+        seat_players(&mut rules, 2);
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+        rules.by_ref().take(200).for_each(drop);
+
+        let events: Vec<Event> = rules.history().events().cloned().collect();
+        assert_eq!(events, *seen.borrow());
+
+        let before = seen.borrow().len();
+        rules.undo().unwrap();
+        rules.redo().unwrap();
+        let mut fork = rules.fork();
+        if fork.needs_reshuffle() {
+            fork.reshuffle().unwrap();
+        }
+        fork.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+        assert_eq!(10, fork.by_ref().take(10).count());
+        assert_eq!(before, seen.borrow().len());
+    }
+
+    #[test]
+    fn insurance_pays_two_to_one_on_a_house_blackjack() {
+        let mut rules = stacked(&[10, 1, 9, 13]);
+        assert_eq!(&Progress::Insurance, rules.current_progress());
+        assert_eq!(Some(0), rules.current_state().insuring_seat());
+        assert_eq!(
+            Err(RuleError::InvalidState(Progress::Insurance)),
+            rules.stand(0)
+        );
+        assert_eq!(
+            Err(RuleError::InsuranceOutOfRange {
+                seat: 0,
+                chips: 6,
+                max: 5
+            }),
+            rules.insure(0, Insurance::Taken(6))
+        );
+        assert_eq!(
+            Err(RuleError::NoBlackjack(0)),
+            rules.insure(0, Insurance::EvenMoney)
+        );
+
+        rules.insure(0, Insurance::Taken(5)).unwrap();
+        assert_eq!(85, rules.current_state().players[0].bankroll);
+        assert_eq!(15, rules.current_state().chips_on_table());
+        assert_eq!(None, rules.current_state().current_seat());
+
+        rules.done_playing().unwrap();
+        let state = rules.current_state();
+        assert_eq!(vec![vec![Outcome::Lose]], *state.results);
+        assert_eq!(100, state.players[0].bankroll);
+        assert!(rules
+            .history()
+            .events()
+            .any(|event| event == &Event::HousePeeked { blackjack: true }));
+        assert_eq!(
+            Some(&Event::InsurancePaid { seat: 0, chips: 15 }),
+            rules.history().current_generation().events().last()
+        );
+    }
+
+    #[test]
+    fn insurance_is_lost_when_the_house_has_no_blackjack() {
+        let mut rules = stacked(&[10, 1, 9, 7]);
+        rules.insure(0, Insurance::Taken(5)).unwrap();
+        assert_eq!(
+            Some(&Event::HousePeeked { blackjack: false }),
+            rules.history().current_generation().events().first()
+        );
+
+        rules.stand(0).unwrap();
+        rules.done_playing().unwrap();
+        assert_eq!(vec![vec![Outcome::Win]], *rules.current_state().results);
+        assert_eq!(105, rules.current_state().players[0].bankroll);
+        assert_eq!(-5, rules.current_state().house.bankroll);
+    }
+
+    #[test]
+    fn insurance_cannot_be_negative() {
+        let mut rules = stacked(&[10, 1, 9, 13]);
+        assert_eq!(
+            Err(RuleError::InsuranceOutOfRange {
+                seat: 0,
+                chips: -5,
+                max: 5
+            }),
+            rules.insure(0, Insurance::Taken(-5))
+        );
+
+        let state = rules.current_state();
+        assert_eq!(90, state.players[0].bankroll);
+        assert_eq!(10, state.chips_on_table());
+        assert_eq!(Some(0), state.insuring_seat());
+    }
+
+    #[test]
+    fn even_money_is_paid_whatever_the_house_has() {
+        for house in [13, 7] {
+            let mut rules = stacked(&[1, 1, 13, house]);
+            rules.insure(0, Insurance::EvenMoney).unwrap();
+            rules.done_playing().unwrap();
+
+            let state = rules.current_state();
+            assert_eq!(vec![vec![Outcome::EvenMoney]], *state.results);
+            assert_eq!(110, state.players[0].bankroll);
+            assert!(state.insurance.contains(&Insurance::EvenMoney));
+        }
+    }
+
+    #[test]
+    fn a_peeked_blackjack_ends_the_game_before_anyone_acts() {
+        let mut rules = stacked(&[10, 10, 9, 1]);
+        assert_eq!(&Progress::Playing, rules.current_progress());
+        assert_eq!(None, rules.current_state().current_seat());
+        assert!(rules.legal_actions(0).is_empty());
+
+        rules.done_playing().unwrap();
+        assert_eq!(vec![vec![Outcome::Lose]], *rules.current_state().results);
+        assert_eq!(90, rules.current_state().players[0].bankroll);
+
+        let no_insurance = TableRules {
+            insurance: false,
+            ..Default::default()
+        };
+        let rules = stacked_at(no_insurance, &[10, 1, 9, 13]);
+        assert_eq!(&Progress::Playing, rules.current_progress());
+        assert_eq!(None, rules.current_state().current_seat());
+    }
+
+    #[test]
+    fn a_house_blackjack_without_a_hole_card_takes_doubled_bets() {
+        let cards = [5, 10, 6, 9, 1];
+        let mut european = stacked_at(TableRules::european(), &cards);
+        european.double(0).unwrap();
+        european.done_playing().unwrap();
+        assert!(european.current_state().house.hand.is_blackjack());
+        assert_eq!(80, european.current_state().players[0].bankroll);
+
+        let original_bets_only = TableRules {
+            original_bets_only: true,
+            ..TableRules::european()
+        };
+        let mut rules = stacked_at(original_bets_only, &cards);
+        rules.double(0).unwrap();
+        rules.done_playing().unwrap();
+        assert_eq!(vec![vec![Outcome::Lose]], *rules.current_state().results);
+        assert_eq!(90, rules.current_state().players[0].bankroll);
+    }
+
+    #[test]
+    fn split_hands_are_handed_back_with_original_bets_only() {
+        let table = TableRules {
+            original_bets_only: true,
+            ..TableRules::european()
+        };
+        let mut rules = stacked_at(table, &[8, 10, 8, 3, 9, 1]);
+        rules.split(0).unwrap();
+        rules.stand(0).unwrap();
+        rules.stand(0).unwrap();
+        rules.done_playing().unwrap();
+
+        let state = rules.current_state();
+        assert_eq!(vec![vec![Outcome::Lose, Outcome::Push]], *state.results);
+        assert_eq!(90, state.players[0].bankroll);
+        assert!(state.is_balanced());
+    }
+
+    #[test]
+    fn strategies_decline_insurance_unless_they_say_otherwise() {
+        let mut rules = stacked(&[10, 1, 9, 7]);
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+        rules.next().unwrap();
+
+        assert_eq!(vec![Insurance::Declined], *rules.current_state().insurance);
+        assert_eq!(100 - 10, rules.current_state().players[0].bankroll);
+        assert_eq!(Some(0), rules.current_state().current_seat());
+    }
 }
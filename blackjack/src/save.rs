@@ -0,0 +1,943 @@
+use crate::{
+    error::SaveError,
+    event::Event,
+    game_state::{GameState, Insurance, Outcome, Progress},
+    generation::Generation,
+    history::History,
+    ledger::Ledger,
+    rules::Rules,
+    shared::Shared,
+    table_rules::{BlackjackPayout, DoubleRestriction, SurrenderRule, TableRules},
+};
+use cards::prelude::CardSource;
+use player::{Chips, Hand, House, Player, Seat};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    time::SystemTime,
+};
+
+/// Every save starts with these bytes so anything else is turned away before it is decoded.
+const MAGIC: [u8; 8] = *b"BLACKJAK";
+
+/// The format version written by this build.  Bump this whenever the saved layout changes and
+/// teach `decode` how to read the version before it.
+///
+/// * 1: The generation tree.  The game was at the newest generation.
+/// * 2: The generation tree with a cursor marking where the game is and what can be redone.
+/// * 3: Every generation in the order they were made, linked by id.
+/// * 4: Each generation also keeps the events which led to it.
+/// * 5: The table rules say how insurance and peeking work and each state keeps the insurance.
+/// * 6: Players and seats are kept apart, so a player can play more than one seat.
+const SAVE_VERSION: u32 = 6;
+
+/// The magic, the version and the checksum of the game which follows.
+const HEADER_LENGTH: usize = MAGIC.len() + 4 + 8;
+
+/// A save is laid out as:
+///
+/// * 8 bytes of magic, `BLACKJAK`
+/// * the format version as a little endian u32
+/// * an FNV-1a checksum of the game as a little endian u64
+/// * the table rules and the whole history, bincode encoded
+///
+/// The strategy is not saved.  A loaded game needs its strategy set again before it can be used
+/// as an iterator.
+impl<S: CardSource + Serialize + DeserializeOwned> Rules<S> {
+    /// Write the game out, including every generation, the order of the cards left in the shoe
+    /// and everything needed to shuffle the next shoe the same way.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ MimicTheDealer, Rules, TableRules };
+    ///
+    /// let mut rule = Rules::with_seed(TableRules::vegas_strip(), 17);
+    /// rule.add_player();
+    /// rule.buy_in(0, 1000);
+    /// rule.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+    /// rule.by_ref().take(20).for_each(drop);
+    ///
+    /// let mut save = vec![];
+    /// rule.save(&mut save).unwrap();
+    /// let loaded: Rules = Rules::load(save.as_slice()).unwrap();
+    ///
+    /// assert_eq!(rule.current_state(), loaded.current_state());
+    /// assert_eq!(rule.table_rules(), loaded.table_rules());
+    /// ```
+    ///
+    /// * `writer`: Where the save is written
+    pub fn save<W: Write>(&self, mut writer: W) -> Result<(), SaveError> {
+        let game = bincode::serialize(&(self.table_rules(), self.history()))
+            .map_err(|error| SaveError::Corrupt(error.to_string()))?;
+
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&SAVE_VERSION.to_le_bytes())?;
+        writer.write_all(&checksum(&game).to_le_bytes())?;
+        writer.write_all(&game)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read a game written by save.  Saves from older versions are migrated as they are read.
+    ///
+    /// * `reader`: Where the save is read from
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, SaveError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() < HEADER_LENGTH || bytes[..MAGIC.len()] != MAGIC {
+            return Err(SaveError::NotASave);
+        }
+        let (header, game) = bytes.split_at(HEADER_LENGTH);
+        let version = u32::from_le_bytes(header[8..12].try_into().expect("The header is sized"));
+        let expected = u64::from_le_bytes(header[12..20].try_into().expect("The header is sized"));
+
+        if version > SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(version));
+        }
+        if checksum(game) != expected {
+            return Err(SaveError::Corrupt(
+                "the checksum does not match".to_string(),
+            ));
+        }
+
+        let (table, history) = decode(version, game)?;
+        Ok(Rules::from_history(table, history))
+    }
+
+    /// Save the game to a file, replacing anything already there.
+    ///
+    /// * `path`: The file to write
+    pub fn save_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        self.save(BufWriter::new(File::create(path)?))
+    }
+
+    /// Load a game saved to a file.
+    ///
+    /// * `path`: The file to read
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, SaveError> {
+        Self::load(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Decode the game from a save of the given version.  Each older version is read in its own
+/// layout and migrated forward one version at a time until it is the current layout.
+fn decode<S: DeserializeOwned>(
+    version: u32,
+    game: &[u8],
+) -> Result<(TableRules, History<S>), SaveError> {
+    let corrupt = |error| SaveError::Corrupt(format!("the game could not be read, {}", error));
+    match version {
+        1 => {
+            let (table, root): (UninsuredTable, NestedGeneration<S>) =
+                bincode::deserialize(game).map_err(corrupt)?;
+            Ok((table.into(), NestedHistory::at_newest(root).flatten()?))
+        }
+        2 => {
+            let (table, history): (UninsuredTable, NestedHistory<S>) =
+                bincode::deserialize(game).map_err(corrupt)?;
+            Ok((table.into(), history.flatten()?))
+        }
+        3 => {
+            let (table, history): (UninsuredTable, EventlessHistory<S>) =
+                bincode::deserialize(game).map_err(corrupt)?;
+            Ok((table.into(), history.try_into()?))
+        }
+        4 => {
+            let (table, history): (UninsuredTable, SavedHistory<UninsuredState<S>>) =
+                bincode::deserialize(game).map_err(corrupt)?;
+            Ok((table.into(), history.try_into()?))
+        }
+        5 => {
+            let (table, history): (TableRules, SavedHistory<UnseatedState<S>>) =
+                bincode::deserialize(game).map_err(corrupt)?;
+            Ok((table, history.try_into()?))
+        }
+        SAVE_VERSION => {
+            let (table, history): (TableRules, History<S>) =
+                bincode::deserialize(game).map_err(corrupt)?;
+            Ok((table, history))
+        }
+        _ => Err(SaveError::Corrupt(format!(
+            "there is no save format version {}",
+            version
+        ))),
+    }
+}
+
+/// A generation as versions 1 and 2 saved it, holding its branches in the order they were made.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct NestedGeneration<S> {
+    state: UninsuredState<S>,
+    timestamp: SystemTime,
+    children: Vec<NestedGeneration<S>>,
+}
+
+/// The history as version 2 saved it.  The cursor is the branch taken at each generation from the
+/// root and undone is the branches to redo, the next one last.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct NestedHistory<S> {
+    root: NestedGeneration<S>,
+    cursor: Vec<usize>,
+    undone: Vec<usize>,
+}
+
+impl<S> NestedHistory<S> {
+    /// Version 1 had no cursor.  The game was at the newest generation, found by following the
+    /// newest branch from the root.
+    fn at_newest(root: NestedGeneration<S>) -> Self {
+        let mut cursor = vec![];
+        let mut generation = &root;
+        while let Some((branch, newest)) = generation
+            .children
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, child)| child.timestamp)
+        {
+            cursor.push(branch);
+            generation = newest;
+        }
+
+        NestedHistory {
+            root,
+            cursor,
+            undone: vec![],
+        }
+    }
+
+    /// Version 2 nested the generations.  They are numbered root first, with each generation's
+    /// branches in the order they were made, so a parent always comes before its branches.
+    fn flatten(self) -> Result<History<S>, SaveError> {
+        let line: Vec<usize> = self
+            .cursor
+            .iter()
+            .chain(self.undone.iter().rev())
+            .copied()
+            .collect();
+        let mut generations = vec![];
+        let mut on_line = vec![];
+        flatten(self.root, None, Some(&line), &mut generations, &mut on_line);
+
+        if on_line.len() != line.len() + 1 {
+            return Err(SaveError::Corrupt(
+                "the cursor is not on the history".to_string(),
+            ));
+        }
+        let cursor = on_line[self.cursor.len()];
+        let undone = on_line[self.cursor.len() + 1..]
+            .iter()
+            .rev()
+            .copied()
+            .collect();
+        History::from_parts(generations, cursor, undone)
+    }
+}
+
+/// Number a nested generation and everything after it.
+///
+/// * `nested`: The generation to number
+/// * `parent`: The id of the generation it came after
+/// * `line`: The branches still to take to follow the cursor's line, if this generation is on it
+/// * `generations`: Every generation numbered so far
+/// * `on_line`: The ids of the generations on the cursor's line
+fn flatten<S>(
+    nested: NestedGeneration<S>,
+    parent: Option<usize>,
+    line: Option<&[usize]>,
+    generations: &mut Vec<Generation<S>>,
+    on_line: &mut Vec<usize>,
+) -> usize {
+    let id = generations.len();
+    let number = parent.map_or(0, |parent| generations[parent].number() + 1);
+    generations.push(Generation::new(
+        id,
+        parent,
+        number,
+        nested.state.into(),
+        vec![],
+    ));
+    if line.is_some() {
+        on_line.push(id);
+    }
+
+    for (branch, child) in nested.children.into_iter().enumerate() {
+        let line = match line.and_then(|line| line.split_first()) {
+            Some((&next, rest)) if next == branch => Some(rest),
+            _ => None,
+        };
+        let child = flatten(child, Some(id), line, generations, on_line);
+        generations[id].add_branch(child);
+    }
+    id
+}
+
+/// A generation as version 3 saved it, before generations kept their events.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct EventlessGeneration<S> {
+    id: usize,
+    parent: Option<usize>,
+    number: usize,
+    state: UninsuredState<S>,
+    branches: Vec<usize>,
+}
+
+/// The history as version 3 saved it.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct EventlessHistory<S> {
+    generations: Vec<EventlessGeneration<S>>,
+    cursor: usize,
+    undone: Vec<usize>,
+}
+
+/// Version 3 did not keep any events, so every generation is given none.
+impl<S> TryFrom<EventlessHistory<S>> for History<S> {
+    type Error = SaveError;
+
+    fn try_from(history: EventlessHistory<S>) -> Result<Self, SaveError> {
+        let generations = history
+            .generations
+            .into_iter()
+            .map(|old| {
+                let mut generation =
+                    Generation::new(old.id, old.parent, old.number, old.state.into(), vec![]);
+                for branch in old.branches {
+                    generation.add_branch(branch);
+                }
+                generation
+            })
+            .collect();
+        History::from_parts(generations, history.cursor, history.undone)
+    }
+}
+
+/// A generation as versions 4 and 5 saved it, with its state in the layout of its version.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct SavedGeneration<T> {
+    id: usize,
+    parent: Option<usize>,
+    number: usize,
+    state: T,
+    events: Vec<Event>,
+    branches: Vec<usize>,
+}
+
+/// The history as versions 4 and 5 saved it.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct SavedHistory<T> {
+    generations: Vec<SavedGeneration<T>>,
+    cursor: usize,
+    undone: Vec<usize>,
+}
+
+/// Versions 4 and 5 only differ in their states.
+impl<S, T: Into<GameState<S>>> TryFrom<SavedHistory<T>> for History<S> {
+    type Error = SaveError;
+
+    fn try_from(history: SavedHistory<T>) -> Result<Self, SaveError> {
+        let generations = history
+            .generations
+            .into_iter()
+            .map(|old| {
+                let mut generation =
+                    Generation::new(old.id, old.parent, old.number, old.state.into(), old.events);
+                for branch in old.branches {
+                    generation.add_branch(branch);
+                }
+                generation
+            })
+            .collect();
+        History::from_parts(generations, history.cursor, history.undone)
+    }
+}
+
+/// A game state as versions 1 to 4 saved it, before anyone could take insurance.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize, Default))]
+struct UninsuredState<S> {
+    progress: Progress,
+    house: House,
+    players: Vec<SeatedPlayer>,
+    shoe: S,
+    turn: usize,
+    results: Vec<Vec<Outcome>>,
+    ledger: Ledger,
+    seed: u64,
+    shuffles: u64,
+}
+
+/// Nobody was ever insured.
+impl<S> From<UninsuredState<S>> for GameState<S> {
+    fn from(state: UninsuredState<S>) -> Self {
+        UnseatedState {
+            progress: state.progress,
+            house: state.house,
+            players: state.players,
+            shoe: state.shoe,
+            turn: state.turn,
+            results: state.results,
+            insurance: vec![],
+            ledger: state.ledger,
+            seed: state.seed,
+            shuffles: state.shuffles,
+        }
+        .into()
+    }
+}
+
+/// A game state as version 5 saved it, when every player sat in exactly one seat.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize, Default))]
+struct UnseatedState<S> {
+    progress: Progress,
+    house: House,
+    players: Vec<SeatedPlayer>,
+    shoe: S,
+    turn: usize,
+    results: Vec<Vec<Outcome>>,
+    insurance: Vec<Insurance>,
+    ledger: Ledger,
+    seed: u64,
+    shuffles: u64,
+}
+
+/// Every player keeps their chips and sits in the seat they were in, so the turn, the results
+/// and the insurance all still line up with the seats.
+impl<S> From<UnseatedState<S>> for GameState<S> {
+    fn from(state: UnseatedState<S>) -> Self {
+        let (players, seats): (Vec<Player>, Vec<Shared<Seat>>) = state
+            .players
+            .into_iter()
+            .enumerate()
+            .map(|(seat, old)| {
+                let player = Player {
+                    bankroll: old.bankroll,
+                };
+                let seat = Seat {
+                    player: seat,
+                    hands: old.hands,
+                    active: old.active,
+                };
+                (player, seat.into())
+            })
+            .unzip();
+
+        GameState {
+            progress: state.progress,
+            house: state.house.into(),
+            players: players.into(),
+            seats,
+            shoe: state.shoe,
+            turn: state.turn,
+            results: state.results.into(),
+            insurance: state.insurance.into(),
+            ledger: state.ledger,
+            seed: state.seed,
+            shuffles: state.shuffles,
+        }
+    }
+}
+
+/// A player as versions 1 to 5 saved them, sitting in the seat of the same number with their
+/// hands and their chips together.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize, Clone))]
+struct SeatedPlayer {
+    hands: Vec<Hand>,
+    active: usize,
+    bankroll: Chips,
+}
+
+/// The table rules as versions 1 to 4 saved them.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct UninsuredTable {
+    dealer_hits_soft_17: bool,
+    double_after_split: bool,
+    double: DoubleRestriction,
+    max_splits: usize,
+    resplit_aces: bool,
+    hit_split_aces: bool,
+    surrender: SurrenderRule,
+    blackjack_payout: BlackjackPayout,
+    decks: usize,
+    penetration: f32,
+    hole_card: bool,
+    min_bet: Chips,
+    max_bet: Chips,
+}
+
+/// Older games were played without insurance or peeking, where a house blackjack took every bet,
+/// so they carry on that way.
+impl From<UninsuredTable> for TableRules {
+    fn from(table: UninsuredTable) -> Self {
+        TableRules {
+            dealer_hits_soft_17: table.dealer_hits_soft_17,
+            double_after_split: table.double_after_split,
+            double: table.double,
+            max_splits: table.max_splits,
+            resplit_aces: table.resplit_aces,
+            hit_split_aces: table.hit_split_aces,
+            surrender: table.surrender,
+            blackjack_payout: table.blackjack_payout,
+            decks: table.decks,
+            penetration: table.penetration,
+            hole_card: table.hole_card,
+            insurance: false,
+            dealer_peeks: false,
+            original_bets_only: false,
+            min_bet: table.min_bet,
+            max_bet: table.max_bet,
+        }
+    }
+}
+
+/// A 64 bit FNV-1a hash.  It is not meant to stop tampering, only to catch a damaged save before
+/// a game is played from it.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        checksum, EventlessGeneration, EventlessHistory, NestedGeneration, NestedHistory,
+        SavedGeneration, SavedHistory, SeatedPlayer, UninsuredState, UninsuredTable, UnseatedState,
+        HEADER_LENGTH, SAVE_VERSION,
+    };
+    use crate::{
+        error::SaveError,
+        event::Event,
+        game_state::{GameState, Insurance, Progress},
+        rules::{Halt, Rules},
+        strategy::MimicTheDealer,
+        table_rules::TableRules,
+    };
+    use cards::prelude::{ContinuousShuffler, Shoe};
+    use player::Chips;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn played(seed: u64, rounds: usize) -> Rules {
+        let mut rules = Rules::with_seed(TableRules::downtown_vegas(), seed);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 1000).unwrap();
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+        rules.by_ref().take(rounds).for_each(drop);
+        rules
+    }
+
+    fn saved(rules: &Rules) -> Vec<u8> {
+        let mut save = vec![];
+        rules.save(&mut save).unwrap();
+        save
+    }
+
+    #[test]
+    fn a_loaded_game_plays_on_exactly_the_same() {
+        let mut rules = played(1, 30);
+        let mut loaded: Rules = Rules::load(saved(&rules).as_slice()).unwrap();
+        assert_eq!(rules.history(), loaded.history());
+
+        loaded.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+        for _ in 0..500 {
+            let state = rules.next();
+            assert_eq!(state, loaded.next());
+            if state.is_none() {
+                assert_eq!(Some(&Halt::Reshuffle), rules.halted());
+                rules.reshuffle().unwrap();
+                loaded.reshuffle().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn continuous_shufflers_keep_their_shuffle() {
+        let csm = ContinuousShuffler::with_seed(2, 15, 4);
+        let mut rules = Rules::with_cards(TableRules::vegas_strip(), csm, 4);
+        rules.add_player().unwrap();
+        rules.buy_in(0, 1000).unwrap();
+        rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+        rules.by_ref().take(10).for_each(drop);
+
+        let mut save = vec![];
+        rules.save(&mut save).unwrap();
+        let mut loaded: Rules<ContinuousShuffler> = Rules::load(save.as_slice()).unwrap();
+        loaded.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+
+        let played: Vec<_> = rules.take(200).collect();
+        assert_eq!(played, loaded.take(200).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn saving_to_a_file() {
+        let path = std::env::temp_dir().join(format!("blackjack-{}.save", std::process::id()));
+        let rules = played(2, 10);
+        rules.save_file(&path).unwrap();
+        let loaded: Rules = Rules::load_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(rules.current_state(), loaded.current_state());
+        assert!(matches!(
+            Rules::<Shoe>::load_file(&path),
+            Err(SaveError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn other_files_are_not_saves() {
+        for bytes in [&b""[..], b"BLACKJAK", b"not a blackjack save at all"] {
+            assert!(matches!(
+                Rules::<Shoe>::load(bytes),
+                Err(SaveError::NotASave)
+            ));
+        }
+    }
+
+    #[test]
+    fn damaged_saves_are_corrupt() {
+        let save = saved(&played(3, 10));
+
+        let mut flipped = save.clone();
+        flipped[HEADER_LENGTH + 40] ^= 0x10;
+        let mut truncated = save.clone();
+        truncated.truncate(save.len() - 7);
+
+        for damaged in [flipped, truncated] {
+            let error = Rules::<Shoe>::load(damaged.as_slice()).err().unwrap();
+            assert!(matches!(error, SaveError::Corrupt(_)));
+            assert_eq!(
+                "The save is corrupt: the checksum does not match.",
+                error.to_string()
+            );
+        }
+
+        let mut garbage = save[..HEADER_LENGTH].to_vec();
+        garbage.extend_from_slice(&[0xff; 16]);
+        garbage[12..20].copy_from_slice(&checksum(&[0xff; 16]).to_le_bytes());
+        assert!(matches!(
+            Rules::<Shoe>::load(garbage.as_slice()),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn histories_which_do_not_hold_together_are_corrupt() {
+        let mut rules = played(6, 10);
+        rules.undo().unwrap();
+        rules.undo().unwrap();
+        let save = saved(&rules);
+        // The current history is laid out the same way as versions 4 and 5 laid theirs out.
+        let (table, history): (TableRules, SavedHistory<GameState<Shoe>>) =
+            bincode::deserialize(&save[HEADER_LENGTH..]).unwrap();
+        assert!(Rules::<Shoe>::load(save_with(SAVE_VERSION, &table, &history).as_slice()).is_ok());
+
+        let corrupt = |problem: &str, tamper: &dyn Fn(&mut SavedHistory<GameState<Shoe>>)| {
+            let mut tampered: SavedHistory<GameState<Shoe>> =
+                bincode::deserialize(&bincode::serialize(&history).unwrap()).unwrap();
+            tamper(&mut tampered);
+            let save = save_with(SAVE_VERSION, &table, &tampered);
+
+            let error = Rules::<Shoe>::load(save.as_slice()).err().unwrap();
+            assert!(matches!(error, SaveError::Corrupt(_)));
+            assert_eq!(
+                format!(
+                    "The save is corrupt: the game could not be read, {}.",
+                    problem
+                ),
+                error.to_string()
+            );
+        };
+
+        let last = history.generations.len() - 1;
+        corrupt("there are no generations", &|history| {
+            history.generations.clear()
+        });
+        corrupt("the cursor is not on the history", &|history| {
+            history.cursor = last + 1
+        });
+        corrupt(
+            "the generations to redo do not follow on from the cursor",
+            &|history| history.undone.reverse(),
+        );
+        corrupt(
+            "the generations to redo do not follow on from the cursor",
+            &|history| history.undone[0] = last + 5,
+        );
+        corrupt(
+            "a generation does not follow on from its parent",
+            &|history| history.generations[last].number += 1,
+        );
+        corrupt(
+            "a generation has a branch which does not follow on from it",
+            &|history| history.generations[0].branches.push(last + 1),
+        );
+        corrupt("the generations are out of order", &|history| {
+            history.generations.swap(1, 2)
+        });
+        corrupt("a seat belongs to nobody at the table", &|history| {
+            history.generations[last].state.seats[0].player = 1
+        });
+    }
+
+    #[test]
+    fn newer_saves_are_refused() {
+        let mut save = saved(&played(4, 1));
+        save[8..12].copy_from_slice(&(SAVE_VERSION + 1).to_le_bytes());
+
+        let error = Rules::<Shoe>::load(save.as_slice()).err().unwrap();
+        assert!(
+            matches!(error, SaveError::UnsupportedVersion(version) if version == SAVE_VERSION + 1)
+        );
+    }
+
+    /// A version 1 or 2 generation made the given number of seconds after the epoch.
+    fn nested(
+        turn: usize,
+        seconds: u64,
+        children: Vec<NestedGeneration<Shoe>>,
+    ) -> NestedGeneration<Shoe> {
+        NestedGeneration {
+            state: UninsuredState {
+                turn,
+                ..Default::default()
+            },
+            timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+            children,
+        }
+    }
+
+    /// The table rules as saves before version 5 wrote them.
+    fn uninsured_table() -> UninsuredTable {
+        let table = TableRules::vegas_strip();
+        UninsuredTable {
+            dealer_hits_soft_17: table.dealer_hits_soft_17,
+            double_after_split: table.double_after_split,
+            double: table.double,
+            max_splits: table.max_splits,
+            resplit_aces: table.resplit_aces,
+            hit_split_aces: table.hit_split_aces,
+            surrender: table.surrender,
+            blackjack_payout: table.blackjack_payout,
+            decks: table.decks,
+            penetration: table.penetration,
+            hole_card: table.hole_card,
+            min_bet: table.min_bet,
+            max_bet: table.max_bet,
+        }
+    }
+
+    /// A save of the given version holding the game, with the table as versions before 5 wrote
+    /// it.
+    fn save_of<T: serde::Serialize>(version: u32, game: &T) -> Vec<u8> {
+        save_with(version, &uninsured_table(), game)
+    }
+
+    /// A save of the given version holding the table and the game.
+    fn save_with<T: serde::Serialize, G: serde::Serialize>(
+        version: u32,
+        table: &T,
+        game: &G,
+    ) -> Vec<u8> {
+        let game = bincode::serialize(&(table, game)).unwrap();
+        let mut save = b"BLACKJAK".to_vec();
+        save.extend_from_slice(&version.to_le_bytes());
+        save.extend_from_slice(&checksum(&game).to_le_bytes());
+        save.extend_from_slice(&game);
+        save
+    }
+
+    #[test]
+    fn version_one_saves_pick_up_at_the_newest_generation() {
+        let root = nested(
+            0,
+            0,
+            vec![
+                nested(1, 1, vec![nested(2, 2, vec![])]),
+                nested(3, 3, vec![]),
+            ],
+        );
+
+        let loaded: Rules = Rules::load(save_of(1, &root).as_slice()).unwrap();
+        let history = loaded.history();
+        assert_eq!(vec![0, 3], history.path());
+        assert_eq!(3, loaded.current_state().turn);
+        assert_eq!(&[1, 3], history.root().branches());
+        assert_eq!(Some(1), history.get(2).unwrap().parent());
+        assert_eq!(2, history.get(2).unwrap().number());
+    }
+
+    #[test]
+    fn version_two_saves_keep_their_cursor() {
+        let history = NestedHistory {
+            root: nested(
+                0,
+                0,
+                vec![
+                    nested(1, 1, vec![nested(2, 2, vec![nested(3, 3, vec![])])]),
+                    nested(4, 4, vec![]),
+                ],
+            ),
+            cursor: vec![0],
+            undone: vec![0, 0],
+        };
+
+        let mut loaded: Rules = Rules::load(save_of(2, &history).as_slice()).unwrap();
+        assert_eq!(1, loaded.current_state().turn);
+        assert_eq!(3, loaded.history().last_generation_number());
+        loaded.redo().unwrap();
+        loaded.redo().unwrap();
+        assert_eq!(3, loaded.current_state().turn);
+        assert_eq!(vec![0, 1, 2, 3], loaded.history().path());
+
+        let lost = NestedHistory {
+            cursor: vec![0, 5],
+            ..history
+        };
+        assert!(matches!(
+            Rules::<Shoe>::load(save_of(2, &lost).as_slice()),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn version_three_saves_have_no_events() {
+        let generation = |id, parent, number, branches| EventlessGeneration::<Shoe> {
+            id,
+            parent,
+            number,
+            state: UninsuredState {
+                turn: id,
+                ..Default::default()
+            },
+            branches,
+        };
+        let history = EventlessHistory {
+            generations: vec![
+                generation(0, None, 0, vec![1, 2]),
+                generation(1, Some(0), 1, vec![]),
+                generation(2, Some(0), 1, vec![]),
+            ],
+            cursor: 0,
+            undone: vec![2],
+        };
+
+        let mut loaded: Rules = Rules::load(save_of(3, &history).as_slice()).unwrap();
+        assert_eq!(&[1, 2], loaded.history().root().branches());
+        loaded.redo().unwrap();
+        assert_eq!(2, loaded.current_state().turn);
+        assert_eq!(vec![0, 2], loaded.history().path());
+        assert_eq!(0, loaded.history().events().count());
+    }
+
+    /// A player as saves before version 6 wrote them, with a single empty hand.
+    fn seated(bankroll: Chips) -> SeatedPlayer {
+        SeatedPlayer {
+            hands: vec![Default::default()],
+            active: 0,
+            bankroll,
+        }
+    }
+
+    #[test]
+    fn version_four_saves_play_on_without_insurance() {
+        let generation = |id, parent, events| SavedGeneration {
+            id,
+            parent,
+            number: id,
+            state: UninsuredState::<Shoe> {
+                players: vec![seated(0); id],
+                ..Default::default()
+            },
+            events,
+            branches: if id == 0 { vec![1] } else { vec![] },
+        };
+        let history = SavedHistory {
+            generations: vec![
+                generation(0, None, vec![]),
+                generation(1, Some(0), vec![Event::PlayerJoined { seat: 0 }]),
+            ],
+            cursor: 1,
+            undone: vec![],
+        };
+
+        let loaded: Rules = Rules::load(save_of(4, &history).as_slice()).unwrap();
+        assert_eq!(1, loaded.current_state().players.len());
+        assert!(loaded.current_state().insurance.is_empty());
+        assert_eq!(
+            vec![&Event::PlayerJoined { seat: 0 }],
+            loaded.history().events().collect::<Vec<_>>()
+        );
+
+        let table = loaded.table_rules();
+        assert_eq!(TableRules::vegas_strip().decks, table.decks);
+        assert!(!table.insurance && !table.dealer_peeks && !table.original_bets_only);
+    }
+
+    #[test]
+    fn version_five_players_keep_their_seats() {
+        let mut betting = seated(90);
+        betting.hands[0].bet = 10;
+        let state = UnseatedState::<Shoe> {
+            progress: Progress::Insurance,
+            players: vec![seated(100), betting],
+            turn: 1,
+            insurance: vec![Insurance::Declined, Insurance::Declined],
+            ..Default::default()
+        };
+        let history = SavedHistory {
+            generations: vec![SavedGeneration {
+                id: 0,
+                parent: None,
+                number: 0,
+                state,
+                events: vec![Event::BoughtIn {
+                    player: 1,
+                    chips: 100,
+                }],
+                branches: vec![],
+            }],
+            cursor: 0,
+            undone: vec![],
+        };
+
+        let table = TableRules::european();
+        let loaded: Rules = Rules::load(save_with(5, &table, &history).as_slice()).unwrap();
+        let state = loaded.current_state();
+        assert_eq!(&table, loaded.table_rules());
+        assert_eq!(
+            vec![100, 90],
+            state.players.iter().map(|p| p.bankroll).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 1],
+            state.seats.iter().map(|s| s.player).collect::<Vec<_>>()
+        );
+        assert_eq!(10, state.seats[1].total_bet());
+        assert_eq!(Some(1), state.insuring_seat());
+        assert_eq!(
+            vec![&Event::BoughtIn {
+                player: 1,
+                chips: 100
+            }],
+            loaded.history().events().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn events_are_saved_with_the_history() {
+        let rules = played(5, 40);
+        let loaded: Rules = Rules::load(saved(&rules).as_slice()).unwrap();
+
+        assert!(rules.history().events().count() > 40);
+        assert!(rules.history().events().eq(loaded.history().events()));
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+/// Shared holds a part of the game state which is shared between generations until it changes.
+/// Cloning only adds another reference.  Changing it copies it first if anything else still
+/// holds it, so every generation keeps what it saw and only the parts which changed are copied.
+///
+/// It reads and changes like the value it holds.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::Shared;
+///
+/// let before: Shared<Vec<i32>> = vec![1, 2].into();
+/// let mut after = before.clone();
+/// assert!(after.is_shared_with(&before));
+///
+/// after.push(3);
+/// assert!(!after.is_shared_with(&before));
+/// assert_eq!(vec![1, 2], *before);
+/// assert_eq!(3, after.len());
+/// ```
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct Shared<T>(Arc<T>);
+
+impl<T> Shared<T> {
+    /// Whether both hold the very same value, rather than two values which are equal.
+    ///
+    /// * `other`: What to compare with
+    pub fn is_shared_with(&self, other: &Shared<T>) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> From<T> for Shared<T> {
+    fn from(value: T) -> Self {
+        Shared(Arc::new(value))
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Copy the value first if another generation is holding it too.
+impl<T: Clone> DerefMut for Shared<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+/// Shown as the value it holds.
+impl<T: std::fmt::Debug> std::fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Saved as the value it holds, so sharing does not change the saved layout.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Shared<T> {
+    fn serialize<Z: serde::Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Shared<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Shared::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shared;
+
+    #[test]
+    fn changes_are_only_copied_while_shared() {
+        let mut only: Shared<Vec<u8>> = vec![1].into();
+        let kept = only.clone();
+        only.push(2);
+        assert!(!only.is_shared_with(&kept));
+        assert_eq!(vec![1], *kept);
+
+        let unchanged = only.clone();
+        let address = only.as_ptr();
+        drop(unchanged);
+        only[0] = 5;
+        assert_eq!(address, only.as_ptr());
+        assert_eq!(vec![5, 2], *only);
+    }
+
+    #[test]
+    fn equal_values_are_equal_whether_shared_or_not() {
+        let one: Shared<Vec<u8>> = vec![1].into();
+        assert_eq!(one, one.clone());
+        assert_eq!(one, vec![1].into());
+        assert_eq!("[1]", format!("{:?}", one));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{
+    action::Action,
+    game_state::{GameState, Insurance},
+    table_rules::TableRules,
+};
+use cards::prelude::{Card, CardSource, Shoe};
+use player::{Chips, Hand};
+
+/// Decision is everything a strategy gets to look at when it is a seat's turn to act.
+#[derive(Debug, Clone, Copy)]
+pub struct Decision<'a, S = Shoe> {
+    /// The seat which needs to act.
+    pub seat: usize,
+    /// The hand being played.
+    pub hand: &'a Hand,
+    /// The house card every player can see.
+    pub dealer_up_card: &'a Card,
+    /// The actions the seat is allowed to take.  There is always at least Hit and Stand.
+    pub legal_actions: &'a [Action],
+    /// The house rules for the table.
+    pub table: &'a TableRules,
+    /// Everything else about the game.
+    pub state: &'a GameState<S>,
+}
+
+/// A Strategy makes the decisions for the players at the table.  Rules asks the strategy for
+/// every bet and every action when it is driven as an iterator.  A strategy can be written for a
+/// single kind of card source, or for all of them.
+pub trait Strategy<S: CardSource = Shoe> {
+    /// The bet for the given seat in the next game.  A bet of zero sits the seat out.
+    fn bet(&mut self, seat: usize, state: &GameState<S>, table: &TableRules) -> Chips;
+
+    /// The action to take.  This should be one of the decision's legal actions.
+    fn act(&mut self, decision: &Decision<S>) -> Action;
+
+    /// What the given seat does when the house shows an ace.  Insurance only pays when the shoe
+    /// is rich in tens, so unless a strategy says otherwise every offer is declined.
+    fn insure(&mut self, _seat: usize, _state: &GameState<S>, _table: &TableRules) -> Insurance {
+        Insurance::Declined
+    }
+}
+
+/// Mimic the dealer is the simplest strategy there is.  Every seat bets the same amount and hits
+/// until it has 17 or more, just like the house.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ MimicTheDealer, Rules };
+///
+/// let mut rule: Rules = Default::default();
+/// rule.add_player();
+/// rule.buy_in(0, 1000);
+/// rule.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+///
+/// let states: Vec<_> = rule.by_ref().take(10).collect();
+/// assert_eq!(10, states.len());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MimicTheDealer {
+    pub bet: Chips,
+}
+
+impl<S: CardSource> Strategy<S> for MimicTheDealer {
+    fn bet(&mut self, seat: usize, state: &GameState<S>, table: &TableRules) -> Chips {
+        flat_bet(self.bet, seat, state, table)
+    }
+
+    fn act(&mut self, decision: &Decision<S>) -> Action {
+        if decision.hand.total() < 17 {
+            Action::Hit
+        } else {
+            Action::Stand
+        }
+    }
+}
+
+/// Bet the same amount every game, kept within the table limits.  A seat which can not cover the
+/// bet sits out.
+pub(crate) fn flat_bet<S>(
+    bet: Chips,
+    seat: usize,
+    state: &GameState<S>,
+    table: &TableRules,
+) -> Chips {
+    let place = &state.seats[seat];
+    let bankroll = state.players[place.player].bankroll + place.total_bet();
+    let bet = bet.clamp(table.min_bet, table.max_bet);
+    if bet <= bankroll {
+        bet
+    } else {
+        0
+    }
+}
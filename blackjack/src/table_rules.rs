@@ -0,0 +1,366 @@
+use crate::game_state::Outcome;
+use cards::prelude::{seeded_rng, Card, CardSource, Rank, Shoe};
+use player::Chips;
+
+/// Which starting hands are allowed to double down.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DoubleRestriction {
+    Any,
+    NineToEleven,
+    TenToEleven,
+}
+
+impl DoubleRestriction {
+    /// Check if a hand with the given total can be doubled.
+    pub fn allows(&self, total: i32) -> bool {
+        match self {
+            DoubleRestriction::Any => true,
+            DoubleRestriction::NineToEleven => (9..=11).contains(&total),
+            DoubleRestriction::TenToEleven => (10..=11).contains(&total),
+        }
+    }
+}
+
+/// When a player is allowed to give up half of their bet.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SurrenderRule {
+    /// Surrender is not offered.
+    NoSurrender,
+    /// Surrender only after the house has checked for blackjack.  A surrendered hand still loses
+    /// the whole bet to a house blackjack.
+    Late,
+    /// Surrender before the house checks for blackjack.  Half the bet is saved even against a
+    /// house blackjack.
+    Early,
+}
+
+/// How much a player's blackjack pays.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlackjackPayout {
+    ThreeToTwo,
+    SixToFive,
+    EvenMoney,
+}
+
+impl BlackjackPayout {
+    /// The payout as a ratio of winnings to bet.
+    pub fn ratio(&self) -> (Chips, Chips) {
+        match self {
+            BlackjackPayout::ThreeToTwo => (3, 2),
+            BlackjackPayout::SixToFive => (6, 5),
+            BlackjackPayout::EvenMoney => (1, 1),
+        }
+    }
+}
+
+/// BlackjackPayout needs a standard way to be printed out.  That's what Display is for.
+impl std::fmt::Display for BlackjackPayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (win, bet) = self.ratio();
+        write!(f, "{}:{}", win, bet)
+    }
+}
+
+/// TableRules are the house rules for a blackjack table.  Every casino plays a little differently
+/// so Rules will follow whichever table rules it is given.
+///
+/// Example:
+/// ```
+/// use blackjack::prelude::{ Rules, TableRules };
+///
+/// let table = TableRules {
+///     decks: 2,
+///     dealer_hits_soft_17: true,
+///     ..TableRules::vegas_strip()
+/// };
+/// let rule = Rules::new(table);
+///
+/// assert_eq!(2, rule.table_rules().decks);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableRules {
+    /// The house hits a soft 17 (H17) instead of standing on it (S17).
+    pub dealer_hits_soft_17: bool,
+    /// A hand which came from a split can be doubled (DAS).
+    pub double_after_split: bool,
+    /// Which starting totals can be doubled.
+    pub double: DoubleRestriction,
+    /// How many times a player can split in one round.  Three splits makes four hands.
+    pub max_splits: usize,
+    /// Split aces can be split again when they are dealt another ace.
+    pub resplit_aces: bool,
+    /// Split aces can take more than one card.
+    pub hit_split_aces: bool,
+    pub surrender: SurrenderRule,
+    pub blackjack_payout: BlackjackPayout,
+    /// How many decks are in the shoe.
+    pub decks: usize,
+    /// How much of the shoe is dealt before it is reshuffled, between 0 and 1.
+    pub penetration: f32,
+    /// The house takes a hole card with the deal.  Without one (European) the house gets its
+    /// second card after the players are done.
+    pub hole_card: bool,
+    /// The house offers insurance, and even money on a blackjack, when it shows an ace.
+    pub insurance: bool,
+    /// The house checks its hole card for blackjack when it shows an ace or a ten.  A house
+    /// blackjack ends the game before any player acts.  Only a table with a hole card can peek.
+    pub dealer_peeks: bool,
+    /// A house blackjack found once play is over only takes each player's original bet, and the
+    /// chips added by doubling and splitting are handed back (OBO).  Without it a house blackjack
+    /// takes every bet on the table, as it does at European tables.
+    pub original_bets_only: bool,
+    /// The smallest bet the table takes.
+    pub min_bet: Chips,
+    /// The largest bet the table takes.
+    pub max_bet: Chips,
+}
+
+impl TableRules {
+    /// A classic Las Vegas Strip table.  Four decks, the house stands on soft 17 and peeks for
+    /// blackjack, double any two cards, double after split and late surrender.
+    pub fn vegas_strip() -> Self {
+        TableRules {
+            dealer_hits_soft_17: false,
+            double_after_split: true,
+            double: DoubleRestriction::Any,
+            max_splits: 3,
+            resplit_aces: false,
+            hit_split_aces: false,
+            surrender: SurrenderRule::Late,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            decks: 4,
+            penetration: 0.75,
+            hole_card: true,
+            insurance: true,
+            dealer_peeks: true,
+            original_bets_only: false,
+            min_bet: 10,
+            max_bet: 5000,
+        }
+    }
+
+    /// A downtown Las Vegas table.  Two decks and the house hits soft 17.
+    pub fn downtown_vegas() -> Self {
+        TableRules {
+            dealer_hits_soft_17: true,
+            surrender: SurrenderRule::NoSurrender,
+            decks: 2,
+            penetration: 0.65,
+            min_bet: 5,
+            max_bet: 2000,
+            ..TableRules::vegas_strip()
+        }
+    }
+
+    /// An Atlantic City table.  Eight decks, the house stands on soft 17 and late surrender.
+    pub fn atlantic_city() -> Self {
+        TableRules {
+            decks: 8,
+            penetration: 0.8,
+            min_bet: 15,
+            ..TableRules::vegas_strip()
+        }
+    }
+
+    /// A European table.  The house takes no hole card, so a house blackjack takes doubled and
+    /// split bets too.  Doubles are only on 9 through 11 and there is no surrender.
+    pub fn european() -> Self {
+        TableRules {
+            double: DoubleRestriction::NineToEleven,
+            double_after_split: false,
+            max_splits: 1,
+            surrender: SurrenderRule::NoSurrender,
+            decks: 6,
+            hole_card: false,
+            dealer_peeks: false,
+            ..TableRules::vegas_strip()
+        }
+    }
+
+    /// Check if the house offers insurance when it shows this card.
+    pub fn offers_insurance(&self, up_card: &Card) -> bool {
+        self.insurance && up_card.rank() == Rank::Ace
+    }
+
+    /// Check if the house looks under this card for blackjack before anyone plays.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::TableRules;
+    /// use cards::prelude::{ Card, Rank, Suit };
+    ///
+    /// let king = Card::new(Rank::King, Suit::Clubs);
+    ///
+    /// assert!(TableRules::vegas_strip().house_peeks(&king));
+    /// assert!(!TableRules::vegas_strip().house_peeks(&Card::new(Rank::Nine, Suit::Clubs)));
+    /// assert!(!TableRules::european().house_peeks(&king));
+    /// ```
+    pub fn house_peeks(&self, up_card: &Card) -> bool {
+        let value = up_card.rank().blackjack_value();
+        self.hole_card && self.dealer_peeks && (value == 1 || value == 10)
+    }
+
+    /// Check if the table takes a bet of this size.
+    pub fn allows_bet(&self, bet: Chips) -> bool {
+        (self.min_bet..=self.max_bet).contains(&bet)
+    }
+
+    /// The chips handed back to a player for a hand with the given bet and outcome.  This
+    /// includes the bet itself, so a win pays back twice the bet.  Anything the table can not
+    /// pay in whole chips goes to the house.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::{ Outcome, TableRules };
+    ///
+    /// let table = TableRules::vegas_strip();
+    ///
+    /// assert_eq!(25, table.payout(Outcome::Blackjack, 10));
+    /// assert_eq!(20, table.payout(Outcome::Win, 10));
+    /// assert_eq!(10, table.payout(Outcome::Push, 10));
+    /// assert_eq!(5, table.payout(Outcome::Surrender, 10));
+    /// assert_eq!(0, table.payout(Outcome::Lose, 10));
+    /// assert_eq!(20, table.payout(Outcome::EvenMoney, 10));
+    /// ```
+    pub fn payout(&self, outcome: Outcome, bet: Chips) -> Chips {
+        match outcome {
+            Outcome::Blackjack => {
+                let (win, per) = self.blackjack_payout.ratio();
+                bet + bet * win / per
+            }
+            Outcome::Win | Outcome::EvenMoney => bet * 2,
+            Outcome::Push => bet,
+            Outcome::Surrender => bet / 2,
+            Outcome::Lose => 0,
+        }
+    }
+
+    /// The number of cards in a full shoe.
+    pub fn shoe_size(&self) -> usize {
+        self.decks * 52
+    }
+
+    /// A full shoe for the table, shuffled by the seed with the cut card placed at the table's
+    /// penetration.  The same seed and shuffle number always give the same shoe, card for card.
+    ///
+    /// Example:
+    /// ```
+    /// use blackjack::prelude::TableRules;
+    /// use cards::prelude::HasCards;
+    ///
+    /// let table = TableRules::vegas_strip();
+    ///
+    /// assert_eq!(table.shuffled_shoe(7, 0), table.shuffled_shoe(7, 0));
+    /// assert_ne!(table.shuffled_shoe(7, 0), table.shuffled_shoe(7, 1));
+    /// assert_eq!(208, table.shuffled_shoe(7, 1).cards_left());
+    /// ```
+    pub fn shuffled_shoe(&self, seed: u64, shuffles: u64) -> Shoe {
+        let mut shoe = Shoe::new(self.decks);
+        shoe.place_cut_card(self.reshuffle_at());
+        shoe.reshuffle_with(&mut seeded_rng(seed, shuffles));
+        shoe
+    }
+
+    /// Where the cut card goes.  Once the shoe has fewer cards than this left it is reshuffled
+    /// before the next game.
+    pub fn reshuffle_at(&self) -> usize {
+        let dealt = (self.shoe_size() as f32 * self.penetration.clamp(0.0, 1.0)) as usize;
+        self.shoe_size() - dealt
+    }
+}
+
+/// Without any other direction a table plays by Las Vegas Strip rules.
+impl Default for TableRules {
+    fn default() -> Self {
+        TableRules::vegas_strip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlackjackPayout, DoubleRestriction, TableRules};
+    use crate::game_state::Outcome;
+    use cards::prelude::{Card, Rank, Suit};
+
+    #[test]
+    fn double_restrictions() {
+        assert!(DoubleRestriction::Any.allows(17));
+        assert!(DoubleRestriction::NineToEleven.allows(9));
+        assert!(!DoubleRestriction::NineToEleven.allows(12));
+        assert!(!DoubleRestriction::TenToEleven.allows(9));
+        assert!(DoubleRestriction::TenToEleven.allows(11));
+    }
+
+    #[test]
+    fn payouts() {
+        assert_eq!("3:2", BlackjackPayout::ThreeToTwo.to_string());
+        assert_eq!("6:5", BlackjackPayout::SixToFive.to_string());
+        assert_eq!("1:1", BlackjackPayout::EvenMoney.to_string());
+    }
+
+    #[test]
+    fn odd_payouts_round_down() {
+        let six_to_five = TableRules {
+            blackjack_payout: BlackjackPayout::SixToFive,
+            ..Default::default()
+        };
+        assert_eq!(22, six_to_five.payout(Outcome::Blackjack, 10));
+        assert_eq!(15, six_to_five.payout(Outcome::Blackjack, 7));
+        assert_eq!(37, TableRules::default().payout(Outcome::Blackjack, 15));
+        assert_eq!(7, TableRules::default().payout(Outcome::Surrender, 15));
+    }
+
+    #[test]
+    fn bet_limits() {
+        let table = TableRules::vegas_strip();
+        assert!(!table.allows_bet(5));
+        assert!(table.allows_bet(10));
+        assert!(table.allows_bet(5000));
+        assert!(!table.allows_bet(5001));
+    }
+
+    #[test]
+    fn penetration() {
+        let table = TableRules::vegas_strip();
+        assert_eq!(208, table.shoe_size());
+        assert_eq!(52, table.reshuffle_at());
+
+        let dealt_out = TableRules {
+            penetration: 1.5,
+            ..TableRules::vegas_strip()
+        };
+        assert_eq!(0, dealt_out.reshuffle_at());
+    }
+
+    #[test]
+    fn presets() {
+        assert_eq!(TableRules::vegas_strip(), Default::default());
+        assert!(TableRules::downtown_vegas().dealer_hits_soft_17);
+        assert_eq!(8, TableRules::atlantic_city().decks);
+        assert!(!TableRules::european().hole_card);
+        assert!(TableRules::european().insurance);
+        assert!(!TableRules::european().original_bets_only);
+    }
+
+    #[test]
+    fn insurance_is_offered_on_an_ace() {
+        let ace = Card::new(Rank::Ace, Suit::Hearts);
+        let ten = Card::new(Rank::Ten, Suit::Hearts);
+        let table = TableRules::vegas_strip();
+        assert!(table.offers_insurance(&ace));
+        assert!(!table.offers_insurance(&ten));
+        assert!(table.house_peeks(&ace) && table.house_peeks(&ten));
+
+        let no_insurance = TableRules {
+            insurance: false,
+            ..TableRules::vegas_strip()
+        };
+        assert!(!no_insurance.offers_insurance(&ace));
+        assert!(TableRules::european().offers_insurance(&ace));
+        assert!(!TableRules::european().house_peeks(&ace));
+    }
+}
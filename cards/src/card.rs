@@ -1,6 +1,8 @@
-use crate::error::CardError;
+use crate::{error::CardError, rank::Rank};
+use std::{cmp::Ordering, str::FromStr};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Suit {
     Clubs,
     Hearts,
@@ -8,88 +10,333 @@ pub enum Suit {
     Diamonds,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Suit {
+    /// Every suit in a standard deck.
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    /// The single letter short notation for the suit.
+    pub fn letter(&self) -> char {
+        match self {
+            Suit::Clubs => 'c',
+            Suit::Diamonds => 'd',
+            Suit::Hearts => 'h',
+            Suit::Spades => 's',
+        }
+    }
+
+    /// The name of the suit.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Suit::Clubs => "Clubs",
+            Suit::Diamonds => "Diamonds",
+            Suit::Hearts => "Hearts",
+            Suit::Spades => "Spades",
+        }
+    }
+
+    /// The Unicode symbol for the suit.
+    pub fn symbol(&self) -> char {
+        match self {
+            Suit::Clubs => '♣',
+            Suit::Diamonds => '♦',
+            Suit::Hearts => '♥',
+            Suit::Spades => '♠',
+        }
+    }
+
+    /// The outlined Unicode symbol for the suit.
+    fn outlined_symbol(&self) -> char {
+        match self {
+            Suit::Clubs => '♧',
+            Suit::Diamonds => '♢',
+            Suit::Hearts => '♡',
+            Suit::Spades => '♤',
+        }
+    }
+
+    /// Where the suit comes in `Suit::ALL`.
+    fn position(&self) -> usize {
+        match self {
+            Suit::Clubs => 0,
+            Suit::Diamonds => 1,
+            Suit::Hearts => 2,
+            Suit::Spades => 3,
+        }
+    }
+
+    /// Where the suit starts in the Unicode playing cards block.
+    fn code_point_base(&self) -> u32 {
+        match self {
+            Suit::Spades => 0x1F0A0,
+            Suit::Hearts => 0x1F0B0,
+            Suit::Diamonds => 0x1F0C0,
+            Suit::Clubs => 0x1F0D0,
+        }
+    }
+}
+
+/// Suits sort in the order `Suit::ALL` lists them.  The variants are declared in an older order
+/// which saved games depend on, so the order is written out rather than derived.
+impl Ord for Suit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.position().cmp(&other.position())
+    }
+}
+
+impl PartialOrd for Suit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Suit needs a standard way to be printed out.  The short notation is the letter, c, d, h or s,
+/// and the alternate form, `{:#}`, is the name of the suit.
+impl std::fmt::Display for Suit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.name())
+        } else {
+            write!(f, "{}", self.letter())
+        }
+    }
+}
+
+/// Read a suit from its letter, its symbol or its name.  Case does not matter.
+///
+/// Example:
+/// ```
+/// use cards::prelude::Suit;
+///
+/// assert_eq!(Ok(Suit::Spades), "s".parse());
+/// assert_eq!(Ok(Suit::Hearts), "♥".parse());
+/// assert_eq!(Ok(Suit::Diamonds), "diamonds".parse());
+/// assert!("x".parse::<Suit>().is_err());
+/// ```
+impl FromStr for Suit {
+    type Err = CardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let mut chars = text.chars();
+        let single = match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        };
+        Suit::ALL
+            .iter()
+            .find(|suit| {
+                text.eq_ignore_ascii_case(suit.name())
+                    || text.eq_ignore_ascii_case(suit.name().trim_end_matches('s'))
+                    || single.is_some_and(|c| {
+                        c.to_ascii_lowercase() == suit.letter()
+                            || c == suit.symbol()
+                            || c == suit.outlined_symbol()
+                    })
+            })
+            .copied()
+            .ok_or_else(|| CardError::InvalidSuit(s.to_string()))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Card {
-    value: i32,
+    rank: Rank,
     suit: Suit,
 }
 
 impl Card {
     /// Creating a new card
     ///
-    /// * `value`: should be the value between 1 and 13
+    /// * `rank`: Ace through King
     /// * `suit`: should be a valid enum for a card
-    pub fn new(value: i32, suit: Suit) -> Result<Card, CardError> {
-        if !(1..=13).contains(&value) {
-            return Err(CardError::ValueOutOfRange(value));
-        }
-        Ok(Card { value, suit })
-    }
-
-    /// face will return the string face value of the card.  This is a standard deck which will
-    /// have an Ace, Jack, Queen, and King
-    pub fn rank(&self) -> String {
-        match self.value {
-            1 => "Ace".to_owned(),
-            11 => "Jack".to_owned(),
-            12 => "Queen".to_owned(),
-            13 => "King".to_owned(),
-            _ => self.value.to_string(),
-        }
+    pub fn new(rank: Rank, suit: Suit) -> Card {
+        Card { rank, suit }
     }
 
-    /// face will return the string face value of the card.  This is a standard deck which will
-    /// have an Ace, Jack, Queen, and King
-    pub fn value(&self) -> i32 {
-        match self.value {
-            1 => 1,
-            11 => 10,
-            12 => 10,
-            13 => 10,
-            _ => self.value,
-        }
+    /// Every card in a standard deck, suit by suit.
+    pub fn all() -> impl Iterator<Item = Card> {
+        Suit::ALL
+            .iter()
+            .flat_map(|&suit| Rank::ALL.iter().map(move |&rank| Card::new(rank, suit)))
+    }
+
+    /// The rank of the card, Ace through King.  Each game decides what a rank is worth.
+    pub fn rank(&self) -> Rank {
+        self.rank
     }
 
     /// show the suit for the card
     pub fn suit(&self) -> Suit {
         self.suit
     }
+
+    /// The card's character in the Unicode playing cards block, such as 🂡 for the Ace of
+    /// Spades.
+    pub fn symbol(&self) -> char {
+        // The block has a Knight between the Jack and the Queen which a standard deck skips.
+        let offset = match self.rank {
+            Rank::Queen | Rank::King => self.rank.number() as u32 + 1,
+            _ => self.rank.number() as u32,
+        };
+        char::from_u32(self.suit.code_point_base() + offset)
+            .expect("Every card is in the playing cards block")
+    }
+}
+
+/// Card needs a standard way to be printed out.  The short notation is the rank then the suit,
+/// such as As or Td, and the alternate form, `{:#}`, is the name, such as Ace of Spades.
+impl std::fmt::Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#} of {:#}", self.rank, self.suit)
+        } else {
+            write!(f, "{}{}", self.rank, self.suit)
+        }
+    }
+}
+
+/// Read a card from short notation (As, Td, 10♥), its name (Ace of Spades) or its character in
+/// the Unicode playing cards block.
+///
+/// Example:
+/// ```
+/// use cards::prelude::{ Card, Rank, Suit };
+///
+/// let ace: Card = "As".parse().unwrap();
+/// assert_eq!(Card::new(Rank::Ace, Suit::Spades), ace);
+/// assert_eq!(Ok(ace), "Ace of Spades".parse());
+/// assert_eq!(Ok(ace), "🂡".parse());
+/// assert_eq!(Ok(Card::new(Rank::Ten, Suit::Hearts)), "10♥".parse());
+/// assert_eq!("Td", Card::new(Rank::Ten, Suit::Diamonds).to_string());
+/// ```
+impl FromStr for Card {
+    type Err = CardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CardError::InvalidCard(s.to_string());
+        let text = s.trim();
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if let [rank, of, suit] = words[..] {
+            if !of.eq_ignore_ascii_case("of") {
+                return Err(invalid());
+            }
+            return Ok(Card::new(rank.parse()?, suit.parse()?));
+        }
+
+        let mut chars = text.chars();
+        let last = chars.next_back().ok_or_else(invalid)?;
+        let rank = chars.as_str();
+        if rank.is_empty() {
+            return Card::all()
+                .find(|card| card.symbol() == last)
+                .ok_or_else(invalid);
+        }
+        Ok(Card::new(rank.parse()?, last.to_string().parse()?))
+    }
+}
+
+/// Read a list of cards such as "As Kd 5h".  Cards written out by name are separated by commas
+/// instead, "Ace of Spades, King of Diamonds".
+///
+/// Example:
+/// ```
+/// use cards::prelude::{ parse_cards, Card, Rank, Suit };
+///
+/// let cards = parse_cards("As Kd").unwrap();
+/// assert_eq!(vec![Card::new(Rank::Ace, Suit::Spades), Card::new(Rank::King, Suit::Diamonds)], cards);
+/// assert_eq!(Ok(cards), parse_cards("Ace of Spades, King of Diamonds"));
+/// ```
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, CardError> {
+    if s.contains(',') {
+        s.split(',').map(str::parse).collect()
+    } else {
+        s.split_whitespace().map(str::parse).collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Card, Suit};
+    use crate::{error::CardError, rank::Rank};
+    use std::collections::HashSet;
+
+    #[test]
+    fn card_rank_and_suit() {
+        let card = Card::new(Rank::Ace, Suit::Clubs);
+        assert_eq!(card.rank(), Rank::Ace);
+        assert_eq!(card.suit(), Suit::Clubs);
+        assert_eq!("Ace", format!("{:#}", card.rank()));
+    }
+
+    #[test]
+    fn every_card_is_different() {
+        let cards: HashSet<Card> = Card::all().collect();
+        assert_eq!(52, cards.len());
+        let symbols: HashSet<char> = Card::all().map(|card| card.symbol()).collect();
+        assert_eq!(52, symbols.len());
+    }
+
+    #[test]
+    fn suits_sort_the_way_they_are_listed() {
+        let mut suits = vec![Suit::Spades, Suit::Diamonds, Suit::Clubs, Suit::Hearts];
+        suits.sort();
+        assert_eq!(Suit::ALL.to_vec(), suits);
+
+        let mut aces: Vec<Card> = Card::all()
+            .filter(|card| card.rank() == Rank::Ace)
+            .collect();
+        aces.reverse();
+        aces.sort();
+        let suits: Vec<Suit> = aces.iter().map(|card| card.suit()).collect();
+        assert_eq!(Suit::ALL.to_vec(), suits);
+    }
 
     #[test]
-    fn card_value() {
-        let card = Card::new(1, Suit::Clubs).unwrap();
-        assert_eq!(card.value, 1);
-        assert_eq!(card.suit, Suit::Clubs);
+    fn every_card_reads_back() {
+        for card in Card::all() {
+            assert_eq!(Ok(card), card.to_string().parse());
+            assert_eq!(Ok(card), format!("{:#}", card).parse());
+            assert_eq!(Ok(card), card.symbol().to_string().parse());
+            let with_symbol = format!("{}{}", card.rank(), card.suit().symbol());
+            assert_eq!(Ok(card), with_symbol.parse());
+        }
     }
 
     #[test]
-    fn card_out_of_range() {
-        let card = Card::new(1, Suit::Clubs);
-        assert!(card.is_ok());
-        let card = Card::new(0, Suit::Clubs);
-        assert!(card.is_err());
-        let card = Card::new(13, Suit::Clubs);
-        assert!(card.is_ok());
-        let card = Card::new(14, Suit::Clubs);
-        assert!(card.is_err());
+    fn notation() {
+        let card = |s: &str| s.parse::<Card>().unwrap();
+        assert_eq!(Card::new(Rank::Ten, Suit::Diamonds), card("Td"));
+        assert_eq!(Card::new(Rank::Nine, Suit::Clubs), card("9c"));
+        assert_eq!(Card::new(Rank::Ten, Suit::Hearts), card("10♥"));
+        assert_eq!(Card::new(Rank::Queen, Suit::Spades), card(" qS "));
+        assert_eq!(Card::new(Rank::King, Suit::Hearts), card("🂾"));
+        assert_eq!(Card::new(Rank::Two, Suit::Clubs), card("two of clubs"));
+        assert_eq!("Ace of Spades", format!("{:#}", card("As")));
+        assert_eq!('🂡', card("As").symbol());
+        assert_eq!('🃝', card("Qc").symbol());
     }
 
     #[test]
-    fn card_rank() {
-        let card = Card::new(1, Suit::Clubs).unwrap();
-        assert_eq!(card.rank(), "Ace");
-        let card = Card::new(5, Suit::Clubs).unwrap();
-        assert_eq!(card.rank(), "5");
-        let card = Card::new(11, Suit::Clubs).unwrap();
-        assert_eq!(card.rank(), "Jack");
-        let card = Card::new(12, Suit::Clubs).unwrap();
-        assert_eq!(card.rank(), "Queen");
-        let card = Card::new(13, Suit::Clubs).unwrap();
-        assert_eq!(card.rank(), "King");
+    fn bad_notation() {
+        let parse = |s: &str| s.parse::<Card>();
+        assert_eq!(Err(CardError::InvalidCard("".to_string())), parse(""));
+        assert_eq!(Err(CardError::InvalidCard("🂬".to_string())), parse("🂬"));
+        assert_eq!(Err(CardError::InvalidRank("X".to_string())), parse("Xs"));
+        assert_eq!(Err(CardError::InvalidSuit("x".to_string())), parse("Ax"));
+        assert!(parse("Ace in Spades").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cards_round_trip() {
+        let cards: Vec<Card> = Card::all().collect();
+
+        let json = serde_json::to_string(&cards).unwrap();
+        assert_eq!(cards, serde_json::from_str::<Vec<Card>>(&json).unwrap());
+
+        let bytes = bincode::serialize(&cards).unwrap();
+        assert_eq!(cards, bincode::deserialize::<Vec<Card>>(&bytes).unwrap());
     }
 }
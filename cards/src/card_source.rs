@@ -0,0 +1,16 @@
+use crate::{card::Card, has_cards::HasCards};
+use rand::Rng;
+
+/// A CardSource is anything a table can deal from.  Besides dealing it takes the played cards back
+/// and says when it needs to be reshuffled, so a game can run over a hand shuffled shoe, a
+/// shuffling machine or a stacked set of cards in a test without knowing which one it has.
+pub trait CardSource: HasCards + Clone + std::fmt::Debug {
+    /// Take back cards which have been played.
+    fn discard(&mut self, cards: Vec<Card>);
+
+    /// The source should be reshuffled before the next round.
+    fn reshuffle_pending(&self) -> bool;
+
+    /// Gather every card back and shuffle them with the given random number generator.
+    fn reshuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R);
+}
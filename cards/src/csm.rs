@@ -0,0 +1,207 @@
+use crate::{
+    card::Card,
+    card_source::CardSource,
+    deck::Deck,
+    has_cards::HasCards,
+    seed::{seeded_rng, SeededRng},
+};
+use rand::{seq::SliceRandom, thread_rng, Rng, SeedableRng};
+
+/// A ContinuousShuffler (CSM) is a shuffling machine which takes the discards back after every
+/// round.  Played cards drop into the reservoir and the machine keeps a small buffer of shuffled
+/// cards ready to deal, topping it up with random cards from the reservoir as it goes.  Since no
+/// card is ever more than a buffer away from going back in, the cards which have been seen say
+/// almost nothing about the cards to come.
+///
+/// Example:
+/// ```
+/// use cards::prelude::{ CardSource, ContinuousShuffler, HasCards };
+///
+/// let mut csm = ContinuousShuffler::with_seed(6, 10, 42);
+/// let played = vec![csm.deal().unwrap(), csm.deal().unwrap()];
+/// assert_eq!(310, csm.cards_left());
+///
+/// csm.discard(played);
+/// assert_eq!(312, csm.cards_left());
+/// assert!(!csm.reshuffle_pending());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContinuousShuffler {
+    /// Shuffled cards ready to deal, dealt from the end.
+    buffer: Vec<Card>,
+    /// Cards in the machine waiting to be shuffled into the buffer.
+    reservoir: Vec<Card>,
+    buffer_size: usize,
+    rng: SeededRng,
+}
+
+impl ContinuousShuffler {
+    /// A machine loaded with the given number of decks.
+    ///
+    /// * `number_of_decks`: How many standard decks to load
+    /// * `buffer_size`: How many shuffled cards the machine keeps ready to deal
+    pub fn new(number_of_decks: usize, buffer_size: usize) -> Self {
+        Self::with_seed(number_of_decks, buffer_size, thread_rng().gen())
+    }
+
+    /// A machine which always shuffles the same way for the same seed.
+    pub fn with_seed(number_of_decks: usize, buffer_size: usize, seed: u64) -> Self {
+        let mut reservoir = vec![];
+        for _ in 0..number_of_decks {
+            reservoir.append(&mut Deck::new().cards);
+        }
+
+        let mut csm = ContinuousShuffler {
+            buffer: vec![],
+            reservoir,
+            buffer_size: buffer_size.max(1),
+            rng: seeded_rng(seed, 0),
+        };
+        csm.fill_buffer();
+        csm
+    }
+
+    /// The number of shuffled cards the machine keeps ready to deal.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Top the buffer up with random cards from the reservoir.
+    fn fill_buffer(&mut self) {
+        while self.buffer.len() < self.buffer_size && !self.reservoir.is_empty() {
+            let index = self.rng.gen_range(0..self.reservoir.len());
+            let card = self.reservoir.swap_remove(index);
+            self.buffer.insert(0, card);
+        }
+    }
+}
+
+impl CardSource for ContinuousShuffler {
+    /// Put played cards back in the machine.
+    fn discard(&mut self, cards: Vec<Card>) {
+        self.reservoir.extend(cards);
+        self.fill_buffer();
+    }
+
+    /// A machine never needs to stop for a reshuffle.
+    fn reshuffle_pending(&self) -> bool {
+        false
+    }
+
+    /// Empty everything back into the machine and start again from the given random number
+    /// generator.
+    fn reshuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.shuffle_with(rng);
+    }
+}
+
+impl HasCards for ContinuousShuffler {
+    fn deal(&mut self) -> Option<Card> {
+        let card = self.buffer.pop();
+        self.fill_buffer();
+        card
+    }
+
+    fn cards_left(&self) -> usize {
+        self.buffer.len() + self.reservoir.len()
+    }
+
+    /// Empty the buffer back into the machine and start again from the given random number
+    /// generator.
+    fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.reservoir.append(&mut self.buffer);
+        self.reservoir.sort_unstable();
+        self.reservoir.shuffle(rng);
+        self.rng = SeededRng::seed_from_u64(rng.gen());
+        self.fill_buffer();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContinuousShuffler;
+    use crate::{card::Card, card_source::CardSource, has_cards::HasCards};
+
+    #[test]
+    fn dealing_everything() {
+        let mut csm = ContinuousShuffler::with_seed(1, 5, 1);
+        let mut dealt = vec![];
+        while let Some(card) = csm.deal() {
+            dealt.push(card);
+        }
+        assert_eq!(52, dealt.len());
+        assert_eq!(0, csm.cards_left());
+
+        dealt.sort_unstable();
+        dealt.dedup();
+        assert_eq!(52, dealt.len());
+    }
+
+    #[test]
+    fn discards_go_straight_back() {
+        let mut csm = ContinuousShuffler::with_seed(2, 8, 2);
+        for _ in 0..1000 {
+            let round: Vec<Card> = (0..6).filter_map(|_| csm.deal()).collect();
+            assert_eq!(98, csm.cards_left());
+            csm.discard(round);
+            assert_eq!(104, csm.cards_left());
+        }
+    }
+
+    #[test]
+    fn discards_can_come_out_again_soon() {
+        let mut csm = ContinuousShuffler::with_seed(1, 4, 3);
+        let first = csm.deal().unwrap();
+        csm.discard(vec![first]);
+
+        let mut seen_again = false;
+        for _ in 0..2000 {
+            let card = csm.deal().unwrap();
+            seen_again |= card == first;
+            csm.discard(vec![card]);
+            if seen_again {
+                break;
+            }
+        }
+        assert!(seen_again);
+    }
+
+    #[test]
+    fn seeded_machines_repeat() {
+        let mut first = ContinuousShuffler::with_seed(4, 10, 9);
+        let mut second = ContinuousShuffler::with_seed(4, 10, 9);
+        for _ in 0..100 {
+            let card = first.deal();
+            assert_eq!(card, second.deal());
+            first.discard(card.into_iter().collect());
+            second.discard(card.into_iter().collect());
+        }
+        assert_eq!(first, second);
+        assert_eq!(10, first.buffer_size());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn the_machine_keeps_shuffling_the_same_way_after_a_round_trip() {
+        let mut csm = ContinuousShuffler::with_seed(2, 10, 4);
+        let card = csm.deal();
+        csm.discard(card.into_iter().collect());
+
+        let json = serde_json::to_string(&csm).unwrap();
+        let mut from_json: ContinuousShuffler = serde_json::from_str(&json).unwrap();
+        let bytes = bincode::serialize(&csm).unwrap();
+        let mut from_bytes: ContinuousShuffler = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(csm, from_json);
+        assert_eq!(csm, from_bytes);
+
+        for _ in 0..200 {
+            let card = csm.deal();
+            assert_eq!(card, from_json.deal());
+            assert_eq!(card, from_bytes.deal());
+            csm.discard(card.into_iter().collect());
+            from_json.discard(card.into_iter().collect());
+            from_bytes.discard(card.into_iter().collect());
+        }
+    }
+}
@@ -1,27 +1,34 @@
-use crate::{
-    card::{Card, Suit},
-    error::CardError,
-    has_cards::HasCards,
-};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use crate::{card::Card, has_cards::HasCards};
+use rand::{seq::SliceRandom, Rng};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Deck {
     pub(crate) cards: Vec<Card>,
 }
 
 impl Deck {
     /// NOTE: A deck is a standard deck without jokers.   It has four suits and Ace through King.
-    pub fn new() -> Result<Deck, CardError> {
-        let mut cards = vec![];
-
-        cards.append(&mut create_cards(Suit::Clubs)?);
-        cards.append(&mut create_cards(Suit::Diamonds)?);
-        cards.append(&mut create_cards(Suit::Hearts)?);
-        cards.append(&mut create_cards(Suit::Spades)?);
+    pub fn new() -> Deck {
+        Deck {
+            cards: Card::all().collect(),
+        }
+    }
 
-        Ok(Deck { cards })
+    /// A deck which deals exactly the given cards, in order.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ parse_cards, Deck, HasCards };
+    ///
+    /// let mut deck = Deck::from_cards(parse_cards("Qh 2c").unwrap());
+    /// assert_eq!("Qh", deck.deal().unwrap().to_string());
+    /// assert_eq!("2c", deck.deal().unwrap().to_string());
+    /// assert!(deck.deal().is_none());
+    /// ```
+    pub fn from_cards(mut cards: Vec<Card>) -> Deck {
+        cards.reverse();
+        Deck { cards }
     }
 }
 
@@ -35,25 +42,17 @@ impl HasCards for Deck {
     }
 
     /// Shuffle the cards
-    fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng);
+    fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
     }
 }
 
 impl Default for Deck {
     fn default() -> Self {
-        Deck::new().unwrap()
+        Deck::new()
     }
 }
 
-fn create_cards(suit: Suit) -> Result<Vec<Card>, CardError> {
-    (1..=13)
-        .into_iter()
-        .map(|value| Card::new(value, suit))
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
     use super::Deck;
@@ -61,19 +60,25 @@ mod tests {
 
     #[test]
     fn check_deck() {
-        let maybe = Deck::new();
-        assert!(maybe.is_ok());
-        let deck = maybe.unwrap();
+        let deck = Deck::new();
         assert_eq!(52, deck.cards.len());
     }
 
     #[test]
     fn shuffle_deck() {
-        let maybe = Deck::new();
-        assert!(maybe.is_ok());
-        let mut deck = maybe.unwrap();
+        let mut deck = Deck::new();
         deck.shuffle();
 
         assert_eq!(52, deck.cards.len());
     }
+
+    #[test]
+    fn seeded_shuffle_deck() {
+        let mut first = Deck::new();
+        let mut second = Deck::new();
+        first.shuffle_seeded(11);
+        second.shuffle_seeded(11);
+
+        assert_eq!(first, second);
+    }
 }
@@ -1,7 +1,15 @@
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq, Eq)]
 pub enum CardError {
     #[error("The value of {0} is out of range")]
     ValueOutOfRange(i32),
+    #[error("{0:?} is not a rank")]
+    InvalidRank(String),
+    #[error("{0:?} is not a suit")]
+    InvalidSuit(String),
+    #[error("{0:?} is not a card")]
+    InvalidCard(String),
+    #[error("There are not enough of the {0} to stack")]
+    NotEnoughCards(String),
 }
@@ -1,9 +1,32 @@
-use crate::card::Card;
+use crate::{card::Card, seed::seeded_rng};
+use rand::{thread_rng, Rng};
 
 pub trait HasCards {
     fn deal(&mut self) -> Option<Card>;
     fn cards_left(&self) -> usize;
 
+    /// Shuffle the cards with the given random number generator.
+    fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R);
+
     /// Shuffle the shoe
-    fn shuffle(&mut self);
+    fn shuffle(&mut self) {
+        self.shuffle_with(&mut thread_rng());
+    }
+
+    /// Shuffle the cards the same way every time for the same seed.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ HasCards, Shoe };
+    ///
+    /// let mut first = Shoe::new(2);
+    /// let mut second = Shoe::new(2);
+    /// first.shuffle_seeded(42);
+    /// second.shuffle_seeded(42);
+    ///
+    /// assert_eq!(first, second);
+    /// ```
+    fn shuffle_seeded(&mut self, seed: u64) {
+        self.shuffle_with(&mut seeded_rng(seed, 0));
+    }
 }
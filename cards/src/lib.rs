@@ -1,6 +1,10 @@
 mod card;
+mod card_source;
+mod csm;
 mod deck;
 mod error;
 mod has_cards;
 pub mod prelude;
+mod rank;
+mod seed;
 mod shoe;
@@ -1,4 +1,12 @@
 pub type Card = crate::card::Card;
+pub use crate::card::parse_cards;
 pub type Suit = crate::card::Suit;
+pub type Rank = crate::rank::Rank;
+pub type CardError = crate::error::CardError;
 pub type Deck = crate::deck::Deck;
 pub type Shoe = crate::shoe::Shoe;
+pub type ContinuousShuffler = crate::csm::ContinuousShuffler;
+pub use crate::card_source::CardSource;
+pub use crate::has_cards::HasCards;
+pub type SeededRng = crate::seed::SeededRng;
+pub use crate::seed::seeded_rng;
@@ -0,0 +1,245 @@
+use crate::error::CardError;
+
+/// The rank of a card, from Ace through King.  Ranks are ordered with the ace low, games which
+/// count the ace high can use the poker value.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rank {
+    Ace = 1,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+}
+
+impl Rank {
+    /// Every rank from Ace through King.
+    pub const ALL: [Rank; 13] = [
+        Rank::Ace,
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+    ];
+
+    /// The number of the rank, from 1 for an Ace through 13 for a King.
+    pub fn number(&self) -> i32 {
+        *self as i32
+    }
+
+    /// The single character short notation for the rank.
+    pub fn letter(&self) -> char {
+        match self {
+            Rank::Ace => 'A',
+            Rank::Ten => 'T',
+            Rank::Jack => 'J',
+            Rank::Queen => 'Q',
+            Rank::King => 'K',
+            _ => char::from_digit(self.number() as u32, 10).expect("Two through Nine are digits"),
+        }
+    }
+
+    /// The name of the rank.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rank::Ace => "Ace",
+            Rank::Two => "Two",
+            Rank::Three => "Three",
+            Rank::Four => "Four",
+            Rank::Five => "Five",
+            Rank::Six => "Six",
+            Rank::Seven => "Seven",
+            Rank::Eight => "Eight",
+            Rank::Nine => "Nine",
+            Rank::Ten => "Ten",
+            Rank::Jack => "Jack",
+            Rank::Queen => "Queen",
+            Rank::King => "King",
+        }
+    }
+
+    /// Is this a Jack, Queen or King.
+    pub fn is_face(&self) -> bool {
+        matches!(self, Rank::Jack | Rank::Queen | Rank::King)
+    }
+
+    /// The value in blackjack.  An ace is one, it is up to the hand to count it as eleven, and
+    /// every face card is ten.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::Rank;
+    ///
+    /// assert_eq!(1, Rank::Ace.blackjack_value());
+    /// assert_eq!(7, Rank::Seven.blackjack_value());
+    /// assert_eq!(10, Rank::Queen.blackjack_value());
+    /// ```
+    pub fn blackjack_value(&self) -> i32 {
+        self.number().min(10)
+    }
+
+    /// The value in baccarat.  Tens and face cards are worth nothing.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::Rank;
+    ///
+    /// assert_eq!(1, Rank::Ace.baccarat_value());
+    /// assert_eq!(0, Rank::Ten.baccarat_value());
+    /// assert_eq!(0, Rank::King.baccarat_value());
+    /// ```
+    pub fn baccarat_value(&self) -> i32 {
+        self.number() % 10 * i32::from(!self.is_face())
+    }
+
+    /// The value in poker, where the ace is high.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::Rank;
+    ///
+    /// assert_eq!(14, Rank::Ace.poker_value());
+    /// assert_eq!(13, Rank::King.poker_value());
+    /// assert_eq!(2, Rank::Two.poker_value());
+    /// ```
+    pub fn poker_value(&self) -> i32 {
+        match self {
+            Rank::Ace => 14,
+            _ => self.number(),
+        }
+    }
+}
+
+/// A rank can be made from its number, 1 for an Ace through 13 for a King.
+impl TryFrom<i32> for Rank {
+    type Error = CardError;
+
+    fn try_from(number: i32) -> Result<Self, Self::Error> {
+        Rank::ALL
+            .iter()
+            .find(|rank| rank.number() == number)
+            .copied()
+            .ok_or(CardError::ValueOutOfRange(number))
+    }
+}
+
+/// Rank needs a standard way to be printed out.  The short notation is a single character, A, 2
+/// through 9, T, J, Q and K.  The alternate form, `{:#}`, is the name of the rank.
+impl std::fmt::Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.name())
+        } else {
+            write!(f, "{}", self.letter())
+        }
+    }
+}
+
+/// Read a rank from its short notation, its name or, for Two through Ten, its number.  Ten can be
+/// written as T or 10 and case does not matter.
+///
+/// Example:
+/// ```
+/// use cards::prelude::Rank;
+///
+/// assert_eq!(Ok(Rank::Ace), "A".parse());
+/// assert_eq!(Ok(Rank::Ten), "10".parse());
+/// assert_eq!(Ok(Rank::Ten), "t".parse());
+/// assert_eq!(Ok(Rank::Queen), "Queen".parse());
+/// assert!("X".parse::<Rank>().is_err());
+/// ```
+impl std::str::FromStr for Rank {
+    type Err = CardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        Rank::ALL
+            .iter()
+            .find(|rank| {
+                text.eq_ignore_ascii_case(rank.name())
+                    || text.eq_ignore_ascii_case(&rank.letter().to_string())
+                    || ((Rank::Two..=Rank::Ten).contains(*rank)
+                        && text == rank.number().to_string())
+            })
+            .copied()
+            .ok_or_else(|| CardError::InvalidRank(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rank;
+
+    #[test]
+    fn ranks_from_numbers() {
+        for (i, rank) in Rank::ALL.iter().enumerate() {
+            assert_eq!(Ok(*rank), Rank::try_from(i as i32 + 1).map_err(|_| ()));
+        }
+        assert!(Rank::try_from(0).is_err());
+        assert!(Rank::try_from(14).is_err());
+    }
+
+    #[test]
+    fn ranks_are_ordered() {
+        let mut ranks = Rank::ALL.to_vec();
+        ranks.reverse();
+        ranks.sort();
+        assert_eq!(Rank::ALL.to_vec(), ranks);
+        assert!(Rank::Ace < Rank::Two);
+        assert!(Rank::Queen < Rank::King);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!("A", Rank::Ace.to_string());
+        assert_eq!("5", Rank::Five.to_string());
+        assert_eq!("T", Rank::Ten.to_string());
+        assert_eq!("Ace", format!("{:#}", Rank::Ace));
+        assert_eq!("Five", format!("{:#}", Rank::Five));
+        assert_eq!("King", format!("{:#}", Rank::King));
+    }
+
+    #[test]
+    fn every_rank_reads_back() {
+        for rank in Rank::ALL {
+            assert_eq!(Ok(rank), rank.to_string().parse());
+            assert_eq!(Ok(rank), format!("{:#}", rank).parse());
+            assert_eq!(Ok(rank), format!("{:#}", rank).to_uppercase().parse());
+        }
+        for rank in &Rank::ALL[1..10] {
+            assert_eq!(Ok(*rank), rank.number().to_string().parse());
+        }
+        assert!("0".parse::<Rank>().is_err());
+        assert!("11".parse::<Rank>().is_err());
+        assert!("".parse::<Rank>().is_err());
+    }
+
+    #[test]
+    fn values() {
+        let blackjack: Vec<i32> = Rank::ALL
+            .iter()
+            .map(|rank| rank.blackjack_value())
+            .collect();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 10], blackjack);
+        let baccarat: Vec<i32> = Rank::ALL.iter().map(|rank| rank.baccarat_value()).collect();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 0, 0, 0], baccarat);
+        let poker: Vec<i32> = Rank::ALL.iter().map(|rank| rank.poker_value()).collect();
+        assert_eq!(vec![14, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13], poker);
+    }
+}
@@ -0,0 +1,45 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The random number generator used for seeded shuffles.  ChaCha gives the same numbers for the
+/// same seed on every platform and every release, so a seeded shuffle can always be repeated.
+pub type SeededRng = ChaCha8Rng;
+
+/// A random number generator for a seed.  Each stream is a separate run of numbers for the same
+/// seed, so one seed can shuffle many shoes without any two of them being the same.
+///
+/// * `seed`: The seed to start from
+/// * `stream`: Which run of numbers to use
+pub fn seeded_rng(seed: u64, stream: u64) -> SeededRng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+#[cfg(test)]
+mod tests {
+    use super::seeded_rng;
+    use rand::Rng;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let first: Vec<u32> = seeded_rng(7, 0)
+            .sample_iter(rand::distributions::Standard)
+            .take(8)
+            .collect();
+        let second: Vec<u32> = seeded_rng(7, 0)
+            .sample_iter(rand::distributions::Standard)
+            .take(8)
+            .collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn streams_differ() {
+        let first: u64 = seeded_rng(7, 0).gen();
+        let second: u64 = seeded_rng(7, 1).gen();
+        let other_seed: u64 = seeded_rng(8, 0).gen();
+        assert_ne!(first, second);
+        assert_ne!(first, other_seed);
+    }
+}
@@ -1,73 +1,436 @@
-use crate::{card::Card, deck::Deck, error::CardError, has_cards::HasCards};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use crate::{
+    card::{parse_cards, Card},
+    card_source::CardSource,
+    deck::Deck,
+    error::CardError,
+    has_cards::HasCards,
+};
+use rand::{seq::SliceRandom, Rng};
+use std::sync::Arc;
 
-#[derive(Debug, Clone)]
+/// A Shoe holds several decks for dealing.  A cut card is placed near the back of the shoe and
+/// once it comes out the shoe should be reshuffled after the current round.  Cards which have been
+/// played go to the discard tray until then.
+///
+/// Cloning a shoe is cheap.  Clones share their cards until one of them is shuffled, so a game
+/// can keep the shoe from every step of its history without copying the cards each time.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SavedShoe", into = "SavedShoe")
+)]
 pub struct Shoe {
-    cards: Vec<Card>,
+    /// The cards in the order they were shuffled, dealt from the end.  Only the first `left` are
+    /// still in the shoe, the rest have been dealt.
+    cards: Arc<Vec<Card>>,
+    left: usize,
+    discards: Arc<Vec<Card>>,
+    /// The cut card comes out when fewer than this many cards are left.
+    cut_card: usize,
 }
 
 impl Shoe {
     /// NOTE: A deck is a standard deck without jokers.   It has four suits and Ace through King.
-    pub fn new(number_of_decks: i32) -> Result<Shoe, CardError> {
+    pub fn new(number_of_decks: usize) -> Shoe {
         let mut cards = vec![];
 
         for _ in 0..number_of_decks {
-            cards.append(&mut Deck::new()?.cards);
+            cards.append(&mut Deck::new().cards);
+        }
+
+        Shoe::dealing(cards)
+    }
+
+    /// A shoe which deals the given cards from the end, with nothing in the discard tray and no
+    /// cut card.
+    fn dealing(cards: Vec<Card>) -> Shoe {
+        Shoe {
+            left: cards.len(),
+            cards: Arc::new(cards),
+            discards: Default::default(),
+            cut_card: 0,
         }
+    }
+
+    /// The cards still in the shoe, the next card to deal last.
+    fn live(&self) -> &[Card] {
+        &self.cards[..self.left]
+    }
+
+    /// Change the cards still in the shoe.  The cards are copied first if another shoe is sharing
+    /// them.
+    ///
+    /// * `change`: What to do to the cards, the next card to deal last
+    fn change_cards<F: FnOnce(&mut Vec<Card>)>(&mut self, change: F) {
+        let mut cards = match Arc::get_mut(&mut self.cards) {
+            Some(cards) => std::mem::take(cards),
+            None => self.live().to_vec(),
+        };
+        cards.truncate(self.left);
+        change(&mut cards);
+        self.left = cards.len();
+        self.cards = Arc::new(cards);
+    }
+
+    /// A shoe which deals exactly the given cards, in order.  This is mostly for tests which
+    /// need to know what comes out next.
+    ///
+    /// * `cards`: The cards to deal, the first card is dealt first
+    pub fn from_cards(mut cards: Vec<Card>) -> Shoe {
+        cards.reverse();
+        Shoe::dealing(cards)
+    }
+
+    /// A shoe which deals the cards written in short notation, in order.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ Card, HasCards, Shoe };
+    ///
+    /// let mut shoe = Shoe::stacked("As Kd 5h").unwrap();
+    /// assert_eq!(3, shoe.cards_left());
+    /// assert_eq!(Some("As".parse::<Card>().unwrap()), shoe.deal());
+    /// ```
+    pub fn stacked(cards: &str) -> Result<Shoe, CardError> {
+        Ok(Shoe::from_cards(parse_cards(cards)?))
+    }
+
+    /// A full shoe with the given cards stacked on top and the rest shuffled below them.  The
+    /// stacked cards are taken out of the shoe, so it still holds exactly the given number of
+    /// decks.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ parse_cards, seeded_rng, HasCards, Shoe };
+    ///
+    /// let top = parse_cards("8s 8d Th").unwrap();
+    /// let mut shoe = Shoe::stacked_on_top(6, top.clone(), &mut seeded_rng(1, 0)).unwrap();
+    ///
+    /// assert_eq!(312, shoe.cards_left());
+    /// assert_eq!(Some(top[0]), shoe.deal());
+    /// assert_eq!(Some(top[1]), shoe.deal());
+    /// assert_eq!(Some(top[2]), shoe.deal());
+    /// ```
+    ///
+    /// * `number_of_decks`: How many decks are in the shoe
+    /// * `top`: The cards to deal first, in order
+    /// * `rng`: Shuffles the rest of the shoe
+    pub fn stacked_on_top<R: Rng + ?Sized>(
+        number_of_decks: usize,
+        top: Vec<Card>,
+        rng: &mut R,
+    ) -> Result<Shoe, CardError> {
+        let mut cards = vec![];
+        for _ in 0..number_of_decks {
+            cards.append(&mut Deck::new().cards);
+        }
+        for card in top.iter() {
+            let index = cards
+                .iter()
+                .position(|c| c == card)
+                .ok_or_else(|| CardError::NotEnoughCards(format!("{:#}", card)))?;
+            cards.swap_remove(index);
+        }
+        cards.shuffle(rng);
+        cards.extend(top.into_iter().rev());
+        Ok(Shoe::dealing(cards))
+    }
+
+    /// Place the cut card so it comes out once fewer than the given number of cards are left.  A
+    /// shoe without a cut card is dealt until it is empty.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ CardSource, HasCards, Shoe };
+    ///
+    /// let mut shoe = Shoe::new(1);
+    /// shoe.place_cut_card(13);
+    /// while shoe.cards_left() > 13 {
+    ///     shoe.deal();
+    /// }
+    /// assert!(!shoe.reshuffle_pending());
+    ///
+    /// shoe.deal();
+    /// assert!(shoe.reshuffle_pending());
+    /// ```
+    pub fn place_cut_card(&mut self, cards_left: usize) {
+        self.cut_card = cards_left;
+    }
 
-        Ok(Shoe { cards })
+    /// The number of cards in the discard tray.
+    pub fn discards(&self) -> usize {
+        self.discards.len()
     }
 }
 
 impl HasCards for Shoe {
     fn deal(&mut self) -> Option<Card> {
-        self.cards.pop()
+        self.left = self.left.checked_sub(1)?;
+        Some(self.cards[self.left])
     }
 
     fn cards_left(&self) -> usize {
-        self.cards.len()
+        self.left
     }
 
     /// Shuffle the cards
-    fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng);
+    fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.change_cards(|cards| cards.shuffle(rng));
+    }
+}
+
+impl CardSource for Shoe {
+    /// Put played cards in the discard tray.
+    fn discard(&mut self, cards: Vec<Card>) {
+        Arc::make_mut(&mut self.discards).extend(cards);
+    }
+
+    /// The cut card has come out, so the shoe should be reshuffled once the round is over.
+    fn reshuffle_pending(&self) -> bool {
+        self.left < self.cut_card
+    }
+
+    /// Gather the discards back into the shoe and shuffle every card with the given random number
+    /// generator.  The cards are put in order before they are shuffled, so the same cards and the
+    /// same generator always make the same shoe no matter how the cards were played.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ seeded_rng, CardSource, HasCards, Shoe };
+    ///
+    /// let mut shoe = Shoe::new(2);
+    /// let played = vec![shoe.deal().unwrap(), shoe.deal().unwrap()];
+    /// shoe.discard(played);
+    /// assert_eq!(102, shoe.cards_left());
+    ///
+    /// shoe.reshuffle_with(&mut seeded_rng(1, 0));
+    /// assert_eq!(104, shoe.cards_left());
+    /// assert_eq!(0, shoe.discards());
+    /// ```
+    fn reshuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let discards = Arc::unwrap_or_clone(std::mem::take(&mut self.discards));
+        self.change_cards(|cards| {
+            cards.extend(discards);
+            cards.sort_unstable();
+            cards.shuffle(rng);
+        });
     }
 }
 
 impl Default for Shoe {
     fn default() -> Self {
-        Shoe::new(7).unwrap()
+        Shoe::new(7)
+    }
+}
+
+/// Two shoes are the same when they have the same cards left to deal in the same order, the same
+/// discards and the same cut card.  The cards which have already been dealt do not matter.
+impl PartialEq for Shoe {
+    fn eq(&self, other: &Self) -> bool {
+        self.live() == other.live()
+            && self.discards == other.discards
+            && self.cut_card == other.cut_card
+    }
+}
+
+impl Eq for Shoe {}
+
+/// Only the cards left to deal are shown, the next card to deal last.
+impl std::fmt::Debug for Shoe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shoe")
+            .field("cards", &self.live())
+            .field("discards", &self.discards)
+            .field("cut_card", &self.cut_card)
+            .finish()
+    }
+}
+
+/// A shoe is saved with only the cards left to deal.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedShoe {
+    cards: Vec<Card>,
+    discards: Vec<Card>,
+    cut_card: usize,
+}
+
+#[cfg(feature = "serde")]
+impl From<Shoe> for SavedShoe {
+    fn from(shoe: Shoe) -> Self {
+        SavedShoe {
+            cards: shoe.live().to_vec(),
+            discards: Arc::unwrap_or_clone(shoe.discards),
+            cut_card: shoe.cut_card,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SavedShoe> for Shoe {
+    fn from(saved: SavedShoe) -> Self {
+        Shoe {
+            cut_card: saved.cut_card,
+            discards: Arc::new(saved.discards),
+            ..Shoe::dealing(saved.cards)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Shoe;
-    use crate::has_cards::HasCards;
+    use crate::{
+        card::{parse_cards, Card},
+        card_source::CardSource,
+        error::CardError,
+        has_cards::HasCards,
+        seed::seeded_rng,
+    };
 
     #[test]
     fn check_shoe() {
-        let maybe = Shoe::new(7);
-        assert!(maybe.is_ok());
-        let shoe = maybe.unwrap();
-        assert_eq!(52 * 7, shoe.cards.len());
+        let shoe = Shoe::new(7);
+        assert_eq!(52 * 7, shoe.cards_left());
     }
 
     #[test]
     fn default_shoe() {
         let shoe: Shoe = Default::default();
-        assert_eq!(52 * 7, shoe.cards.len());
+        assert_eq!(52 * 7, shoe.cards_left());
     }
 
     #[test]
     fn shuffle_shoe() {
-        let maybe = Shoe::new(7);
-        assert!(maybe.is_ok());
-        let mut shoe = maybe.unwrap();
+        let mut shoe = Shoe::new(7);
         shoe.shuffle();
 
-        assert_eq!(52 * 7, shoe.cards.len());
+        assert_eq!(52 * 7, shoe.cards_left());
+    }
+
+    #[test]
+    fn seeded_shuffles_repeat() {
+        let mut first = Shoe::new(4);
+        let mut second = Shoe::new(4);
+        let mut third = Shoe::new(4);
+        first.shuffle_seeded(2022);
+        second.shuffle_seeded(2022);
+        third.shuffle_seeded(2023);
+
+        assert_eq!(first, second);
+        assert_ne!(first, third);
+        assert_ne!(Shoe::new(4), first);
+    }
+
+    #[test]
+    fn stacked_shoes_deal_in_order() {
+        let mut shoe = Shoe::stacked("As, Kd, 10♥").unwrap();
+        let dealt: Vec<String> = std::iter::from_fn(|| shoe.deal())
+            .map(|card| card.to_string())
+            .collect();
+        assert_eq!(vec!["As", "Kd", "Th"], dealt);
+        assert!(Shoe::stacked("As Zz").is_err());
+    }
+
+    #[test]
+    fn stacking_on_top_keeps_the_shoe_whole() {
+        let top = parse_cards("As As").unwrap();
+        let mut shoe = Shoe::stacked_on_top(2, top, &mut seeded_rng(4, 0)).unwrap();
+        let mut dealt: Vec<Card> = std::iter::from_fn(|| shoe.deal()).collect();
+        assert_eq!("As", dealt[0].to_string());
+        assert_eq!("As", dealt[1].to_string());
+
+        dealt.sort_unstable();
+        let mut full = Shoe::new(2).live().to_vec();
+        full.sort_unstable();
+        assert_eq!(full, dealt);
+
+        let too_many = parse_cards("As As As").unwrap();
+        assert_eq!(
+            Err(CardError::NotEnoughCards("Ace of Spades".to_string())),
+            Shoe::stacked_on_top(2, too_many, &mut seeded_rng(4, 0))
+        );
+    }
+
+    #[test]
+    fn no_cut_card_deals_every_card() {
+        let mut shoe = Shoe::new(1);
+        while shoe.deal().is_some() {
+            assert!(!shoe.reshuffle_pending());
+        }
+        assert_eq!(None, shoe.deal());
+    }
+
+    #[test]
+    fn reshuffling_gathers_the_discards() {
+        let mut shoe = Shoe::new(2);
+        shoe.place_cut_card(52);
+        let mut played = vec![];
+        while !shoe.reshuffle_pending() {
+            played.push(shoe.deal().unwrap());
+        }
+        assert_eq!(53, played.len());
+        shoe.discard(played);
+        assert_eq!(53, shoe.discards());
+
+        shoe.reshuffle_with(&mut seeded_rng(3, 0));
+        assert_eq!(104, shoe.cards_left());
+        assert_eq!(0, shoe.discards());
+        assert!(!shoe.reshuffle_pending());
+    }
+
+    #[test]
+    fn reshuffles_do_not_depend_on_play() {
+        let mut fresh = Shoe::new(2);
+        fresh.reshuffle_with(&mut seeded_rng(8, 1));
+
+        let mut played = Shoe::new(2);
+        played.shuffle();
+        let cards = (0..30).filter_map(|_| played.deal()).collect();
+        played.discard(cards);
+        played.reshuffle_with(&mut seeded_rng(8, 1));
+
+        assert_eq!(fresh, played);
+    }
+
+    #[test]
+    fn clones_share_cards_until_shuffled() {
+        let mut shoe = Shoe::new(2);
+        shoe.shuffle_seeded(5);
+        let mut clone = shoe.clone();
+        assert!(std::sync::Arc::ptr_eq(&shoe.cards, &clone.cards));
+
+        let first = shoe.deal();
+        assert_eq!(first, clone.deal());
+        assert_eq!(shoe, clone);
+        assert!(std::sync::Arc::ptr_eq(&shoe.cards, &clone.cards));
+
+        let before = shoe.clone();
+        clone.shuffle_seeded(6);
+        assert!(!std::sync::Arc::ptr_eq(&shoe.cards, &clone.cards));
+        assert_eq!(before, shoe);
+        assert_eq!(103, clone.cards_left());
+        assert_ne!(shoe, clone);
+
+        clone.discard(vec![first.unwrap()]);
+        assert_eq!(0, shoe.discards());
+        assert_eq!(1, clone.discards());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn shoes_round_trip() {
+        let mut shoe = Shoe::new(2);
+        shoe.place_cut_card(20);
+        shoe.reshuffle_with(&mut seeded_rng(3, 0));
+        let dealt: Vec<Card> = (0..90).filter_map(|_| shoe.deal()).collect();
+        shoe.discard(dealt);
+
+        let json = serde_json::to_string(&shoe).unwrap();
+        assert_eq!(shoe, serde_json::from_str(&json).unwrap());
+
+        let bytes = bincode::serialize(&shoe).unwrap();
+        assert_eq!(shoe, bincode::deserialize(&bytes).unwrap());
     }
 }
@@ -1,9 +1,89 @@
 use bevy::prelude::*;
-use ui::UIPlugin;
+use blackjack::prelude::{Halt, MimicTheDealer, Playback, Progress, Rules, TableRules};
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use ui::{ReplayPlugin, UIPlugin};
+
+/// How many rounds `--record` deals.
+const RECORDED_ROUNDS: usize = 100;
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .add_plugin(UIPlugin)
-        .run();
+    if let Some(path) = path_after("--record") {
+        match record(&path) {
+            Ok(()) => println!("Recorded {} rounds to {}", RECORDED_ROUNDS, path.display()),
+            Err(error) => {
+                eprintln!("Could not record {}: {}", path.display(), error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins);
+
+    match path_after("--replay") {
+        Some(path) => {
+            let playback = Rules::load_file(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|rules| Playback::new(rules).map_err(|error| error.to_string()))
+                .unwrap_or_else(|error| {
+                    eprintln!("Could not replay {}: {}", path.display(), error);
+                    std::process::exit(1);
+                });
+            app.insert_non_send_resource(playback)
+                .add_plugin(ReplayPlugin);
+        }
+        None => {
+            app.add_plugin(UIPlugin);
+        }
+    }
+
+    app.run();
+}
+
+/// `game --replay <save>` plays a saved session back instead of opening the table.
+///
+/// `game --record <save>` deals a session at a Vegas Strip table and saves it without opening a
+/// window.  One player bets 10 a round and plays every hand the way the dealer would, so the
+/// replay has mistakes to find.  The table cannot be played by hand yet, so this is how a session
+/// to replay is made without writing one through the library.
+///
+/// * `flag`: The flag the path follows
+fn path_after(flag: &str) -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+/// Deal a session and save it.  Every recording is shuffled differently.
+///
+/// * `path`: Where the session is saved
+fn record(path: &Path) -> Result<(), String> {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let mut rules: Rules = Rules::with_seed(TableRules::vegas_strip(), seed);
+    rules.add_player().map_err(|error| error.to_string())?;
+    rules.buy_in(0, 1000).map_err(|error| error.to_string())?;
+    rules.set_strategy(Box::new(MimicTheDealer { bet: 10 }));
+
+    let mut played = 0;
+    while played < RECORDED_ROUNDS {
+        match rules.next() {
+            Some(state) if state.progress == Progress::Done => played += 1,
+            Some(_) => (),
+            None if rules.halted() == Some(&Halt::Reshuffle) => {
+                rules.reshuffle().map_err(|error| error.to_string())?
+            }
+            None => return Err(format!("the game stopped early, {:?}", rules.halted())),
+        }
+    }
+    rules.save_file(path).map_err(|error| error.to_string())
 }
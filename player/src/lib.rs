@@ -1,18 +1,211 @@
-use cards::prelude::Card;
+mod value;
 
-#[derive(Debug, Default, Clone)]
+use cards::prelude::{parse_cards, Card, CardError};
+use std::str::FromStr;
+
+pub use value::{HandValue, BLACKJACK};
+
+/// Chips are how money is counted at the table.  They are signed so the house can be behind.
+pub type Chips = i64;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hand {
     cards: Vec<Card>,
+    /// The chips wagered on this hand.
+    pub bet: Chips,
+    /// The bet on this hand was doubled and it took exactly one more card.
+    pub doubled: bool,
+    /// The hand was given up for half of the bet.
+    pub surrendered: bool,
+    /// The hand came from splitting a pair.  A split hand can never be a blackjack.
+    pub split: bool,
 }
 
-#[derive(Debug, Default, Clone)]
+/// Build a hand from a list of cards.  The first card in the list is the first card shown.
+impl From<Vec<Card>> for Hand {
+    fn from(cards: Vec<Card>) -> Self {
+        Hand {
+            cards,
+            ..Default::default()
+        }
+    }
+}
+
+/// Read a hand from a list of cards such as "As Kd 5h".  Cards written out by name are
+/// separated by commas instead, "Ace of Spades, King of Diamonds".  The first card is the first
+/// card shown.
+///
+/// Example:
+/// ```
+/// use player::Hand;
+///
+/// let hand: Hand = "As Kd".parse().unwrap();
+/// assert!(hand.is_blackjack());
+///
+/// let hand: Hand = "Ace of Spades, 6♥".parse().unwrap();
+/// assert_eq!("As 6h", hand.to_string());
+/// assert!("As Xd".parse::<Hand>().is_err());
+/// ```
+impl FromStr for Hand {
+    type Err = CardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Hand::from(parse_cards(s)?))
+    }
+}
+
+/// Hand needs a standard way to be printed out.  Each card is written in short notation, or by
+/// name with the alternate form, `{:#}`.
+impl std::fmt::Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separator = if f.alternate() { ", " } else { " " };
+        for (i, card) in self.cards.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", separator)?;
+            }
+            if f.alternate() {
+                write!(f, "{:#}", card)?;
+            } else {
+                write!(f, "{}", card)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Hand {
+    /// A pair is two cards with the same value, so a King and a Ten can be split.
+    pub fn is_pair(&self) -> bool {
+        self.cards.len() == 2
+            && self.cards[0].rank().blackjack_value() == self.cards[1].rank().blackjack_value()
+    }
+
+    /// Split a pair into two hands.  This hand keeps the first card and the second card is
+    /// returned as a new hand.  Both hands are marked as split.  The new hand has no bet on it
+    /// until one is placed.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ Card, Rank, Suit };
+    /// use player::{ Hand, HandleCards };
+    ///
+    /// let mut hand = Hand::from(vec![
+    ///     Card::new(Rank::Eight, Suit::Clubs),
+    ///     Card::new(Rank::Eight, Suit::Hearts),
+    /// ]);
+    ///
+    /// let other = hand.split_pair().unwrap();
+    /// assert_eq!(1, hand.number_of_cards());
+    /// assert_eq!(1, other.number_of_cards());
+    /// assert!(hand.split && other.split);
+    /// ```
+    pub fn split_pair(&mut self) -> Option<Hand> {
+        if !self.is_pair() {
+            return None;
+        }
+
+        let card = self.cards.pop()?;
+        self.split = true;
+        Some(Hand {
+            cards: vec![card],
+            split: true,
+            ..Default::default()
+        })
+    }
+}
+
+/// A Player is someone at the table with chips.  A player plays from one or more seats, and the
+/// bets in every one of them come out of the same bankroll.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
-    pub hand: Hand,
+    /// The chips the player has which are not bet.
+    pub bankroll: Chips,
+}
+
+/// A Seat is a place at the table which is dealt in and bet on by itself.  Each seat starts with
+/// one hand and gets more by splitting pairs, and every hand has its own bet.
+///
+/// Example:
+/// ```
+/// use player::{ HandleCards, Seat };
+///
+/// let mut seat = Seat::new(2);
+/// seat.hands[0] = "8s 8d".parse().unwrap();
+/// seat.hands[0].bet = 10;
+/// let other = seat.hands[0].split_pair().unwrap();
+/// seat.hands.push(other);
+///
+/// assert_eq!(2, seat.player);
+/// assert_eq!(10, seat.total_bet());
+/// assert_eq!(2, seat.trash().len());
+/// assert_eq!(1, seat.hands.len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Seat {
+    /// The player sitting here, by their place among the players at the table.
+    pub player: usize,
+    /// Every hand played from this seat, in the order they are played.
+    pub hands: Vec<Hand>,
+    /// The index of the hand being acted on.
+    pub active: usize,
+}
+
+impl Seat {
+    /// The given player sits down with a single empty hand.
+    ///
+    /// * `player`: Who is sitting here
+    pub fn new(player: usize) -> Self {
+        Seat {
+            player,
+            hands: vec![Default::default()],
+            active: 0,
+        }
+    }
+
+    /// The hand being acted on.  When every hand in the seat is finished there is no active
+    /// hand.
+    pub fn active_hand(&self) -> Option<&Hand> {
+        self.hands.get(self.active)
+    }
+
+    /// The hand being acted on as mutable.
+    pub fn active_hand_mut(&mut self) -> Option<&mut Hand> {
+        self.hands.get_mut(self.active)
+    }
+
+    /// The chips bet across every hand in the seat.
+    pub fn total_bet(&self) -> Chips {
+        self.hands.iter().map(|hand| hand.bet).sum()
+    }
+
+    /// A seat is in the round once it has a bet down.
+    pub fn is_betting(&self) -> bool {
+        self.total_bet() > 0
+    }
+
+    /// Clear every hand away and go back to a single empty hand.  The player stays seated.
+    pub fn trash(&mut self) -> Vec<Card> {
+        let trashing = self
+            .hands
+            .iter_mut()
+            .flat_map(|hand| hand.trash())
+            .collect();
+        self.hands = vec![Default::default()];
+        self.active = 0;
+        trashing
+    }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct House {
     pub hand: Hand,
+    /// What the house has won or lost.  This starts at zero and goes negative when the house is
+    /// behind.
+    pub bankroll: Chips,
 }
 
 /// Many things can Handle cards.  This trait will allow common ways to handle cardv
@@ -30,11 +223,11 @@ pub trait HandleCards {
 ///
 /// # Example
 /// ```
-/// use cards::prelude::{ Card, Suit };
+/// use cards::prelude::{ Card, Rank, Suit };
 /// use player::{ Hand, HandleCards };
 ///
 /// let mut hand: Hand = Default::default();
-/// hand.recieve(Card::new(1,Suit::Clubs).unwrap());
+/// hand.recieve(Card::new(Rank::Ace, Suit::Clubs));
 /// assert!(hand.show_card().is_some());
 /// assert_eq!(hand.show_hand().len(), 1);
 /// assert!(hand.trash_card().is_some());
@@ -63,7 +256,7 @@ impl HandleCards for Hand {
 
     fn trash(&mut self) -> Vec<Card> {
         let trashing = self.cards.to_vec();
-        self.cards.clear();
+        *self = Default::default();
         trashing
     }
 
@@ -74,20 +267,102 @@ impl HandleCards for Hand {
 
 #[cfg(test)]
 mod tests {
-    use super::{Hand, HandleCards};
-    use cards::prelude::{Card, Suit};
+    use super::{Hand, HandleCards, Seat};
+    use cards::prelude::{Card, Rank, Suit};
 
     #[test]
     fn hand_has_cards() {
         let mut hand: Hand = Default::default();
         assert_eq!(hand.number_of_cards(), 0);
-        hand.recieve(Card::new(1, Suit::Clubs).unwrap());
-        hand.recieve(Card::new(1, Suit::Diamonds).unwrap());
+        hand.recieve(Card::new(Rank::Ace, Suit::Clubs));
+        hand.recieve(Card::new(Rank::Ace, Suit::Diamonds));
         assert_eq!(hand.number_of_cards(), 2);
         assert_eq!(hand.cards().len(), 2);
         assert!(hand.show_card().is_some());
         assert!(hand.show_card().is_some());
-        assert_eq!(hand.show_card().unwrap().rank(), "Ace");
-        assert_eq!(hand.show_card().unwrap().value(), 1);
+        assert_eq!(hand.show_card().unwrap().rank(), Rank::Ace);
+    }
+
+    #[test]
+    fn hand_notation() {
+        let hand: Hand = "As Td 9c".parse().unwrap();
+        assert_eq!(3, hand.number_of_cards());
+        assert_eq!(Card::new(Rank::Nine, Suit::Clubs), hand.cards()[2]);
+        assert_eq!("As Td 9c", hand.to_string());
+        assert_eq!(
+            "Ace of Spades, Ten of Diamonds, Nine of Clubs",
+            format!("{:#}", hand)
+        );
+        assert_eq!(
+            Ok(hand),
+            "Ace of Spades, Ten of Diamonds, Nine of Clubs".parse()
+        );
+
+        let empty: Hand = "".parse().unwrap();
+        assert_eq!(0, empty.number_of_cards());
+    }
+
+    #[test]
+    fn only_pairs_split() {
+        let mut hand = Hand::from(vec![
+            Card::new(Rank::King, Suit::Clubs),
+            Card::new(Rank::Ten, Suit::Diamonds),
+        ]);
+        let other = hand.split_pair().unwrap();
+        assert_eq!(10, hand.total());
+        assert_eq!(10, other.total());
+
+        let mut hand = Hand::from(vec![
+            Card::new(Rank::Nine, Suit::Clubs),
+            Card::new(Rank::Ten, Suit::Diamonds),
+        ]);
+        assert!(hand.split_pair().is_none());
+        assert_eq!(2, hand.number_of_cards());
+    }
+
+    #[test]
+    fn split_aces_are_not_blackjack() {
+        let mut hand = Hand::from(vec![
+            Card::new(Rank::Ace, Suit::Clubs),
+            Card::new(Rank::Ace, Suit::Diamonds),
+        ]);
+        let _ = hand.split_pair().unwrap();
+        hand.recieve(Card::new(Rank::King, Suit::Spades));
+        assert_eq!(21, hand.total());
+        assert!(!hand.is_blackjack());
+    }
+
+    #[test]
+    fn trashing_a_seat_leaves_one_hand() {
+        let mut seat = Seat::new(3);
+        seat.hands[0].recieve(Card::new(Rank::Eight, Suit::Clubs));
+        seat.hands[0].recieve(Card::new(Rank::Eight, Suit::Hearts));
+        let other = seat.hands[0].split_pair().unwrap();
+        seat.hands.push(other);
+        seat.active = 1;
+
+        assert_eq!(2, seat.trash().len());
+        assert_eq!(1, seat.hands.len());
+        assert_eq!(0, seat.active);
+        assert_eq!(3, seat.player);
+        assert_eq!(0, seat.active_hand().unwrap().number_of_cards());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn seats_round_trip() {
+        let mut seat = Seat {
+            player: 1,
+            hands: vec!["8s 3d".parse().unwrap(), "8c Kh".parse().unwrap()],
+            active: 1,
+        };
+        seat.hands[0].doubled = true;
+        seat.hands[1].split = true;
+
+        let json = serde_json::to_string(&seat).unwrap();
+        assert_eq!(seat, serde_json::from_str(&json).unwrap());
+
+        let bytes = bincode::serialize(&seat).unwrap();
+        assert_eq!(seat, bincode::deserialize(&bytes).unwrap());
     }
 }
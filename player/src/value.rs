@@ -0,0 +1,254 @@
+use crate::{Hand, HandleCards};
+use cards::prelude::Rank;
+
+/// The highest total a hand can have before it is bust.
+pub const BLACKJACK: i32 = 21;
+
+/// HandValue is the blackjack value of a hand.  The total is the best total the hand can make and
+/// soft lets you know if an ace is being counted as eleven to get there.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandValue {
+    pub total: i32,
+    pub soft: bool,
+}
+
+/// HandValue needs a standard way to be printed out, "soft 17" or "hard 17".
+impl std::fmt::Display for HandValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.soft {
+            write!(f, "soft {}", self.total)
+        } else {
+            write!(f, "hard {}", self.total)
+        }
+    }
+}
+
+impl Hand {
+    /// The total of the hand when every ace is counted as one.
+    fn hard_total(&self) -> i32 {
+        self.cards()
+            .iter()
+            .map(|card| card.rank().blackjack_value())
+            .sum()
+    }
+
+    fn number_of_aces(&self) -> usize {
+        self.cards()
+            .iter()
+            .filter(|card| card.rank() == Rank::Ace)
+            .count()
+    }
+
+    /// All of the totals the hand could make, from lowest to highest.  Each ace can be counted as
+    /// one or eleven so a hand with aces will have more than one total.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ Card, Rank, Suit };
+    /// use player::Hand;
+    ///
+    /// let hand = Hand::from(vec![
+    ///     Card::new(Rank::Ace, Suit::Clubs),
+    ///     Card::new(Rank::Ace, Suit::Hearts),
+    ///     Card::new(Rank::Six, Suit::Spades),
+    /// ]);
+    ///
+    /// assert_eq!(vec![8, 18, 28], hand.totals());
+    /// ```
+    pub fn totals(&self) -> Vec<i32> {
+        let hard = self.hard_total();
+        (0..=self.number_of_aces() as i32)
+            .map(|elevens| hard + 10 * elevens)
+            .collect()
+    }
+
+    /// The best value of the hand.  This is the highest total which is not bust, or the hard total
+    /// when every total is bust.
+    ///
+    /// Example:
+    /// ```
+    /// use cards::prelude::{ Card, Rank, Suit };
+    /// use player::{ Hand, HandValue };
+    ///
+    /// let hand = Hand::from(vec![
+    ///     Card::new(Rank::Ace, Suit::Clubs),
+    ///     Card::new(Rank::Six, Suit::Spades),
+    /// ]);
+    ///
+    /// assert_eq!(HandValue { total: 17, soft: true }, hand.value());
+    /// ```
+    pub fn value(&self) -> HandValue {
+        let hard = self.hard_total();
+        if self.number_of_aces() > 0 && hard + 10 <= BLACKJACK {
+            HandValue {
+                total: hard + 10,
+                soft: true,
+            }
+        } else {
+            HandValue {
+                total: hard,
+                soft: false,
+            }
+        }
+    }
+
+    /// The best total of the hand.
+    pub fn total(&self) -> i32 {
+        self.value().total
+    }
+
+    /// A soft hand is counting an ace as eleven.  It can not bust by taking one more card.
+    pub fn is_soft(&self) -> bool {
+        self.value().soft
+    }
+
+    /// A blackjack (natural) is exactly two cards making 21.  A hand which came from a split is
+    /// only ever 21.
+    pub fn is_blackjack(&self) -> bool {
+        !self.split && self.number_of_cards() == 2 && self.total() == BLACKJACK
+    }
+
+    /// A hand is bust when even the lowest total is over 21.
+    pub fn is_bust(&self) -> bool {
+        self.hard_total() > BLACKJACK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HandValue, BLACKJACK};
+    use crate::Hand;
+    use cards::prelude::{Card, Rank, Suit};
+
+    /// Value a set of card values the long way.  Every ace is tried as both one and eleven and the
+    /// best total wins.
+    fn reference(values: &[i32]) -> (Vec<i32>, HandValue) {
+        let aces: Vec<usize> = (0..values.len()).filter(|&i| values[i] == 1).collect();
+        let mut totals = vec![];
+        let mut best: Option<HandValue> = None;
+
+        for mask in 0..(1 << aces.len()) {
+            let mut total = 0;
+            let mut elevens = 0;
+            for (i, value) in values.iter().enumerate() {
+                match aces.iter().position(|&ace| ace == i) {
+                    Some(bit) if mask & (1 << bit) != 0 => {
+                        total += 11;
+                        elevens += 1;
+                    }
+                    _ => total += value,
+                }
+            }
+            if !totals.contains(&total) {
+                totals.push(total);
+            }
+
+            let candidate = HandValue {
+                total,
+                soft: elevens > 0,
+            };
+            best = match best {
+                None => Some(candidate),
+                Some(current) => {
+                    let better = if candidate.total <= BLACKJACK {
+                        current.total > BLACKJACK || candidate.total > current.total
+                    } else {
+                        current.total > BLACKJACK && candidate.total < current.total
+                    };
+                    Some(if better { candidate } else { current })
+                }
+            };
+        }
+
+        totals.sort_unstable();
+        (totals, best.unwrap())
+    }
+
+    fn hand_of(ranks: &[i32]) -> Hand {
+        let suits = [Suit::Clubs, Suit::Hearts, Suit::Spades, Suit::Diamonds];
+        Hand::from(
+            ranks
+                .iter()
+                .enumerate()
+                .map(|(i, &rank)| Card::new(Rank::try_from(rank).unwrap(), suits[i % suits.len()]))
+                .collect::<Vec<Card>>(),
+        )
+    }
+
+    fn check(ranks: &[i32]) {
+        let hand = hand_of(ranks);
+        let values: Vec<i32> = hand
+            .cards
+            .iter()
+            .map(|card| card.rank().blackjack_value())
+            .collect();
+        let (totals, best) = reference(&values);
+
+        assert_eq!(totals, hand.totals(), "totals for {:?}", ranks);
+        assert_eq!(best, hand.value(), "value for {:?}", ranks);
+        assert_eq!(best.total, hand.total(), "total for {:?}", ranks);
+        assert_eq!(best.soft, hand.is_soft(), "soft for {:?}", ranks);
+        assert_eq!(
+            best.total > BLACKJACK,
+            hand.is_bust(),
+            "bust for {:?}",
+            ranks
+        );
+        assert_eq!(
+            ranks.len() == 2 && best.total == BLACKJACK,
+            hand.is_blackjack(),
+            "blackjack for {:?}",
+            ranks
+        );
+    }
+
+    #[test]
+    fn every_two_card_hand() {
+        for first in 1..=13 {
+            for second in 1..=13 {
+                check(&[first, second]);
+            }
+        }
+    }
+
+    #[test]
+    fn every_three_card_hand() {
+        for first in 1..=13 {
+            for second in 1..=13 {
+                for third in 1..=13 {
+                    check(&[first, second, third]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_hand() {
+        let hand: Hand = Default::default();
+        assert_eq!(vec![0], hand.totals());
+        assert_eq!(0, hand.total());
+        assert!(!hand.is_soft());
+        assert!(!hand.is_bust());
+        assert!(!hand.is_blackjack());
+    }
+
+    #[test]
+    fn soft_and_hard_seventeen() {
+        let soft = hand_of(&[1, 6]);
+        assert_eq!("soft 17", soft.value().to_string());
+        let hard = hand_of(&[10, 7]);
+        assert_eq!("hard 17", hard.value().to_string());
+        let hard_with_ace = hand_of(&[1, 6, 10]);
+        assert_eq!("hard 17", hard_with_ace.value().to_string());
+    }
+
+    #[test]
+    fn naturals_and_busts() {
+        assert!(hand_of(&[1, 13]).is_blackjack());
+        assert!(!hand_of(&[7, 7, 7]).is_blackjack());
+        assert!(hand_of(&[10, 10, 2]).is_bust());
+        assert!(!hand_of(&[1, 1, 1, 1, 1, 6]).is_bust());
+        assert_eq!(20, hand_of(&[1, 1, 1, 1, 6]).total());
+    }
+}
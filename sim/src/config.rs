@@ -0,0 +1,367 @@
+use crate::error::SimError;
+use blackjack::prelude::TableRules;
+use cards::prelude::{parse_cards, Card, Shoe};
+use player::{Chips, Hand};
+use rand::thread_rng;
+
+/// The help printed for --help.
+pub const USAGE: &str = "Usage: casino-sim [options]
+
+Options:
+    --rounds N          Rounds to play, per seat (default 100000)
+    --threads N         Threads to play on (default every core)
+    --table NAME        vegas, downtown, atlantic or european (default vegas)
+    --decks N           Decks in the shoe
+    --h17               The house hits soft 17
+    --s17               The house stands on soft 17
+    --penetration P     How much of the shoe is dealt, between 0 and 1
+    --players N         Seats at the table (default 1)
+    --strategy NAME     basic or mimic (default basic)
+    --bet N             The base bet, within the table limits (default the table minimum)
+    --spread SPREAD     Hi-Lo bet spread as true count:units, e.g. \"1:2,2:4,3:8\"
+    --bankroll N        The bankroll in base bets, for risk of ruin (default 1000)
+    --seed N            Shuffle every shoe from this seed to repeat a run
+    --csm BUFFER        Deal from a continuous shuffler holding this many cards ready
+    --stack CARDS       Stack these cards on top of every shoe, e.g. \"As Kd 5h\"
+    --help              Print this help";
+
+/// Every option which takes a value.
+const OPTIONS: [&str; 13] = [
+    "--rounds",
+    "--threads",
+    "--table",
+    "--decks",
+    "--penetration",
+    "--players",
+    "--strategy",
+    "--bet",
+    "--spread",
+    "--bankroll",
+    "--seed",
+    "--csm",
+    "--stack",
+];
+
+/// Which strategy plays every seat.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StrategyKind {
+    Basic,
+    Mimic,
+}
+
+/// Config is everything needed to run a simulation.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Config {
+    pub rounds: u64,
+    pub threads: usize,
+    pub table: TableRules,
+    pub players: usize,
+    pub strategy: StrategyKind,
+    /// The base bet.  Every result is reported in units of this bet.
+    pub bet: Chips,
+    /// Pairs of true count and units to bet once the count reaches it, sorted by count.
+    pub spread: Vec<(i32, Chips)>,
+    /// The bankroll, in base bets.
+    pub bankroll: f64,
+    /// The seed every shoe is shuffled from, or a random seed for every shoe.
+    pub seed: Option<u64>,
+    /// Deal from a continuous shuffler with this buffer size instead of a hand shuffled shoe.
+    pub csm: Option<usize>,
+    /// Cards dealt first from every shoe, with the rest shuffled below them.
+    pub stack: Vec<Card>,
+    pub help: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let table = TableRules::vegas_strip();
+        Config {
+            rounds: 100_000,
+            threads: std::thread::available_parallelism()
+                .map(|threads| threads.get())
+                .unwrap_or(1),
+            bet: table.min_bet,
+            table,
+            players: 1,
+            strategy: StrategyKind::Basic,
+            spread: vec![],
+            bankroll: 1000.0,
+            seed: None,
+            csm: None,
+            stack: vec![],
+            help: false,
+        }
+    }
+}
+
+impl Config {
+    /// Build a config from the command line arguments, without the program name.  Table options
+    /// are applied on top of the chosen table no matter the order they are given in.
+    ///
+    /// Example:
+    /// ```
+    /// use casino_sim::config::Config;
+    ///
+    /// let args = ["--table", "downtown", "--s17", "--rounds", "500"];
+    /// let config = Config::from_args(args.iter().map(|arg| arg.to_string())).unwrap();
+    ///
+    /// assert_eq!(500, config.rounds);
+    /// assert_eq!(2, config.table.decks);
+    /// assert!(!config.table.dealer_hits_soft_17);
+    /// ```
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, SimError> {
+        let mut config: Config = Default::default();
+        let mut table = None;
+        let mut decks = None;
+        let mut hits_soft_17 = None;
+        let mut penetration = None;
+        let mut bet = None;
+
+        let mut args = args.into_iter();
+        while let Some(option) = args.next() {
+            if option == "--help" {
+                config.help = true;
+                continue;
+            }
+            if option == "--h17" || option == "--s17" {
+                hits_soft_17 = Some(option == "--h17");
+                continue;
+            }
+
+            if !OPTIONS.contains(&option.as_str()) {
+                return Err(SimError::UnknownOption(option));
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| SimError::MissingValue(option.clone()))?;
+            let invalid = || SimError::InvalidValue {
+                option: option.clone(),
+                value: value.clone(),
+            };
+            match option.as_str() {
+                "--rounds" => config.rounds = value.parse().map_err(|_| invalid())?,
+                "--threads" => {
+                    config.threads = value.parse().map_err(|_| invalid())?;
+                    if config.threads == 0 {
+                        return Err(invalid());
+                    }
+                }
+                "--table" => {
+                    table = Some(match value.as_str() {
+                        "vegas" => TableRules::vegas_strip(),
+                        "downtown" => TableRules::downtown_vegas(),
+                        "atlantic" => TableRules::atlantic_city(),
+                        "european" => TableRules::european(),
+                        _ => return Err(invalid()),
+                    })
+                }
+                "--decks" => match value.parse() {
+                    Ok(count) if count > 0 => decks = Some(count),
+                    _ => return Err(invalid()),
+                },
+                "--penetration" => match value.parse::<f32>() {
+                    Ok(amount) if (0.0..=1.0).contains(&amount) => penetration = Some(amount),
+                    _ => return Err(invalid()),
+                },
+                "--players" => match value.parse() {
+                    Ok(count) if count > 0 => config.players = count,
+                    _ => return Err(invalid()),
+                },
+                "--strategy" => {
+                    config.strategy = match value.as_str() {
+                        "basic" => StrategyKind::Basic,
+                        "mimic" => StrategyKind::Mimic,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "--bet" => match value.parse() {
+                    Ok(chips) if chips > 0 => bet = Some(chips),
+                    _ => return Err(invalid()),
+                },
+                "--spread" => config.spread = parse_spread(&value).ok_or_else(invalid)?,
+                "--bankroll" => match value.parse::<f64>() {
+                    Ok(units) if units > 0.0 => config.bankroll = units,
+                    _ => return Err(invalid()),
+                },
+                "--csm" => match value.parse() {
+                    Ok(buffer) if buffer > 0 => config.csm = Some(buffer),
+                    _ => return Err(invalid()),
+                },
+                "--stack" => config.stack = parse_cards(&value).map_err(|_| invalid())?,
+                "--seed" => config.seed = Some(value.parse().map_err(|_| invalid())?),
+                _ => return Err(SimError::UnknownOption(option)),
+            }
+        }
+
+        if let Some(table) = table {
+            config.table = table;
+        }
+        if let Some(decks) = decks {
+            config.table.decks = decks;
+        }
+        if let Some(hits_soft_17) = hits_soft_17 {
+            config.table.dealer_hits_soft_17 = hits_soft_17;
+        }
+        if let Some(penetration) = penetration {
+            config.table.penetration = penetration;
+        }
+        config.bet = bet.unwrap_or(config.table.min_bet);
+        if !config.table.allows_bet(config.bet) {
+            return Err(SimError::InvalidValue {
+                option: "--bet".to_string(),
+                value: config.bet.to_string(),
+            });
+        }
+
+        if !config.stack.is_empty() {
+            if config.csm.is_some() {
+                return Err(SimError::Conflict(
+                    "--stack".to_string(),
+                    "--csm".to_string(),
+                ));
+            }
+            let decks = config.table.decks;
+            if Shoe::stacked_on_top(decks, config.stack.clone(), &mut thread_rng()).is_err() {
+                return Err(SimError::InvalidValue {
+                    option: "--stack".to_string(),
+                    value: format!("{}", Hand::from(config.stack.clone())),
+                });
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Parse a bet spread such as "1:2,2:4,3:8".
+fn parse_spread(spread: &str) -> Option<Vec<(i32, Chips)>> {
+    let mut steps = spread
+        .split(',')
+        .map(|step| {
+            let (count, units) = step.split_once(':')?;
+            let units: Chips = units.trim().parse().ok()?;
+            if units < 1 {
+                return None;
+            }
+            Some((count.trim().parse().ok()?, units))
+        })
+        .collect::<Option<Vec<(i32, Chips)>>>()?;
+    steps.sort_unstable();
+    Some(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, StrategyKind};
+    use crate::error::SimError;
+    use blackjack::prelude::TableRules;
+    use player::Hand;
+
+    fn parse(args: &[&str]) -> Result<Config, SimError> {
+        Config::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(&[]).unwrap();
+        assert_eq!(100_000, config.rounds);
+        assert!(config.threads > 0);
+        assert_eq!(TableRules::vegas_strip(), config.table);
+        assert_eq!(10, config.bet);
+        assert_eq!(StrategyKind::Basic, config.strategy);
+        assert!(config.spread.is_empty());
+    }
+
+    #[test]
+    fn table_options_apply_to_the_table() {
+        let config = parse(&[
+            "--decks",
+            "6",
+            "--h17",
+            "--table",
+            "european",
+            "--penetration",
+            "0.5",
+        ])
+        .unwrap();
+        assert_eq!(6, config.table.decks);
+        assert!(config.table.dealer_hits_soft_17);
+        assert!(!config.table.hole_card);
+        assert_eq!(0.5, config.table.penetration);
+    }
+
+    #[test]
+    fn bet_spread() {
+        let config = parse(&["--spread", "3:8, 1:2,2:4", "--bet", "25"]).unwrap();
+        assert_eq!(vec![(1, 2), (2, 4), (3, 8)], config.spread);
+        assert_eq!(25, config.bet);
+    }
+
+    #[test]
+    fn bets_have_to_be_within_the_table_limits() {
+        let table = TableRules::vegas_strip();
+        let max = table.max_bet.to_string();
+        assert_eq!(table.max_bet, parse(&["--bet", &max]).unwrap().bet);
+
+        let over = (table.max_bet + 1).to_string();
+        for bet in ["1", over.as_str()] {
+            assert_eq!(
+                Err(SimError::InvalidValue {
+                    option: "--bet".to_string(),
+                    value: bet.to_string(),
+                }),
+                parse(&["--bet", bet])
+            );
+        }
+    }
+
+    #[test]
+    fn seed() {
+        assert_eq!(None, parse(&[]).unwrap().seed);
+        assert_eq!(Some(42), parse(&["--seed", "42"]).unwrap().seed);
+        assert!(parse(&["--seed", "-1"]).is_err());
+    }
+
+    #[test]
+    fn stacked_cards() {
+        let config = parse(&["--stack", "As Kd"]).unwrap();
+        assert_eq!("As Kd", Hand::from(config.stack).to_string());
+        assert!(parse(&["--stack", "As Xd"]).is_err());
+        assert_eq!(
+            Err(SimError::Conflict(
+                "--stack".to_string(),
+                "--csm".to_string()
+            )),
+            parse(&["--stack", "As", "--csm", "10"])
+        );
+        assert!(matches!(
+            parse(&["--decks", "1", "--stack", "As As"]),
+            Err(SimError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn shuffling_machine() {
+        assert_eq!(None, parse(&[]).unwrap().csm);
+        assert_eq!(Some(15), parse(&["--csm", "15"]).unwrap().csm);
+        assert!(parse(&["--csm", "0"]).is_err());
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert_eq!(
+            Err(SimError::UnknownOption("--fast".to_string())),
+            parse(&["--fast"])
+        );
+        assert_eq!(
+            Err(SimError::MissingValue("--rounds".to_string())),
+            parse(&["--rounds"])
+        );
+        assert!(matches!(
+            parse(&["--strategy", "psychic"]),
+            Err(SimError::InvalidValue { .. })
+        ));
+        assert!(parse(&["--spread", "1:0"]).is_err());
+        assert!(parse(&["--threads", "0"]).is_err());
+        assert!(parse(&["--penetration", "2"]).is_err());
+    }
+}
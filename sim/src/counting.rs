@@ -0,0 +1,127 @@
+use blackjack::prelude::{Action, Decision, GameState, Insurance, Strategy, TableRules};
+use cards::prelude::{Card, CardSource, Shoe};
+use player::{Chips, HandleCards};
+use std::{cell::Cell, rc::Rc};
+
+/// The Hi-Lo count for a single card.  Low cards leaving the shoe are good for the player and
+/// tens and aces leaving are bad.
+pub fn hi_lo(card: &Card) -> i32 {
+    match card.rank().blackjack_value() {
+        2..=6 => 1,
+        7..=9 => 0,
+        _ => -1,
+    }
+}
+
+/// The running count of every card seen at the table in a finished game.
+pub fn count_game<S>(state: &GameState<S>) -> i32 {
+    let house = state.house.hand.cards().iter();
+    let seats = state
+        .seats
+        .iter()
+        .flat_map(|seat| seat.hands.iter())
+        .flat_map(|hand| hand.cards().iter());
+    house.chain(seats).map(hi_lo).sum()
+}
+
+/// The running count divided by the decks left in the shoe, rounded down.
+pub fn true_count(running_count: i32, cards_left: usize) -> i32 {
+    let decks_left = (cards_left as f64 / 52.0).max(0.5);
+    (running_count as f64 / decks_left).floor() as i32
+}
+
+/// Spread plays like another strategy but sizes every bet by the true count.  The running count
+/// is kept by whoever is watching the games and shared with the strategy.
+pub struct Spread<S: CardSource = Shoe> {
+    pub play: Box<dyn Strategy<S>>,
+    pub unit: Chips,
+    pub running_count: Rc<Cell<i32>>,
+    /// Pairs of true count and units, sorted by count.
+    pub spread: Vec<(i32, Chips)>,
+}
+
+impl<S: CardSource> Spread<S> {
+    /// The number of units to bet at a true count.
+    pub fn units_at(&self, true_count: i32) -> Chips {
+        self.spread
+            .iter()
+            .rev()
+            .find(|(count, _)| true_count >= *count)
+            .map(|(_, units)| *units)
+            .unwrap_or(1)
+    }
+}
+
+impl<S: CardSource> Strategy<S> for Spread<S> {
+    fn bet(&mut self, seat: usize, state: &GameState<S>, table: &TableRules) -> Chips {
+        let count = true_count(self.running_count.get(), state.shoe.cards_left());
+        let bet = (self.unit * self.units_at(count)).clamp(table.min_bet, table.max_bet);
+        let place = &state.seats[seat];
+        let bankroll = state.players[place.player].bankroll + place.total_bet();
+        if bet <= bankroll {
+            bet
+        } else {
+            0
+        }
+    }
+
+    fn act(&mut self, decision: &Decision<S>) -> Action {
+        self.play.act(decision)
+    }
+
+    fn insure(&mut self, seat: usize, state: &GameState<S>, table: &TableRules) -> Insurance {
+        self.play.insure(seat, state, table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hi_lo, true_count, Spread};
+    use blackjack::prelude::{MimicTheDealer, Rules, Strategy};
+    use cards::prelude::{Card, Rank, Suit};
+    use std::{cell::Cell, rc::Rc};
+
+    #[test]
+    fn a_full_deck_counts_to_zero() {
+        let count: i32 = Rank::ALL
+            .iter()
+            .map(|&rank| hi_lo(&Card::new(rank, Suit::Hearts)))
+            .sum();
+        assert_eq!(0, count);
+        assert_eq!(1, hi_lo(&Card::new(Rank::Five, Suit::Clubs)));
+        assert_eq!(-1, hi_lo(&Card::new(Rank::Queen, Suit::Clubs)));
+        assert_eq!(-1, hi_lo(&Card::new(Rank::Ace, Suit::Clubs)));
+    }
+
+    #[test]
+    fn true_counts() {
+        assert_eq!(2, true_count(8, 208));
+        assert_eq!(-2, true_count(-4, 104));
+        assert_eq!(-1, true_count(-1, 208));
+        assert_eq!(10, true_count(5, 10));
+    }
+
+    #[test]
+    fn bets_follow_the_count() {
+        let mut rules: Rules = Default::default();
+        rules.add_player().unwrap();
+        rules.buy_in(0, 1000).unwrap();
+        let table = rules.table_rules().clone();
+
+        let running_count = Rc::new(Cell::new(0));
+        let mut spread = Spread {
+            play: Box::new(MimicTheDealer { bet: 10 }),
+            unit: 10,
+            running_count: running_count.clone(),
+            spread: vec![(1, 2), (3, 8)],
+        };
+        assert_eq!(10, spread.bet(0, rules.current_state(), &table));
+
+        running_count.set(20);
+        assert_eq!(80, spread.bet(0, rules.current_state(), &table));
+
+        running_count.set(1_000);
+        spread.unit = 500;
+        assert_eq!(0, spread.bet(0, rules.current_state(), &table));
+    }
+}
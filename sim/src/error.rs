@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SimError {
+    #[error("Unknown option {0}.")]
+    UnknownOption(String),
+    #[error("Option {0} needs a value.")]
+    MissingValue(String),
+    #[error("The value {value} for {option} is not valid.")]
+    InvalidValue { option: String, value: String },
+    #[error("Option {0} can not be used with {1}.")]
+    Conflict(String, String),
+    #[error("The simulation stopped early: {0}")]
+    Stopped(String),
+}
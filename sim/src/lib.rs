@@ -0,0 +1,303 @@
+pub mod config;
+pub mod counting;
+pub mod error;
+pub mod stats;
+
+use blackjack::prelude::{
+    BasicStrategy, Event, GameState, Halt, MimicTheDealer, Progress, Rules, Strategy,
+};
+use cards::prelude::{seeded_rng, CardSource, ContinuousShuffler, Shoe};
+use config::{Config, StrategyKind};
+use counting::{count_game, Spread};
+use error::SimError;
+use player::Chips;
+use rand::{thread_rng, Rng};
+use stats::Stats;
+use std::{cell::Cell, rc::Rc};
+
+/// Every seat buys in with more chips than it could ever lose in a single shoe, so the results
+/// are never cut short by a seat going broke.
+const BUY_IN: Chips = 1 << 48;
+
+/// The most rounds played at a shuffling machine before starting a fresh one.  A shuffling machine
+/// never stops for a reshuffle, so its games are broken up to keep each table's history short.
+/// A hand shuffled shoe is always played down to the cut card.
+const SESSION_ROUNDS: u64 = 50;
+
+/// Play the rounds in the config and collect the results of every seat.  The rounds are split
+/// across the config's threads and every thread plays its own shoes.  With a seed the same
+/// config on the same number of threads always deals the same cards.  A table which can not carry
+/// on stops the simulation with an error.
+///
+/// Example:
+/// ```
+/// use casino_sim::{ config::Config, simulate };
+///
+/// let config = Config {
+///     rounds: 200,
+///     threads: 2,
+///     ..Default::default()
+/// };
+/// let stats = simulate(&config).unwrap();
+///
+/// assert_eq!(200, stats.rounds);
+/// ```
+pub fn simulate(config: &Config) -> Result<Stats, SimError> {
+    let threads = config.threads.max(1) as u64;
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|thread| {
+                let rounds = config.rounds / threads + u64::from(thread < config.rounds % threads);
+                scope.spawn(move || simulate_rounds(config, thread, rounds))
+            })
+            .collect();
+
+        let mut stats: Stats = Default::default();
+        for worker in workers {
+            stats.merge(&worker.join().expect("A simulation thread panicked")?);
+        }
+        Ok(stats)
+    })
+}
+
+/// Play shoe after shoe until the given number of rounds have been played.
+fn simulate_rounds(config: &Config, thread: u64, rounds: u64) -> Result<Stats, SimError> {
+    let mut stats: Stats = Default::default();
+    let mut played = 0;
+    let mut shoes = 0;
+    while played < rounds {
+        let seed = match config.seed {
+            Some(seed) => seed.wrapping_add(thread << 32 | shoes),
+            None => thread_rng().gen(),
+        };
+        let rounds = rounds - played;
+        let table = config.table.clone();
+        played += match config.csm {
+            Some(buffer) => {
+                let rounds = rounds.min(SESSION_ROUNDS);
+                let csm = ContinuousShuffler::with_seed(config.table.decks, buffer, seed);
+                play_shoe(
+                    config,
+                    Rules::with_cards(table, csm, seed),
+                    rounds,
+                    &mut stats,
+                )?
+            }
+            None if config.stack.is_empty() => {
+                play_shoe(config, Rules::with_seed(table, seed), rounds, &mut stats)?
+            }
+            None => {
+                let mut shoe = Shoe::stacked_on_top(
+                    table.decks,
+                    config.stack.clone(),
+                    &mut seeded_rng(seed, 0),
+                )
+                .expect("The stacked cards are checked when the config is made");
+                shoe.place_cut_card(table.reshuffle_at());
+                play_shoe(
+                    config,
+                    Rules::with_cards(table, shoe, seed),
+                    rounds,
+                    &mut stats,
+                )?
+            }
+        };
+        shoes += 1;
+    }
+    Ok(stats)
+}
+
+/// Play through a single freshly shuffled shoe, or until enough rounds have been played.  This
+/// returns the number of rounds played, or why the table stopped before the shoe was finished.
+fn play_shoe<S: CardSource + 'static>(
+    config: &Config,
+    mut rules: Rules<S>,
+    rounds: u64,
+    stats: &mut Stats,
+) -> Result<u64, SimError> {
+    for player in 0..config.players {
+        rules.add_player().expect("Seats are added between games");
+        rules
+            .buy_in(player, BUY_IN)
+            .expect("Seats buy in between games");
+    }
+
+    let play: Box<dyn Strategy<S>> = match config.strategy {
+        StrategyKind::Basic => Box::new(BasicStrategy::new(config.bet)),
+        StrategyKind::Mimic => Box::new(MimicTheDealer { bet: config.bet }),
+    };
+    let running_count = Rc::new(Cell::new(0));
+    rules.set_strategy(Box::new(Spread {
+        play,
+        unit: config.bet,
+        running_count: running_count.clone(),
+        spread: config.spread.clone(),
+    }));
+
+    let mut played = 0;
+    while played < rounds {
+        let state = match rules.next() {
+            Some(state) => state,
+            None => break,
+        };
+        if state.progress == Progress::Done {
+            let payouts = rules.history().current_generation().events();
+            record(&state, payouts, config, stats);
+            running_count.set(running_count.get() + count_game(&state));
+            played += 1;
+        }
+    }
+
+    match rules.halted() {
+        None | Some(Halt::Reshuffle) => Ok(played),
+        Some(halt) => Err(SimError::Stopped(halt.to_string())),
+    }
+}
+
+/// Record the result of every seat in a finished game from the payouts which finished it.
+/// Insurance counts towards what a seat won or lost but not towards what it wagered.
+fn record<S>(state: &GameState<S>, payouts: &[Event], config: &Config, stats: &mut Stats) {
+    for (seat, place) in state.seats.iter().enumerate() {
+        let first = match place.hands.first() {
+            Some(hand) if place.is_betting() => hand,
+            _ => continue,
+        };
+        let initial_bet = if first.doubled {
+            first.bet / 2
+        } else {
+            first.bet
+        };
+        let insurance = state
+            .insurance
+            .get(seat)
+            .map_or(0, |insurance| insurance.chips());
+        let paid: Chips = payouts
+            .iter()
+            .map(|event| match *event {
+                Event::Payout {
+                    seat: paid, chips, ..
+                }
+                | Event::InsurancePaid { seat: paid, chips }
+                    if paid == seat =>
+                {
+                    chips
+                }
+                _ => 0,
+            })
+            .sum();
+        let staked = place.total_bet() + insurance;
+        stats.record(initial_bet, paid - staked, config.bet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{play_shoe, simulate, SESSION_ROUNDS};
+    use crate::{
+        config::{Config, StrategyKind},
+        stats::Stats,
+    };
+    use blackjack::prelude::{Rules, TableRules};
+    use cards::prelude::parse_cards;
+
+    #[test]
+    fn every_round_is_recorded() {
+        let config = Config {
+            rounds: 1_000,
+            threads: 3,
+            players: 2,
+            ..Default::default()
+        };
+        let stats = simulate(&config).unwrap();
+        assert_eq!(2_000, stats.rounds);
+        assert!(stats.wagered >= 2_000.0);
+    }
+
+    #[test]
+    fn seeded_runs_repeat() {
+        let config = Config {
+            rounds: 500,
+            threads: 2,
+            seed: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(simulate(&config), simulate(&config));
+    }
+
+    #[test]
+    fn shuffling_machines_play_every_round() {
+        let config = Config {
+            rounds: 1_000,
+            threads: 2,
+            csm: Some(20),
+            seed: Some(3),
+            ..Default::default()
+        };
+        let stats = simulate(&config).unwrap();
+        assert_eq!(1_000, stats.rounds);
+        assert_eq!(stats, simulate(&config).unwrap());
+    }
+
+    #[test]
+    fn hand_shuffled_shoes_are_played_to_the_cut_card() {
+        let config = Config {
+            table: TableRules::atlantic_city(),
+            ..Default::default()
+        };
+        let rules = Rules::with_seed(config.table.clone(), 9);
+        let played = play_shoe(&config, rules, u64::MAX, &mut Stats::default()).unwrap();
+        assert!(played > SESSION_ROUNDS, "{} rounds", played);
+    }
+
+    #[test]
+    fn stacked_shoes_start_every_shoe() {
+        let config = Config {
+            rounds: 2,
+            threads: 2,
+            strategy: StrategyKind::Mimic,
+            stack: parse_cards("As Kh Kd 7c").unwrap(),
+            ..Default::default()
+        };
+        let stats = simulate(&config).unwrap();
+        assert_eq!(2, stats.rounds);
+        assert_eq!(3.0, stats.net);
+    }
+
+    #[test]
+    fn mimic_the_dealer_loses() {
+        let config = Config {
+            rounds: 10_000,
+            threads: 2,
+            strategy: StrategyKind::Mimic,
+            ..Default::default()
+        };
+        let stats = simulate(&config).unwrap();
+        assert!(stats.house_edge() > 0.0, "{:?}", stats);
+    }
+
+    #[test]
+    fn spreads_raise_the_average_bet() {
+        let config = Config {
+            rounds: 2_000,
+            threads: 1,
+            spread: vec![(1, 4), (2, 8)],
+            ..Default::default()
+        };
+        let stats = simulate(&config).unwrap();
+        assert!(stats.wagered > stats.rounds as f64);
+    }
+
+    #[test]
+    fn crowded_tables_deal_through_the_discards() {
+        for args in [
+            "--decks 2 --players 7",
+            "--decks 6 --players 7 --penetration 1",
+            "--decks 1 --players 3 --penetration 1",
+        ] {
+            let args = format!("{} --rounds 300 --threads 1 --seed 2", args);
+            let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+            let stats = simulate(&config).unwrap_or_else(|error| panic!("{}: {}", args, error));
+            assert_eq!(300 * config.players as u64, stats.rounds, "{}", args);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use casino_sim::{config::Config, simulate};
+use std::time::Instant;
+
+fn main() {
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, casino_sim::config::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if config.help {
+        println!("{}", casino_sim::config::USAGE);
+        return;
+    }
+
+    let table = &config.table;
+    println!(
+        "{} decks, {}, {} blackjack, {:.0}% penetration",
+        table.decks,
+        if table.dealer_hits_soft_17 {
+            "H17"
+        } else {
+            "S17"
+        },
+        table.blackjack_payout,
+        table.penetration * 100.0
+    );
+    if let Some(buffer) = config.csm {
+        println!("Continuous shuffler with {} cards ready", buffer);
+    }
+    println!(
+        "{} rounds, {} seat(s), {:?} strategy, {} chip base bet on {} thread(s)",
+        config.rounds, config.players, config.strategy, config.bet, config.threads
+    );
+
+    let start = Instant::now();
+    let stats = match simulate(&config) {
+        Ok(stats) => stats,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    let elapsed = start.elapsed();
+
+    println!();
+    println!("Hands played:       {}", stats.rounds);
+    println!(
+        "Average bet:        {:.3} units",
+        stats.wagered / stats.rounds.max(1) as f64
+    );
+    println!(
+        "House edge:         {:.3}% ± {:.3}% (95% confidence)",
+        stats.house_edge() * 100.0,
+        stats.house_edge_margin() * 100.0
+    );
+    println!("Player result:      {:.4} units per hand", stats.mean());
+    println!("Standard deviation: {:.4} units per hand", stats.std_dev());
+    println!(
+        "Risk of ruin:       {:.2}% with {} units",
+        stats.risk_of_ruin(config.bankroll) * 100.0,
+        config.bankroll
+    );
+    println!(
+        "Time:               {:.2?} ({:.0} hands a second)",
+        elapsed,
+        stats.rounds as f64 / elapsed.as_secs_f64()
+    );
+}
@@ -0,0 +1,182 @@
+use player::Chips;
+
+/// The z score for a 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// Stats collects the result of every round a seat plays.  Results are kept in units of the base
+/// bet so different tables and bet sizes can be compared.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    /// The number of rounds played.
+    pub rounds: u64,
+    /// The sum of every initial bet, in units.
+    pub wagered: f64,
+    /// The sum of every win and loss, in units.
+    pub net: f64,
+    /// The sum of every win and loss squared, used for the variance.
+    pub net_squared: f64,
+}
+
+impl Stats {
+    /// Record a round.
+    ///
+    /// * `initial_bet`: The bet placed before the cards were dealt
+    /// * `net`: What the seat won, or lost when negative, once everything was paid
+    /// * `unit`: The size of a base bet
+    pub fn record(&mut self, initial_bet: Chips, net: Chips, unit: Chips) {
+        let unit = unit as f64;
+        let net = net as f64 / unit;
+
+        self.rounds += 1;
+        self.wagered += initial_bet as f64 / unit;
+        self.net += net;
+        self.net_squared += net * net;
+    }
+
+    /// Add the rounds from another set of stats.
+    pub fn merge(&mut self, other: &Stats) {
+        self.rounds += other.rounds;
+        self.wagered += other.wagered;
+        self.net += other.net;
+        self.net_squared += other.net_squared;
+    }
+
+    /// The average result of a round, in units.
+    pub fn mean(&self) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        self.net / self.rounds as f64
+    }
+
+    /// The standard deviation of a round, in units.
+    pub fn std_dev(&self) -> f64 {
+        if self.rounds < 2 {
+            return 0.0;
+        }
+        let n = self.rounds as f64;
+        let variance = (self.net_squared - self.net * self.net / n) / (n - 1.0);
+        variance.max(0.0).sqrt()
+    }
+
+    /// What the house keeps of every unit wagered.  A positive edge is good for the house.
+    pub fn house_edge(&self) -> f64 {
+        if self.wagered == 0.0 {
+            return 0.0;
+        }
+        -self.net / self.wagered
+    }
+
+    /// Half the width of the 95% confidence interval around the house edge.
+    pub fn house_edge_margin(&self) -> f64 {
+        if self.rounds == 0 || self.wagered == 0.0 {
+            return 0.0;
+        }
+        let average_bet = self.wagered / self.rounds as f64;
+        Z_95 * self.std_dev() / (self.rounds as f64).sqrt() / average_bet
+    }
+
+    /// The chance of losing the whole bankroll, given in units, playing forever.  A player
+    /// without an edge is always ruined in the end.
+    pub fn risk_of_ruin(&self, bankroll: f64) -> f64 {
+        let mean = self.mean();
+        let std_dev = self.std_dev();
+        if mean <= 0.0 || std_dev == 0.0 {
+            return 1.0;
+        }
+        (-2.0 * mean * bankroll / (std_dev * std_dev))
+            .exp()
+            .min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stats;
+
+    fn close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-9,
+            "expected {} got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn empty_stats() {
+        let stats: Stats = Default::default();
+        close(0.0, stats.mean());
+        close(0.0, stats.std_dev());
+        close(0.0, stats.house_edge());
+        close(1.0, stats.risk_of_ruin(100.0));
+    }
+
+    #[test]
+    fn house_edge_and_deviation() {
+        let mut stats: Stats = Default::default();
+        stats.record(10, 10, 10);
+        stats.record(10, -10, 10);
+        stats.record(10, -10, 10);
+        stats.record(20, 30, 10);
+
+        assert_eq!(4, stats.rounds);
+        close(5.0, stats.wagered);
+        close(2.0, stats.net);
+        close(-0.4, stats.house_edge());
+        close(0.5, stats.mean());
+        close((11.0_f64 / 3.0).sqrt(), stats.std_dev());
+    }
+
+    #[test]
+    fn merging_is_the_same_as_recording() {
+        let mut all: Stats = Default::default();
+        let mut first: Stats = Default::default();
+        let mut second: Stats = Default::default();
+        for (i, net) in [-10, 10, 15, -20, 0, 10].iter().enumerate() {
+            all.record(10, *net, 10);
+            if i % 2 == 0 {
+                first.record(10, *net, 10);
+            } else {
+                second.record(10, *net, 10);
+            }
+        }
+        first.merge(&second);
+        assert_eq!(all, first);
+    }
+
+    #[test]
+    fn risk_of_ruin() {
+        let mut stats: Stats = Default::default();
+        for _ in 0..50 {
+            stats.record(1, 1, 1);
+            stats.record(1, -1, 1);
+        }
+        stats.record(1, 2, 1);
+
+        let small = stats.risk_of_ruin(10.0);
+        let large = stats.risk_of_ruin(100.0);
+        assert!(small < 1.0);
+        assert!(large < small);
+
+        let mut losing: Stats = Default::default();
+        losing.record(1, -1, 1);
+        losing.record(1, 1, 1);
+        losing.record(1, -1, 1);
+        close(1.0, losing.risk_of_ruin(1000.0));
+    }
+
+    #[test]
+    fn margin_shrinks_with_more_rounds() {
+        let mut few: Stats = Default::default();
+        let mut many: Stats = Default::default();
+        for i in 0..10_000 {
+            let net = if i % 2 == 0 { 1 } else { -1 };
+            if i < 100 {
+                few.record(1, net, 1);
+            }
+            many.record(1, net, 1);
+        }
+        assert!(many.house_edge_margin() < few.house_edge_margin());
+    }
+}
@@ -3,6 +3,9 @@ use bevy::{
     prelude::*,
 };
 
+mod replay;
+pub use replay::ReplayPlugin;
+
 pub struct UIPlugin;
 
 impl Plugin for UIPlugin {
@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use blackjack::prelude::{Advice, Insurance, Playback};
+use cards::prelude::{Card, Suit};
+use player::{Hand, HandleCards};
+
+/// ReplayPlugin plays a recorded session back round by round.  The session comes from a
+/// `Playback` which has to be added to the app as a non send resource before the plugin.
+///
+/// * Space plays and pauses
+/// * Left and Right step back and forward one generation
+/// * Down and Up jump to the previous and next round
+/// * Home and End seek to the start and the end
+/// * M seeks to the next decision which was not basic strategy
+/// * Minus and Equals slow playback down and speed it up
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_replay)
+            .add_system(replay_controls)
+            .add_system(replay_clock)
+            .add_system(draw_table);
+    }
+}
+
+/// The text the whole table is drawn into.
+#[derive(Component)]
+struct TableText;
+
+/// The font every part of the table is written in.
+struct TableFont(Handle<Font>);
+
+const FONT_SIZE: f32 = 28.0;
+const TEXT: Color = Color::WHITE;
+const DIM: Color = Color::GRAY;
+const RED_SUIT: Color = Color::rgb(0.95, 0.3, 0.3);
+const CHIPS: Color = Color::GOLD;
+const GOOD: Color = Color::rgb(0.4, 0.9, 0.4);
+const MISTAKE: Color = Color::rgb(1.0, 0.4, 0.2);
+
+fn setup_replay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn_bundle(UiCameraBundle::default());
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(15.0),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::default(),
+            ..Default::default()
+        })
+        .insert(TableText);
+    commands.insert_resource(TableFont(asset_server.load("fonts/FiraCode-Medium.ttf")));
+}
+
+fn replay_controls(keys: Res<Input<KeyCode>>, mut playback: NonSendMut<Playback>) {
+    if keys.just_pressed(KeyCode::Space) {
+        playback.toggle();
+    }
+    if keys.just_pressed(KeyCode::Right) {
+        playback.pause();
+        playback.step_forward();
+    }
+    if keys.just_pressed(KeyCode::Left) {
+        playback.pause();
+        playback.step_back();
+    }
+    // Running off either end of the session leaves playback where it is.
+    if keys.just_pressed(KeyCode::Up) {
+        playback.next_round().ok();
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        playback.previous_round().ok();
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        playback.seek(0).ok();
+    }
+    if keys.just_pressed(KeyCode::End) {
+        let end = playback.len() - 1;
+        playback.seek(end).ok();
+    }
+    if keys.just_pressed(KeyCode::M) {
+        playback.pause();
+        playback.next_mistake().ok();
+    }
+    if keys.just_pressed(KeyCode::Equals) {
+        let speed = playback.speed() * 2.0;
+        playback.set_speed(speed);
+    }
+    if keys.just_pressed(KeyCode::Minus) {
+        let speed = playback.speed() / 2.0;
+        playback.set_speed(speed);
+    }
+}
+
+fn replay_clock(time: Res<Time>, mut playback: NonSendMut<Playback>) {
+    playback.tick(time.delta_seconds());
+}
+
+/// Write the table out as it is at the playback position: where playback is, the house, every
+/// seat with its player's chips and its hands, what just happened and what basic strategy thinks
+/// of it.
+fn draw_table(
+    playback: NonSend<Playback>,
+    font: Res<TableFont>,
+    mut query: Query<&mut Text, With<TableText>>,
+) {
+    let font = &font.0;
+    let section = |value: String, color: Color| TextSection {
+        value,
+        style: TextStyle {
+            font: font.clone(),
+            font_size: FONT_SIZE,
+            color,
+        },
+    };
+    let state = playback.state();
+    let mut sections = vec![section(
+        format!(
+            "Round {}/{}  Generation {}/{}  {} x{}\n\n",
+            playback.round(),
+            playback.number_of_rounds(),
+            playback.position(),
+            playback.len() - 1,
+            if playback.is_playing() {
+                "Playing"
+            } else {
+                "Paused"
+            },
+            playback.speed()
+        ),
+        DIM,
+    )];
+
+    sections.push(section("House  ".to_string(), TEXT));
+    sections.extend(hand_sections(&state.house.hand, &section));
+    sections.push(section(
+        format!("  {} chips\n\n", state.house.bankroll),
+        CHIPS,
+    ));
+
+    for (seat, place) in state.seats.iter().enumerate() {
+        sections.push(section(
+            format!("Seat {}  Player {}  ", seat, place.player),
+            TEXT,
+        ));
+        if let Some(player) = state.player_at(seat) {
+            sections.push(section(format!("{} chips", player.bankroll), CHIPS));
+        }
+        match state.insurance.get(seat) {
+            Some(Insurance::Declined) | None => {}
+            Some(insurance) => sections.push(section(format!("  {}", insurance), CHIPS)),
+        }
+        sections.push(section("\n".to_string(), TEXT));
+        for (index, hand) in place.hands.iter().enumerate() {
+            let marker = if state.current_seat() == Some(seat) && place.active == index {
+                "> "
+            } else {
+                "  "
+            };
+            sections.push(section(marker.to_string(), TEXT));
+            sections.extend(hand_sections(hand, &section));
+            if hand.bet > 0 {
+                sections.push(section(format!("  bet {}", hand.bet), CHIPS));
+            }
+            if let Some(outcome) = state.results.get(seat).and_then(|o| o.get(index)) {
+                sections.push(section(format!("  {}", outcome), TEXT));
+            }
+            sections.push(section("\n".to_string(), TEXT));
+        }
+        sections.push(section("\n".to_string(), TEXT));
+    }
+
+    for event in playback.events() {
+        sections.push(section(format!("{}\n", event), DIM));
+    }
+    if let Some(advice) = playback.advice() {
+        sections.push(advice_section(&advice, &section));
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections = sections.clone();
+    }
+}
+
+/// A hand's cards, red suits in red, followed by its value.
+fn hand_sections<F: Fn(String, Color) -> TextSection>(
+    hand: &Hand,
+    section: &F,
+) -> Vec<TextSection> {
+    let mut sections: Vec<TextSection> = hand
+        .cards()
+        .iter()
+        .map(|card| section(format!("{} ", card), card_color(card)))
+        .collect();
+    if !hand.cards().is_empty() {
+        sections.push(section(format!("({})", hand.value()), DIM));
+    }
+    sections
+}
+
+fn card_color(card: &Card) -> Color {
+    match card.suit() {
+        Suit::Hearts | Suit::Diamonds => RED_SUIT,
+        Suit::Clubs | Suit::Spades => TEXT,
+    }
+}
+
+/// Basic strategy's view of the decision the seat is about to make.
+fn advice_section<F: Fn(String, Color) -> TextSection>(
+    advice: &Advice,
+    section: &F,
+) -> TextSection {
+    if advice.is_mistake() {
+        section(
+            format!(
+                "\nSeat {} will {} but basic strategy says {}\n",
+                advice.seat,
+                advice.taken.to_string().to_lowercase(),
+                advice.advised.to_string().to_lowercase()
+            ),
+            MISTAKE,
+        )
+    } else {
+        section(
+            format!(
+                "\nSeat {} will {}, the basic strategy play\n",
+                advice.seat,
+                advice.taken.to_string().to_lowercase()
+            ),
+            GOOD,
+        )
+    }
+}